@@ -0,0 +1,241 @@
+//! Command-line parsing.
+
+use std::path::{Path, PathBuf};
+
+use dendron_core::export::ExportFormat;
+
+pub const USAGE: &str = "\
+Usage:
+  dendron connections list [--format table|json]
+  dendron query <connection> (-e <sql> | -f <file>) [--format table|csv|json|ndjson] [-y]
+  dendron describe <connection> <[schema.]table> [--format table|json]
+  dendron export <connection> (-e <sql> | -f <file>) -o <path> [--format <format>] [--table <name>] [-y]
+
+Options:
+  -e, --execute <sql>  SQL to run
+  -f, --file <file>    Read the SQL from a file, or stdin for '-'
+  -o, --output <path>  File to export to; the format follows its extension
+      --format <name>  Output format
+      --table <name>   Target table of a SQL export
+  -y, --yes            Run statements that need confirmation without asking
+  -h, --help           Show this help";
+
+#[derive(Debug)]
+pub enum Command {
+    Help,
+    ConnectionsList {
+        format: OutputFormat,
+    },
+    Query {
+        connection: String,
+        source: SqlSource,
+        format: OutputFormat,
+        yes: bool,
+    },
+    Describe {
+        connection: String,
+        table: String,
+        format: OutputFormat,
+    },
+    Export {
+        connection: String,
+        source: SqlSource,
+        output: PathBuf,
+        format: ExportFormat,
+        table: Option<String>,
+        yes: bool,
+    },
+}
+
+#[derive(Debug)]
+pub enum SqlSource {
+    Inline(String),
+    /// A file, or stdin for `-`.
+    File(PathBuf),
+}
+
+impl SqlSource {
+    pub fn read(&self) -> std::io::Result<String> {
+        match self {
+            SqlSource::Inline(sql) => Ok(sql.clone()),
+            SqlSource::File(path) if path.as_os_str() == "-" => std::io::read_to_string(std::io::stdin()),
+            SqlSource::File(path) => std::fs::read_to_string(path),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Csv,
+    Json,
+    Ndjson,
+}
+
+impl OutputFormat {
+    fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "table" => Ok(OutputFormat::Table),
+            "csv" => Ok(OutputFormat::Csv),
+            "json" => Ok(OutputFormat::Json),
+            "ndjson" | "jsonl" => Ok(OutputFormat::Ndjson),
+            _ => Err(format!("unknown output format '{name}'")),
+        }
+    }
+}
+
+fn parse_export_format(name: &str) -> Result<ExportFormat, String> {
+    match name {
+        "csv" => Ok(ExportFormat::Csv),
+        "json" => Ok(ExportFormat::Json),
+        "ndjson" | "jsonl" => Ok(ExportFormat::Ndjson),
+        "md" | "markdown" => Ok(ExportFormat::Markdown),
+        "html" | "htm" => Ok(ExportFormat::Html),
+        "xlsx" => Ok(ExportFormat::Xlsx),
+        "sql" => Ok(ExportFormat::Sql),
+        "parquet" => Ok(ExportFormat::Parquet),
+        "arrow" | "ipc" => Ok(ExportFormat::Arrow),
+        _ => Err(format!("unknown export format '{name}'")),
+    }
+}
+
+/// Export format implied by the output file's extension.
+fn format_from_extension(path: &Path) -> Result<ExportFormat, String> {
+    let ext = path.extension().and_then(|e| e.to_str()).map(str::to_lowercase);
+    match ext {
+        Some(ext) => parse_export_format(&ext)
+            .map_err(|_| format!("can't tell the export format from '.{ext}'; pass --format")),
+        None => Err("output has no extension; pass --format".to_string()),
+    }
+}
+
+/// Everything after the subcommand: positionals in order, plus flags.
+#[derive(Default)]
+struct Parsed {
+    positional: Vec<String>,
+    execute: Option<String>,
+    file: Option<PathBuf>,
+    output: Option<PathBuf>,
+    format: Option<String>,
+    table: Option<String>,
+    yes: bool,
+    help: bool,
+}
+
+impl Parsed {
+    fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Parsed::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            // `--flag=value` is the same as `--flag value`.
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+                _ => (arg.clone(), None),
+            };
+            let mut value = |name: &str| {
+                inline.clone().or_else(|| args.next()).ok_or_else(|| format!("{name} needs a value"))
+            };
+            match flag.as_str() {
+                "-e" | "--execute" => parsed.execute = Some(value(&flag)?),
+                "-f" | "--file" => parsed.file = Some(value(&flag)?.into()),
+                "-o" | "--output" => parsed.output = Some(value(&flag)?.into()),
+                "--format" => parsed.format = Some(value(&flag)?),
+                "--table" => parsed.table = Some(value(&flag)?),
+                "-y" | "--yes" => parsed.yes = true,
+                "-h" | "--help" => parsed.help = true,
+                "-" => parsed.positional.push(arg),
+                _ if flag.starts_with('-') => return Err(format!("unknown option '{flag}'")),
+                _ => parsed.positional.push(arg),
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn expect_positional(&self, names: &[&str]) -> Result<(), String> {
+        match self.positional.len().cmp(&names.len()) {
+            std::cmp::Ordering::Less => Err(format!("missing <{}>", names[self.positional.len()])),
+            std::cmp::Ordering::Greater => Err(format!("unexpected argument '{}'", self.positional[names.len()])),
+            std::cmp::Ordering::Equal => Ok(()),
+        }
+    }
+
+    fn source(&mut self) -> Result<SqlSource, String> {
+        match (self.execute.take(), self.file.take()) {
+            (Some(sql), None) => Ok(SqlSource::Inline(sql)),
+            (None, Some(path)) => Ok(SqlSource::File(path)),
+            (Some(_), Some(_)) => Err("pass either -e or -f, not both".to_string()),
+            (None, None) => Err("pass the SQL with -e or -f".to_string()),
+        }
+    }
+
+    fn output_format(&self, allowed: &[OutputFormat]) -> Result<OutputFormat, String> {
+        let Some(name) = &self.format else {
+            return Ok(OutputFormat::Table);
+        };
+        let format = OutputFormat::parse(name)?;
+        if allowed.contains(&format) {
+            Ok(format)
+        } else {
+            Err(format!("--format {name} isn't available here"))
+        }
+    }
+}
+
+/// Parse the arguments after the program name.
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.into_iter();
+    let Some(command) = args.next() else {
+        return Ok(Command::Help);
+    };
+    let mut parsed = Parsed::from_args(args)?;
+    if parsed.help || command == "help" || command == "-h" || command == "--help" {
+        return Ok(Command::Help);
+    }
+
+    use OutputFormat::*;
+    match command.as_str() {
+        "connections" => {
+            parsed.expect_positional(&["subcommand"]).map_err(|_| "expected 'connections list'".to_string())?;
+            if parsed.positional[0] != "list" {
+                return Err(format!("unknown connections subcommand '{}'", parsed.positional[0]));
+            }
+            Ok(Command::ConnectionsList { format: parsed.output_format(&[Table, Json])? })
+        }
+        "query" => {
+            parsed.expect_positional(&["connection"])?;
+            Ok(Command::Query {
+                source: parsed.source()?,
+                format: parsed.output_format(&[Table, Csv, Json, Ndjson])?,
+                connection: parsed.positional.remove(0),
+                yes: parsed.yes,
+            })
+        }
+        "describe" => {
+            parsed.expect_positional(&["connection", "table"])?;
+            let format = parsed.output_format(&[Table, Json])?;
+            let mut positional = parsed.positional.into_iter();
+            Ok(Command::Describe {
+                connection: positional.next().unwrap_or_default(),
+                table: positional.next().unwrap_or_default(),
+                format,
+            })
+        }
+        "export" => {
+            parsed.expect_positional(&["connection"])?;
+            let output = parsed.output.take().ok_or("pass the file to export to with -o")?;
+            let format = match &parsed.format {
+                Some(name) => parse_export_format(name)?,
+                None => format_from_extension(&output)?,
+            };
+            Ok(Command::Export {
+                source: parsed.source()?,
+                connection: parsed.positional.remove(0),
+                output,
+                format,
+                table: parsed.table.take(),
+                yes: parsed.yes,
+            })
+        }
+        _ => Err(format!("unknown command '{command}'")),
+    }
+}
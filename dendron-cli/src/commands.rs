@@ -0,0 +1,308 @@
+//! The subcommands, run against a headless [`Workspace`].
+
+use std::collections::HashMap;
+use std::io::{BufWriter, IsTerminal, Write};
+
+use dendron_core::config::SavedConnection;
+use dendron_core::export::{ExportFormat, ExportOptions, RowWriter};
+use dendron_core::query::{self, QueryType, SqlAnalyzer};
+use dendron_core::workspace::{OpenConnection, Workspace};
+use serde_json::json;
+
+use crate::args::{OutputFormat, SqlSource};
+use crate::table::{self, TableWriter};
+
+/// The CLI runs everything in a single tab of its own.
+pub const CLI_TAB: u32 = 0;
+
+/// Error for a command stopped with Ctrl-C.
+pub const CANCELLED: &str = "cancelled";
+
+type CmdResult = Result<(), String>;
+
+// ── connections list ─────────────────────────────────────────────────────────
+
+/// Where a saved connection points, without its credentials.
+fn connection_target(saved: &SavedConnection) -> String {
+    match saved {
+        SavedConnection::Sqlite { path, .. } => path.clone(),
+        SavedConnection::Postgres { host, port, username, database, ssh, .. } => {
+            let target = format!("{username}@{host}:{port}/{database}");
+            match ssh {
+                Some(ssh) => format!("{target} via ssh {}@{}", ssh.username, ssh.host),
+                None => target,
+            }
+        }
+    }
+}
+
+fn connection_type(saved: &SavedConnection) -> &'static str {
+    match saved {
+        SavedConnection::Sqlite { .. } => "sqlite",
+        SavedConnection::Postgres { .. } => "postgres",
+    }
+}
+
+pub async fn connections_list(ws: &Workspace, format: OutputFormat) -> CmdResult {
+    let connections = ws.config.lock().await.connections.clone();
+    match format {
+        OutputFormat::Json => {
+            let list: Vec<_> = connections.iter()
+                .map(|c| json!({
+                    "name": c.name(),
+                    "type": connection_type(c),
+                    "target": connection_target(c),
+                    "tags": c.tags(),
+                    "read_only": c.is_read_only(),
+                    "dangerous": c.is_dangerous(),
+                }))
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&list).map_err(|e| e.to_string())?);
+        }
+        _ => {
+            let headers = ["name", "type", "target", "tags", "flags"].map(String::from);
+            let rows: Vec<Vec<String>> = connections.iter()
+                .map(|c| {
+                    let mut flags = Vec::new();
+                    if c.is_read_only() {
+                        flags.push("read-only");
+                    }
+                    if c.is_dangerous() {
+                        flags.push("dangerous");
+                    }
+                    vec![
+                        c.name().to_string(),
+                        connection_type(c).to_string(),
+                        connection_target(c),
+                        c.tags().join(", "),
+                        flags.join(", "),
+                    ]
+                })
+                .collect();
+            print!("{}", table::render(&headers, &rows));
+        }
+    }
+    Ok(())
+}
+
+// ── query ────────────────────────────────────────────────────────────────────
+
+async fn open(ws: &Workspace, connection: &str) -> Result<std::sync::Arc<OpenConnection>, String> {
+    ws.set_tab_connection(CLI_TAB, Some(connection.to_string())).await;
+    ws.open_connection(connection).await.map_err(|e| e.to_string())
+}
+
+fn read_sql(source: &SqlSource) -> Result<String, String> {
+    source.read().map_err(|e| format!("can't read SQL: {e}"))
+}
+
+/// Prompt on stderr and read a line from stdin. Ctrl-C gives up.
+async fn ask(prompt: &str) -> Result<String, String> {
+    eprint!("{prompt}");
+    std::io::stderr().flush().map_err(|e| e.to_string())?;
+    // A plain thread rather than `spawn_blocking`: after Ctrl-C the read is
+    // abandoned, and the runtime would otherwise wait for it on shutdown.
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    std::thread::spawn(move || {
+        let mut answer = String::new();
+        let _ = tx.send(std::io::stdin().read_line(&mut answer).map(|_| answer));
+    });
+    tokio::select! {
+        answer = rx => match answer {
+            Ok(answer) => Ok(answer.map_err(|e| e.to_string())?.trim().to_string()),
+            Err(_) => Err(CANCELLED.to_string()),
+        },
+        _ = tokio::signal::ctrl_c() => Err(CANCELLED.to_string()),
+    }
+}
+
+/// Ask before running `sql` where the app would show its confirmation
+/// dialog, returning what was typed when the policy asks for the database
+/// name. Statements the environment policy forbids are refused outright;
+/// `yes` skips the question but not the policy.
+async fn confirm(ws: &Workspace, sql: &str, yes: bool) -> Result<Option<String>, String> {
+    let safety = ws.check_query_safety(CLI_TAB, sql).await;
+    if safety.is_blocked() {
+        return Err(safety.violations.join("\n"));
+    }
+    if !safety.requires_confirmation || (yes && safety.typed_confirmation.is_none()) {
+        return Ok(None);
+    }
+    if !std::io::stdin().is_terminal() {
+        return Err(match safety.typed_confirmation {
+            Some(_) => format!(
+                "the policy for '{}' requires typing the database name; run this from a terminal",
+                safety.connection_name,
+            ),
+            None => format!(
+                "{} needs confirmation on '{}'; re-run with --yes",
+                format!("{:?}", safety.query_type).to_uppercase(),
+                safety.connection_name,
+            ),
+        });
+    }
+
+    eprintln!("{}", safety.warning_message());
+    if matches!(safety.query_type, QueryType::Update | QueryType::Delete) {
+        if let Ok(preview) = ws.dry_run_query(CLI_TAB, sql, 0, &HashMap::new()).await {
+            let n = preview.rows_affected;
+            eprintln!("This will {} {n} row{}.", format!("{:?}", safety.query_type).to_lowercase(), if n == 1 { "" } else { "s" });
+        }
+    }
+
+    let typed = safety.typed_confirmation.clone().or_else(|| {
+        matches!(safety.query_type, QueryType::Drop | QueryType::Truncate)
+            .then(|| safety.connection_name.clone())
+    });
+    let confirmed = match &typed {
+        Some(expected) => ask(&format!("Type '{expected}' to continue: ")).await? == *expected,
+        None => matches!(ask("Continue? [y/N] ").await?.to_lowercase().as_str(), "y" | "yes"),
+    };
+    if confirmed {
+        Ok(typed)
+    } else {
+        Err("aborted".to_string())
+    }
+}
+
+fn stdout_writer(format: OutputFormat, postgres: bool) -> Box<dyn RowWriter + Send> {
+    let out = BufWriter::new(std::io::stdout());
+    let options = ExportOptions { postgres, ..Default::default() };
+    match format {
+        OutputFormat::Table => Box::new(TableWriter::new(out)),
+        OutputFormat::Csv => ExportFormat::Csv.writer(out, &options),
+        OutputFormat::Json => ExportFormat::Json.writer(out, &options),
+        OutputFormat::Ndjson => ExportFormat::Ndjson.writer(out, &options),
+    }
+}
+
+/// Run every statement in `source` in order, stopping at the first error.
+/// SELECT results go to stdout in `format`; row counts go to stderr.
+pub async fn query(ws: &Workspace, connection: &str, source: &SqlSource, format: OutputFormat, yes: bool) -> CmdResult {
+    let sql = read_sql(source)?;
+    let open = open(ws, connection).await?;
+    let statements = query::split_statements(&sql, open.conn.dialect());
+    if statements.is_empty() {
+        return Err("no SQL to run".to_string());
+    }
+
+    // Every statement is confirmed before any of them runs.
+    let mut confirmations = Vec::with_capacity(statements.len());
+    for statement in &statements {
+        confirmations.push(confirm(ws, &statement.text, yes).await?);
+    }
+
+    let params = HashMap::new();
+    for (statement, confirmation) in statements.iter().zip(&confirmations) {
+        if SqlAnalyzer::new(open.conn.dialect(), &statement.text).is_read_only() {
+            let mut writer = stdout_writer(format, open.conn.is_postgres());
+            ws.stream_query(CLI_TAB, &statement.text, &params, writer.as_mut())
+                .await
+                .map_err(|e| e.to_string())?;
+        } else {
+            let result = ws.execute_query(CLI_TAB, &statement.text, 0, &params, confirmation.as_deref())
+                .await
+                .map_err(|e| e.to_string())?;
+            let n = result.affected_rows.unwrap_or(0);
+            eprintln!("{n} row{} affected", if n == 1 { "" } else { "s" });
+        }
+    }
+    Ok(())
+}
+
+// ── describe ─────────────────────────────────────────────────────────────────
+
+pub async fn describe(ws: &Workspace, connection: &str, table: &str, format: OutputFormat) -> CmdResult {
+    let open = open(ws, connection).await?;
+    let (schema, table) = match table.split_once('.') {
+        Some((schema, table)) => (schema, table),
+        None if open.conn.is_postgres() => ("public", table),
+        None => ("main", table),
+    };
+    let structure = open.conn.describe_table(schema, table).await.map_err(|e| e.to_string())?;
+    if structure.columns.is_empty() {
+        return Err(format!("table '{schema}.{table}' not found"));
+    }
+
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&structure).map_err(|e| e.to_string())?);
+        return Ok(());
+    }
+
+    let headers = ["column", "type", "nullable", "default", "key"].map(String::from);
+    let rows: Vec<Vec<String>> = structure.columns.iter()
+        .map(|c| vec![
+            c.name.clone(),
+            c.data_type.clone(),
+            if c.is_nullable { "yes" } else { "no" }.to_string(),
+            c.default_value.clone().unwrap_or_default(),
+            if c.is_primary_key { "PK" } else { "" }.to_string(),
+        ])
+        .collect();
+    print!("{}", table::render(&headers, &rows));
+
+    if !structure.indexes.is_empty() {
+        println!("\nIndexes:");
+        for index in &structure.indexes {
+            let kind = if index.is_primary { " PRIMARY KEY" } else if index.is_unique { " UNIQUE" } else { "" };
+            println!("  {}{kind} ({})", index.name, index.columns.join(", "));
+        }
+    }
+    if !structure.foreign_keys.is_empty() {
+        println!("\nForeign keys:");
+        for fk in &structure.foreign_keys {
+            println!(
+                "  {} ({}) REFERENCES {} ({})",
+                fk.name,
+                fk.columns.join(", "),
+                fk.referenced_table,
+                fk.referenced_columns.join(", "),
+            );
+        }
+    }
+    Ok(())
+}
+
+// ── export ───────────────────────────────────────────────────────────────────
+
+/// Stream every row of a single SELECT into `output`.
+pub async fn export(
+    ws: &Workspace,
+    connection: &str,
+    source: &SqlSource,
+    output: &std::path::Path,
+    format: ExportFormat,
+    table: Option<String>,
+    yes: bool,
+) -> CmdResult {
+    let sql = read_sql(source)?;
+    let open = open(ws, connection).await?;
+    let statements = query::split_statements(&sql, open.conn.dialect());
+    let [statement] = statements.as_slice() else {
+        return Err("export runs exactly one SELECT statement".to_string());
+    };
+    confirm(ws, &statement.text, yes).await?;
+
+    let mut options = ExportOptions::default();
+    if let Some(table) = table {
+        options.table = table;
+    }
+    let show_progress = std::io::stderr().is_terminal();
+    let summary = ws.export_query_to_file(CLI_TAB, &statement.text, output, format, &options, |progress| {
+        if show_progress {
+            eprint!("\r{} rows written", progress.rows_written);
+        }
+    }).await.map_err(|e| e.to_string())?;
+
+    if show_progress {
+        eprint!("\r");
+    }
+    eprintln!(
+        "{} rows, {} bytes written to {} in {} ms",
+        summary.rows_written,
+        summary.bytes_written,
+        output.display(),
+        summary.execution_time_ms,
+    );
+    Ok(())
+}
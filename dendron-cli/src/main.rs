@@ -0,0 +1,73 @@
+//! `dendron`: saved connections from the command line.
+//!
+//! Runs on the same [`Workspace`] as the desktop app, so connections, team
+//! policy, confirmation rules, audit and history all carry over.
+
+mod args;
+mod commands;
+mod table;
+
+use std::process::ExitCode;
+use std::time::Duration;
+
+use dendron_core::error::AppError;
+use dendron_core::workspace::Workspace;
+
+use args::{Command, USAGE};
+
+/// How long a cancelled command gets to clean up after Ctrl-C.
+const CANCEL_GRACE: Duration = Duration::from_secs(5);
+
+async fn run(ws: &Workspace, command: Command) -> Result<(), String> {
+    match command {
+        Command::Help => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        Command::ConnectionsList { format } => commands::connections_list(ws, format).await,
+        Command::Query { connection, source, format, yes } => {
+            commands::query(ws, &connection, &source, format, yes).await
+        }
+        Command::Describe { connection, table, format } => {
+            commands::describe(ws, &connection, &table, format).await
+        }
+        Command::Export { connection, source, output, format, table, yes } => {
+            commands::export(ws, &connection, &source, &output, format, table, yes).await
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let command = match args::parse(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("dendron: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let ws = Workspace::load_headless();
+    let run = run(&ws, command);
+    tokio::pin!(run);
+    let result = tokio::select! {
+        result = &mut run => result,
+        _ = tokio::signal::ctrl_c() => {
+            // Cancelling lets the running statement roll back and a partial
+            // export be removed; anything slower to stop is just dropped.
+            ws.cancel_query(commands::CLI_TAB).await;
+            tokio::time::timeout(CANCEL_GRACE, run).await.unwrap_or(Err(commands::CANCELLED.to_string()))
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) if e == commands::CANCELLED || e == AppError::QueryCancelled.to_string() => {
+            eprintln!("dendron: cancelled");
+            ExitCode::from(130)
+        }
+        Err(e) => {
+            eprintln!("dendron: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
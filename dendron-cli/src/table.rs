@@ -0,0 +1,93 @@
+//! Aligned plain-text tables, in the style of psql.
+
+use std::io::Write;
+
+use dendron_core::error::Result;
+use dendron_core::export::RowWriter;
+
+/// Cells wider than this are cut short with an ellipsis.
+const MAX_CELL_WIDTH: usize = 80;
+
+/// Render `rows` under `headers` as an aligned table with a row count footer.
+pub fn render(headers: &[String], rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = (0..headers.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row.get(i).map_or(0, |c| c.chars().count()))
+                .chain(std::iter::once(headers[i].chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let line = |cells: &[String]| {
+        let padded: Vec<String> = cells.iter().zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        format!(" {}", padded.join(" | ")).trim_end().to_string()
+    };
+
+    let mut out = String::new();
+    out.push_str(&line(headers));
+    out.push('\n');
+    let rule: Vec<String> = widths.iter().map(|w| "-".repeat(w + 2)).collect();
+    out.push_str(&rule.join("+"));
+    out.push('\n');
+    for row in rows {
+        out.push_str(&line(row));
+        out.push('\n');
+    }
+    match rows.len() {
+        1 => out.push_str("(1 row)\n"),
+        n => out.push_str(&format!("({n} rows)\n")),
+    }
+    out
+}
+
+/// One cell as it's shown: NULL spelled out, control characters escaped so a
+/// row stays on one line, and long values shortened.
+fn display_cell(cell: Option<&str>) -> String {
+    let Some(cell) = cell else {
+        return "NULL".to_string();
+    };
+    let escaped = cell.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r").replace('\t', "\\t");
+    if escaped.chars().count() > MAX_CELL_WIDTH {
+        let mut short: String = escaped.chars().take(MAX_CELL_WIDTH - 1).collect();
+        short.push('…');
+        short
+    } else {
+        escaped
+    }
+}
+
+/// [`RowWriter`] for the terminal. Rows are held until the end, since column
+/// widths depend on all of them.
+pub struct TableWriter<W: Write> {
+    out: W,
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl<W: Write> TableWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self { out, columns: Vec::new(), rows: Vec::new() }
+    }
+}
+
+impl<W: Write> RowWriter for TableWriter<W> {
+    fn begin(&mut self, columns: &[String], _column_types: &[String]) -> Result<()> {
+        self.columns = columns.to_vec();
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[Option<String>]) -> Result<()> {
+        self.rows.push(row.iter().map(|cell| display_cell(cell.as_deref())).collect());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.out.write_all(render(&self.columns, &self.rows).as_bytes())?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+//! Append-only audit log of statements run against dangerous connections.
+//!
+//! One JSON object per line in `audit.log` next to the config file. Entries
+//! are only ever appended, each with a single write, so a crash can at worst
+//! leave a truncated last line, which readers skip.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::Utc;
+
+use crate::config::Config;
+use crate::error::{AppError, Result};
+use crate::query::QueryType;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// UTC, `YYYY-MM-DDTHH:MM:SS.mmmZ`, so entries sort and filter as text.
+    pub timestamp: String,
+    pub connection: String,
+    pub tags: Vec<String>,
+    pub os_user: String,
+    pub statement: String,
+    pub query_type: QueryType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub affected_rows: Option<u64>,
+    pub duration_ms: u128,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// An entry being timed; call [`finish`](Self::finish) when the statement returns.
+pub struct PendingAudit {
+    entry: AuditEntry,
+    started: Instant,
+}
+
+impl AuditEntry {
+    /// Start timing `statement` on `connection`.
+    pub fn start(connection: &str, tags: &[String], statement: &str, query_type: QueryType) -> PendingAudit {
+        PendingAudit {
+            entry: AuditEntry {
+                timestamp: now_timestamp(),
+                connection: connection.to_string(),
+                tags: tags.to_vec(),
+                os_user: os_user(),
+                statement: statement.to_string(),
+                query_type,
+                affected_rows: None,
+                duration_ms: 0,
+                success: false,
+                error: None,
+            },
+            started: Instant::now(),
+        }
+    }
+}
+
+impl PendingAudit {
+    /// Complete the entry with the statement's outcome.
+    pub fn finish(mut self, outcome: std::result::Result<Option<u64>, &str>) -> AuditEntry {
+        self.entry.duration_ms = self.started.elapsed().as_millis();
+        match outcome {
+            Ok(rows) => {
+                self.entry.success = true;
+                self.entry.affected_rows = rows;
+            }
+            Err(e) => self.entry.error = Some(e.to_string()),
+        }
+        self.entry
+    }
+}
+
+/// Which entries to return; every set field must match.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditFilter {
+    #[serde(default)]
+    pub connection: Option<String>,
+    #[serde(default)]
+    pub query_type: Option<QueryType>,
+    /// Inclusive lower bound, compared as text (`2024-05-01` works).
+    #[serde(default)]
+    pub since: Option<String>,
+    /// Exclusive upper bound, compared as text.
+    #[serde(default)]
+    pub until: Option<String>,
+    /// Case-insensitive substring of the statement.
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub errors_only: bool,
+    /// Keep only the newest `limit` matches.
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl AuditFilter {
+    fn matches(&self, e: &AuditEntry) -> bool {
+        self.connection.as_ref().is_none_or(|c| &e.connection == c)
+            && self.query_type.as_ref().is_none_or(|t| &e.query_type == t)
+            && self.since.as_ref().is_none_or(|s| e.timestamp.as_str() >= s.as_str())
+            && self.until.as_ref().is_none_or(|u| e.timestamp.as_str() < u.as_str())
+            && self.text.as_ref().is_none_or(|t| e.statement.to_lowercase().contains(&t.to_lowercase()))
+            && (!self.errors_only || !e.success)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditExportFormat {
+    Ndjson,
+    Csv,
+}
+
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// `audit.log` in the config directory.
+    pub fn open_default() -> Option<Self> {
+        Config::config_dir().map(|dir| Self::new(dir.join("audit.log")))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, entry: &AuditEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(&line)?;
+        Ok(())
+    }
+
+    /// Matching entries, oldest first.
+    pub fn read(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>> {
+        let file = match File::open(&self.path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut entries = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            // Torn or hand-edited lines are skipped rather than failing the read.
+            let Ok(entry) = serde_json::from_str::<AuditEntry>(&line) else { continue };
+            if filter.matches(&entry) {
+                entries.push(entry);
+            }
+        }
+        if let Some(limit) = filter.limit {
+            let skip = entries.len().saturating_sub(limit);
+            entries.drain(..skip);
+        }
+        Ok(entries)
+    }
+
+    /// Write matching entries to `path`. Returns how many were written.
+    pub fn export(&self, filter: &AuditFilter, format: AuditExportFormat, path: &Path) -> Result<usize> {
+        let entries = self.read(filter)?;
+        let file = File::create(path)
+            .map_err(|e| AppError::FileWriteFailed(path.display().to_string(), e.to_string()))?;
+        match format {
+            AuditExportFormat::Ndjson => {
+                let mut out = std::io::BufWriter::new(file);
+                for entry in &entries {
+                    serde_json::to_writer(&mut out, entry)?;
+                    out.write_all(b"\n")?;
+                }
+                out.flush()?;
+            }
+            AuditExportFormat::Csv => {
+                let mut out = csv::Writer::from_writer(file);
+                out.write_record([
+                    "timestamp", "connection", "tags", "os_user", "statement", "query_type",
+                    "affected_rows", "duration_ms", "success", "error",
+                ])?;
+                for e in &entries {
+                    out.write_record([
+                        e.timestamp.clone(),
+                        e.connection.clone(),
+                        e.tags.join(","),
+                        e.os_user.clone(),
+                        e.statement.clone(),
+                        format!("{:?}", e.query_type).to_uppercase(),
+                        e.affected_rows.map(|n| n.to_string()).unwrap_or_default(),
+                        e.duration_ms.to_string(),
+                        e.success.to_string(),
+                        e.error.clone().unwrap_or_default(),
+                    ])?;
+                }
+                out.flush()?;
+            }
+        }
+        Ok(entries.len())
+    }
+}
+
+/// Current UTC time as `YYYY-MM-DDTHH:MM:SS.mmmZ`, which sorts as text.
+pub(crate) fn now_timestamp() -> String {
+    Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+}
+
+/// Login name of the user running the app.
+fn os_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
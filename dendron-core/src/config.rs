@@ -1,8 +1,11 @@
 //! Application configuration
 
 use crate::error::Result;
+use crate::format::SqlFormatOptions;
+use crate::params::ParamValue;
 use crate::security::EncryptedPassword;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -12,18 +15,56 @@ pub struct Config {
     pub settings: Settings,
     #[serde(default)]
     pub last_connection: Option<String>,
+    /// Pre-database history, moved into the history store on first read.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub query_history: Vec<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub saved_queries: Vec<SavedQuery>,
+    /// Last parameter values for project queries, by file path.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub project_query_params: HashMap<String, HashMap<String, ParamValue>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SavedQuery {
     pub name: String,
     pub query: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub connection: Option<String>,
+    /// Slash-separated, e.g. `reports/monthly`; None at the top level.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Values last used for its parameters, by parameter name.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub params: HashMap<String, ParamValue>,
+}
+
+impl SavedQuery {
+    /// `folder/name`, unique among saved queries.
+    pub fn path(&self) -> String {
+        match &self.folder {
+            Some(folder) => format!("{}/{}", folder, self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let lower = tag.to_lowercase();
+        self.tags.iter().any(|t| t.to_lowercase() == lower)
+    }
+}
+
+/// `a//b/` → `a/b`; empty → None.
+pub fn normalize_folder(folder: &str) -> Option<String> {
+    let parts: Vec<&str> = folder.split(['/', '\\'])
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("/"))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -61,6 +102,12 @@ pub enum SavedConnection {
         path: String,
         #[serde(default, skip_serializing_if = "Vec::is_empty")]
         tags: Vec<String>,
+        /// Open the database read-only; writes fail in the session.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        read_only: bool,
+        /// Let MCP clients run statements that change data or schema.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        mcp_writes: bool,
     },
     #[serde(rename = "postgres")]
     Postgres {
@@ -77,6 +124,12 @@ pub enum SavedConnection {
         tags: Vec<String>,
         #[serde(default, skip_serializing_if = "Option::is_none")]
         ssh: Option<SshConfig>,
+        /// Open every session read-only; writes fail in the session.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        read_only: bool,
+        /// Let MCP clients run statements that change data or schema.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        mcp_writes: bool,
     },
 }
 
@@ -109,6 +162,21 @@ impl SavedConnection {
         })
     }
 
+    pub fn is_read_only(&self) -> bool {
+        match self {
+            SavedConnection::Sqlite { read_only, .. } => *read_only,
+            SavedConnection::Postgres { read_only, .. } => *read_only,
+        }
+    }
+
+    /// Whether MCP clients may run more than reads on this connection.
+    pub fn allows_mcp_writes(&self) -> bool {
+        match self {
+            SavedConnection::Sqlite { mcp_writes, .. } => *mcp_writes,
+            SavedConnection::Postgres { mcp_writes, .. } => *mcp_writes,
+        }
+    }
+
     pub fn has_tag(&self, tag: &str) -> bool {
         let lower = tag.to_lowercase();
         self.tags().iter().any(|t| t.to_lowercase() == lower)
@@ -166,6 +234,8 @@ pub struct Settings {
     pub show_tree: bool,
     #[serde(default)]
     pub theme_name: Option<String>,
+    #[serde(default)]
+    pub formatter: SqlFormatOptions,
 }
 
 impl Default for Settings {
@@ -175,6 +245,7 @@ impl Default for Settings {
             editor_height: 160.0,
             show_tree: true,
             theme_name: None,
+            formatter: SqlFormatOptions::default(),
         }
     }
 }
@@ -217,4 +288,64 @@ impl Config {
     pub fn remove_connection(&mut self, name: &str) {
         self.connections.retain(|c| c.name() != name);
     }
+
+    /// Add or update a saved query. With `previous_path`, the query stored
+    /// there is removed first, so renames and folder moves don't leave a copy.
+    /// A different query already at the new path is only replaced with
+    /// `overwrite`. When `query` has no parameter values, the stored ones are
+    /// kept. Returns the query's normalized path.
+    pub fn save_query(
+        &mut self,
+        mut query: SavedQuery,
+        previous_path: Option<&str>,
+        overwrite: bool,
+    ) -> Result<String> {
+        query.name = query.name.trim().to_string();
+        if query.name.is_empty() || query.name.contains(['/', '\\']) {
+            return Err("Query name must be non-empty and contain no slashes".into());
+        }
+        query.folder = query.folder.as_deref().and_then(normalize_folder);
+        let path = query.path();
+        if !overwrite && previous_path != Some(path.as_str()) && self.saved_query(&path).is_some() {
+            return Err(format!("A saved query named '{path}' already exists").into());
+        }
+        if query.params.is_empty() {
+            let stored = previous_path
+                .and_then(|previous| self.saved_query(previous))
+                .or_else(|| self.saved_query(&path));
+            if let Some(stored) = stored {
+                query.params = stored.params.clone();
+            }
+        }
+        if let Some(previous) = previous_path {
+            self.remove_saved_query(previous);
+        }
+        self.saved_queries.retain(|q| q.path() != path);
+        self.saved_queries.push(query);
+        Ok(path)
+    }
+
+    pub fn remove_saved_query(&mut self, path: &str) {
+        self.saved_queries.retain(|q| q.path() != path);
+    }
+
+    pub fn saved_query(&self, path: &str) -> Option<&SavedQuery> {
+        self.saved_queries.iter().find(|q| q.path() == path)
+    }
+
+    /// Remembered parameter values for a personal query's path or a project
+    /// query's file.
+    pub fn remembered_params(&self, key: &str) -> Option<&HashMap<String, ParamValue>> {
+        match self.saved_query(key) {
+            Some(query) => Some(&query.params),
+            None => self.project_query_params.get(key),
+        }
+    }
+
+    pub fn remember_params(&mut self, key: &str, params: HashMap<String, ParamValue>) {
+        match self.saved_queries.iter_mut().find(|q| q.path() == key) {
+            Some(query) => query.params = params,
+            None => { self.project_query_params.insert(key.to_string(), params); }
+        }
+    }
 }
@@ -0,0 +1,229 @@
+//! Batched inserts into a single table inside one transaction.
+//!
+//! Values arrive as text (`None` = NULL), the same shape
+//! [`DbRow::decode_lossless`] produces, so anything that can produce rows —
+//! another connection, a CSV file, a JSON dump — can feed the same pipeline.
+//!
+//! [`DbRow::decode_lossless`]: super::postgres::DbRow::decode_lossless
+
+use sqlx::{Postgres, Sqlite, Transaction};
+
+use crate::error::Result;
+use crate::query::quote_ident;
+use super::types::ColumnKind;
+use super::DatabaseConnection;
+
+/// SQLite's historical default for SQLITE_MAX_VARIABLE_NUMBER.
+const SQLITE_MAX_PARAMS: usize = 999;
+
+enum BulkTransaction {
+    Postgres(Transaction<'static, Postgres>),
+    Sqlite(Transaction<'static, Sqlite>),
+}
+
+/// An open transaction that appends rows to `schema.table`.
+///
+/// Dropping without [`commit`](Self::commit) rolls everything back.
+pub struct BulkInserter {
+    tx: BulkTransaction,
+    target: String,
+    columns: Vec<String>,
+    kinds: Vec<ColumnKind>,
+}
+
+/// Fully qualified, quoted table name.
+pub fn qualified_name(schema: &str, table: &str) -> String {
+    format!("{}.{}", quote_ident(schema), quote_ident(table))
+}
+
+/// CREATE TABLE statement for `columns` in the given backend's types.
+pub fn create_table_sql(schema: &str, table: &str, columns: &[(String, ColumnKind)], postgres: bool) -> String {
+    let cols = columns.iter()
+        .map(|(name, kind)| format!("{} {}", quote_ident(name), kind.sql_type(postgres)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("CREATE TABLE {} ({})", qualified_name(schema, table), cols)
+}
+
+impl BulkInserter {
+    /// Begin a transaction on `conn` that will insert into the named columns.
+    /// `columns` carries the destination kind of each column.
+    pub async fn begin(
+        conn: &DatabaseConnection,
+        schema: &str,
+        table: &str,
+        columns: Vec<(String, ColumnKind)>,
+    ) -> Result<Self> {
+        if columns.is_empty() {
+            return Err("No columns to insert".into());
+        }
+        let tx = match conn {
+            DatabaseConnection::Postgres(pool) => BulkTransaction::Postgres(pool.begin().await?),
+            DatabaseConnection::Sqlite(pool) => BulkTransaction::Sqlite(pool.begin().await?),
+        };
+        let (columns, kinds) = columns.into_iter().unzip();
+        Ok(Self { tx, target: qualified_name(schema, table), columns, kinds })
+    }
+
+    /// Run a statement (typically CREATE TABLE) inside the same transaction.
+    pub async fn execute(&mut self, sql: &str) -> Result<()> {
+        match &mut self.tx {
+            BulkTransaction::Postgres(tx) => { sqlx::query(sql).execute(&mut **tx).await?; }
+            BulkTransaction::Sqlite(tx) => { sqlx::query(sql).execute(&mut **tx).await?; }
+        }
+        Ok(())
+    }
+
+    /// Insert one batch. Each row must have one cell per column.
+    pub async fn insert(&mut self, rows: &[Vec<Option<String>>]) -> Result<u64> {
+        if rows.is_empty() {
+            return Ok(0);
+        }
+        self.check_row_widths(rows)?;
+
+        let col_list = self.columns.iter().map(|c| quote_ident(c)).collect::<Vec<_>>().join(", ");
+
+        match &mut self.tx {
+            BulkTransaction::Postgres(tx) => {
+                // One jsonb parameter per batch: Postgres coerces each field
+                // through the column's input function, so text values land in
+                // integer/timestamp/uuid/bytea columns without per-type casts,
+                // and there is no bind-parameter limit to chunk around.
+                let records: Vec<serde_json::Value> = rows.iter().map(|row| {
+                    let mut map = serde_json::Map::new();
+                    for ((name, kind), value) in self.columns.iter().zip(&self.kinds).zip(row) {
+                        let v = match value {
+                            None => serde_json::Value::Null,
+                            Some(s) if *kind == ColumnKind::Json => serde_json::from_str(s)
+                                .unwrap_or_else(|_| serde_json::Value::String(s.clone())),
+                            Some(s) => serde_json::Value::String(s.clone()),
+                        };
+                        map.insert(name.clone(), v);
+                    }
+                    serde_json::Value::Object(map)
+                }).collect();
+
+                let sql = format!(
+                    "INSERT INTO {target} ({col_list}) SELECT {col_list} FROM jsonb_populate_recordset(NULL::{target}, $1)",
+                    target = self.target,
+                );
+                let result = sqlx::query(&sql)
+                    .bind(serde_json::Value::Array(records))
+                    .execute(&mut **tx)
+                    .await?;
+                Ok(result.rows_affected())
+            }
+            BulkTransaction::Sqlite(tx) => {
+                let per_chunk = (SQLITE_MAX_PARAMS / self.columns.len()).max(1);
+                let placeholders = format!("({})", vec!["?"; self.columns.len()].join(", "));
+                let mut affected = 0;
+
+                for chunk in rows.chunks(per_chunk) {
+                    let sql = format!(
+                        "INSERT INTO {} ({}) VALUES {}",
+                        self.target, col_list, vec![placeholders.as_str(); chunk.len()].join(", ")
+                    );
+                    let mut query = sqlx::query(&sql);
+                    for row in chunk {
+                        for (kind, value) in self.kinds.iter().zip(row) {
+                            query = match (kind, value.as_deref()) {
+                                (_, None) => query.bind(None::<String>),
+                                (ColumnKind::Bytes, Some(s)) => match decode_hex(s) {
+                                    Some(bytes) => query.bind(bytes),
+                                    None => query.bind(s.to_string()),
+                                },
+                                (ColumnKind::Boolean, Some(s)) => match s.to_lowercase().as_str() {
+                                    "true" | "t" => query.bind(1_i64),
+                                    "false" | "f" => query.bind(0_i64),
+                                    _ => query.bind(s.to_string()),
+                                },
+                                (_, Some(s)) => query.bind(s.to_string()),
+                            };
+                        }
+                    }
+                    affected += query.execute(&mut **tx).await?.rows_affected();
+                }
+                Ok(affected)
+            }
+        }
+    }
+
+    /// Insert one batch, using `COPY ... FROM STDIN` on Postgres.
+    ///
+    /// Values must already be in the column's text input format. COPY reads
+    /// arrays only as `{a,b}`, so tables with `Json` columns, which may be
+    /// arrays carried as JSON text, fall back to [`insert`](Self::insert),
+    /// as does SQLite.
+    pub async fn copy_rows(&mut self, rows: &[Vec<Option<String>>]) -> Result<u64> {
+        if rows.is_empty() {
+            return Ok(0);
+        }
+        self.check_row_widths(rows)?;
+        let has_json = self.kinds.contains(&ColumnKind::Json);
+        let tx = match &mut self.tx {
+            BulkTransaction::Postgres(tx) if !has_json => tx,
+            _ => return self.insert(rows).await,
+        };
+
+        let mut data = String::new();
+        for row in rows {
+            let line = row.iter()
+                .map(|v| match v {
+                    None => "\\N".to_string(),
+                    Some(s) => copy_text_escape(s),
+                })
+                .collect::<Vec<_>>()
+                .join("\t");
+            data.push_str(&line);
+            data.push('\n');
+        }
+
+        let col_list = self.columns.iter().map(|c| quote_ident(c)).collect::<Vec<_>>().join(", ");
+        let mut copy = tx.copy_in_raw(&format!("COPY {} ({}) FROM STDIN", self.target, col_list)).await?;
+        if let Err(e) = copy.send(data.into_bytes()).await {
+            let _ = copy.abort(e.to_string()).await;
+            return Err(e.into());
+        }
+        Ok(copy.finish().await?)
+    }
+
+    fn check_row_widths(&self, rows: &[Vec<Option<String>>]) -> Result<()> {
+        match rows.iter().find(|r| r.len() != self.columns.len()) {
+            Some(bad) => Err(format!(
+                "Row has {} values but {} columns were expected",
+                bad.len(), self.columns.len()
+            ).into()),
+            None => Ok(()),
+        }
+    }
+
+    pub async fn commit(self) -> Result<()> {
+        match self.tx {
+            BulkTransaction::Postgres(tx) => tx.commit().await?,
+            BulkTransaction::Sqlite(tx) => tx.commit().await?,
+        }
+        Ok(())
+    }
+}
+
+/// Escape a value for COPY's text format.
+fn copy_text_escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Parse the `\x`-prefixed hex form that [`DbRow::decode_lossless`] emits for binary columns.
+///
+/// [`DbRow::decode_lossless`]: super::postgres::DbRow::decode_lossless
+pub(crate) fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    let hex = s.strip_prefix("\\x")?;
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
@@ -1,5 +1,9 @@
 use crate::error::Result;
+use crate::query::SqlDialect;
 use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::Executor;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -19,10 +23,14 @@ pub enum ConnectionConfig {
         database: String,
         username: String,
         password: String,
+        #[serde(default)]
+        read_only: bool,
     },
     Sqlite {
         name: String,
         path: PathBuf,
+        #[serde(default)]
+        read_only: bool,
     },
 }
 
@@ -34,6 +42,20 @@ impl ConnectionConfig {
         }
     }
 
+    pub fn is_read_only(&self) -> bool {
+        match self {
+            ConnectionConfig::Postgres { read_only, .. } => *read_only,
+            ConnectionConfig::Sqlite { read_only, .. } => *read_only,
+        }
+    }
+
+    /// Open sessions read-only regardless of the saved setting.
+    pub fn force_read_only(&mut self) {
+        match self {
+            ConnectionConfig::Postgres { read_only, .. } | ConnectionConfig::Sqlite { read_only, .. } => *read_only = true,
+        }
+    }
+
     pub fn connection_string(&self) -> String {
         match self {
             ConnectionConfig::Postgres { host, port, database, username, password, .. } => {
@@ -57,17 +79,58 @@ impl DatabaseConnection {
         matches!(self, DatabaseConnection::Postgres(_))
     }
 
+    /// Dialect to parse this connection's SQL with.
+    pub fn dialect(&self) -> SqlDialect {
+        match self {
+            DatabaseConnection::Postgres(_) => SqlDialect::Postgres,
+            DatabaseConnection::Sqlite(_) => SqlDialect::Sqlite,
+        }
+    }
+
+    /// Open a pool. Read-only configs are enforced per session, so every
+    /// pooled connection rejects writes regardless of which code path uses it,
+    /// and a Postgres session that stopped being read-only is never reused.
     pub async fn connect(config: &ConnectionConfig) -> Result<Self> {
         match config {
-            ConnectionConfig::Postgres { .. } => {
+            ConnectionConfig::Postgres { read_only: false, .. } => {
                 let pool = sqlx::PgPool::connect(&config.connection_string()).await?;
                 Ok(DatabaseConnection::Postgres(pool))
             }
-            ConnectionConfig::Sqlite { path, .. } => {
+            ConnectionConfig::Postgres { read_only: true, .. } => {
+                let pool = PgPoolOptions::new()
+                    .after_connect(|conn, _meta| Box::pin(async move {
+                        conn.execute("SET default_transaction_read_only = on").await?;
+                        Ok(())
+                    }))
+                    // A statement can switch its session back with `SET` or
+                    // `set_config`, or leave a read-write transaction open;
+                    // such a session is closed instead of returning to the pool.
+                    .after_release(|conn, _meta| Box::pin(async move {
+                        let still_read_only: bool = sqlx::query_scalar(
+                            "SELECT current_setting('default_transaction_read_only') = 'on' \
+                             AND current_setting('transaction_read_only') = 'on'",
+                        )
+                        .fetch_one(&mut *conn)
+                        .await?;
+                        Ok(still_read_only)
+                    }))
+                    .connect(&config.connection_string())
+                    .await?;
+                Ok(DatabaseConnection::Postgres(pool))
+            }
+            ConnectionConfig::Sqlite { path, read_only: false, .. } => {
                 let conn_str = format!("sqlite:{}?mode=rwc", path.display());
                 let pool = sqlx::SqlitePool::connect(&conn_str).await?;
                 Ok(DatabaseConnection::Sqlite(pool))
             }
+            ConnectionConfig::Sqlite { path, read_only: true, .. } => {
+                let options = SqliteConnectOptions::new()
+                    .filename(path)
+                    .read_only(true)
+                    .pragma("query_only", "ON");
+                let pool = sqlx::SqlitePool::connect_with(options).await?;
+                Ok(DatabaseConnection::Sqlite(pool))
+            }
         }
     }
 
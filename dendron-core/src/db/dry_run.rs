@@ -0,0 +1,120 @@
+//! Run an UPDATE or DELETE inside a transaction that is always rolled back,
+//! to learn how many rows it would touch before running it for real.
+//!
+//! The transaction holds one pooled connection for its whole life, so the
+//! statement and the rollback happen on the same session. Row locks taken by
+//! the statement are held until the rollback.
+
+use futures::TryStreamExt;
+use serde::Serialize;
+use sqlparser::ast::{SelectItem, Statement, WildcardAdditionalOptions};
+
+use crate::error::{AppError, Result};
+use crate::params::{bind_values, ParamValue};
+use crate::query::{QueryType, SqlAnalyzer};
+use super::postgres::{DbRow, QueryResult};
+use super::DatabaseConnection;
+
+/// Affected rows kept for display when no limit is given.
+pub const DEFAULT_DRY_RUN_SAMPLE: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DryRunResult {
+    pub query_type: QueryType,
+    pub rows_affected: u64,
+    /// Up to the requested number of affected rows, via RETURNING on Postgres.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample: Option<QueryResult>,
+    pub execution_time_ms: u128,
+}
+
+impl DatabaseConnection {
+    /// Execute a single UPDATE or DELETE and roll it back, with `params`
+    /// bound to its native numbered placeholders.
+    ///
+    /// With `sample_limit` above zero on Postgres, the statement gets
+    /// `RETURNING *` (unless it has its own RETURNING) and the first
+    /// `sample_limit` rows are kept.
+    pub async fn dry_run(&self, sql: &str, params: &[ParamValue], sample_limit: usize) -> Result<DryRunResult> {
+        let analyzer = SqlAnalyzer::new(self.dialect(), sql);
+        let stmt = match analyzer.statements() {
+            Some([stmt]) => stmt,
+            Some(_) => return Err(AppError::InvalidInput("A dry run takes exactly one statement".into())),
+            None => return Err(AppError::InvalidInput(format!(
+                "Could not parse SQL: {}", analyzer.parse_error().unwrap_or_default()
+            ))),
+        };
+        let query_type = analyzer.query_type();
+        if !matches!(query_type, QueryType::Update | QueryType::Delete) {
+            return Err(AppError::InvalidInput("Only UPDATE and DELETE can be dry-run".into()));
+        }
+
+        let start = std::time::Instant::now();
+        let (rows_affected, sample) = match self {
+            DatabaseConnection::Postgres(pool) if sample_limit > 0 => {
+                let sql = with_returning(stmt).to_string();
+                let mut tx = pool.begin().await?;
+                let mut stream = bind_values(sqlx::query(&sql), params).fetch(&mut *tx);
+                let mut count = 0;
+                let mut kept = Vec::new();
+                while let Some(row) = stream.try_next().await? {
+                    count += 1;
+                    if kept.len() < sample_limit {
+                        kept.push(DbRow::Postgres(row));
+                    }
+                }
+                drop(stream);
+                tx.rollback().await?;
+                (count, Some(sample_result(&kept, count)))
+            }
+            DatabaseConnection::Postgres(pool) => {
+                let mut tx = pool.begin().await?;
+                let affected = bind_values(sqlx::query(sql), params).execute(&mut *tx).await?.rows_affected();
+                tx.rollback().await?;
+                (affected, None)
+            }
+            DatabaseConnection::Sqlite(pool) => {
+                let mut tx = pool.begin().await?;
+                let affected = bind_values(sqlx::query(sql), params).execute(&mut *tx).await?.rows_affected();
+                tx.rollback().await?;
+                (affected, None)
+            }
+        };
+
+        Ok(DryRunResult {
+            query_type,
+            rows_affected,
+            sample,
+            execution_time_ms: start.elapsed().as_millis(),
+        })
+    }
+}
+
+/// `stmt` with `RETURNING *` added if it doesn't return anything yet.
+fn with_returning(stmt: &Statement) -> Statement {
+    let all = || Some(vec![SelectItem::Wildcard(WildcardAdditionalOptions::default())]);
+    let mut stmt = stmt.clone();
+    match &mut stmt {
+        Statement::Update { returning, .. } if returning.is_none() => *returning = all(),
+        Statement::Delete(delete) if delete.returning.is_none() => delete.returning = all(),
+        _ => {}
+    }
+    stmt
+}
+
+fn sample_result(rows: &[DbRow], total: u64) -> QueryResult {
+    let (columns, column_types) = rows.first().map(DbRow::columns).unwrap_or_default();
+    let rows: Vec<Vec<String>> = rows.iter()
+        .map(|row| row.decode().into_iter().map(|v| v.unwrap_or_else(|| "NULL".to_string())).collect())
+        .collect();
+    QueryResult {
+        columns,
+        column_types,
+        row_count: rows.len(),
+        truncated: (rows.len() as u64) < total,
+        rows,
+        execution_time_ms: 0,
+        has_order_by: true,
+        affected_rows: Some(total),
+    }
+}
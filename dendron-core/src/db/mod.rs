@@ -1,8 +1,14 @@
+pub mod bulk;
 pub mod connection;
+pub mod dry_run;
 pub mod postgres;
 pub mod sqlite;
 pub mod schema;
 pub mod ssh;
+pub mod statements;
+pub mod transfer;
+pub mod types;
 
 pub use connection::*;
 pub use schema::{SchemaInfo, TableInfo, ColumnInfo, TableStructure, ColumnDetail, IndexInfo, ForeignKeyInfo};
+pub use types::ColumnKind;
@@ -1,11 +1,19 @@
-use crate::error::Result;
-use futures::TryStreamExt;
+use crate::error::{AppError, Result};
+use crate::params::{bind_values, ParamValue};
+use crate::query::quote_ident;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::sqlite::SqliteRow;
 use sqlx::{Row, Column, ValueRef, TypeInfo};
 use super::DatabaseConnection;
 
 pub const DEFAULT_ROW_LIMIT: usize = 1000;
 
+/// Rows a SELECT reads between progress reports.
+pub const PROGRESS_INTERVAL: usize = 100;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryResult {
     pub columns: Vec<String>,
@@ -21,231 +29,392 @@ pub struct QueryResult {
     pub affected_rows: Option<u64>,
 }
 
+/// The rows a SELECT has read so far, reported while the rest of the page
+/// is still loading.
+pub struct FetchProgress<'a> {
+    pub columns: &'a [String],
+    pub column_types: &'a [String],
+    pub rows: &'a [Vec<String>],
+}
+
+/// A fetched row from either backend, decoded on demand.
+pub enum DbRow {
+    Postgres(PgRow),
+    Sqlite(SqliteRow),
+}
+
+impl DbRow {
+    /// Column names and driver type names, in select-list order.
+    pub fn columns(&self) -> (Vec<String>, Vec<String>) {
+        fn names<C: Column>(cols: &[C]) -> (Vec<String>, Vec<String>) {
+            cols.iter()
+                .map(|c| (c.name().to_string(), c.type_info().name().to_string()))
+                .unzip()
+        }
+        match self {
+            DbRow::Postgres(row) => names(row.columns()),
+            DbRow::Sqlite(row) => names(row.columns()),
+        }
+    }
+
+    /// Decode every cell to text for the results grid (pretty JSON,
+    /// truncated binary). `None` is SQL NULL; types that can't be decoded
+    /// show as `<typename>`.
+    pub fn decode(&self) -> Vec<Option<String>> {
+        self.cells(true)
+            .map(|cell| cell.unwrap_or_else(|type_name| Some(format!("<{type_name}>"))))
+            .collect()
+    }
+
+    /// Decode every cell to text that can be written back to a database or
+    /// a file. Fails on a type that can't be decoded rather than writing a
+    /// placeholder in its place.
+    pub fn decode_lossless(&self) -> Result<Vec<Option<String>>> {
+        let (names, _) = self.columns();
+        self.cells(false)
+            .zip(&names)
+            .map(|(cell, name)| cell.map_err(|type_name| AppError::QueryFailed(format!(
+                "Column '{name}' has type {type_name}, which can't be read as text here; cast it with {}::text",
+                quote_ident(name)
+            ))))
+            .collect()
+    }
+
+    /// Each cell, or the lowercase type name of one that can't be decoded.
+    fn cells(&self, preview: bool) -> impl Iterator<Item = std::result::Result<Option<String>, String>> + '_ {
+        let count = match self {
+            DbRow::Postgres(row) => row.columns().len(),
+            DbRow::Sqlite(row) => row.columns().len(),
+        };
+        (0..count).map(move |i| match self {
+            DbRow::Postgres(row) => decode_pg_cell(row, i, preview),
+            DbRow::Sqlite(row) => decode_sqlite_cell(row, i, preview),
+        })
+    }
+}
+
+fn hex_bytes(b: &[u8], preview: bool) -> String {
+    if preview {
+        let hex: String = b.iter().take(32).map(|byte| format!("{byte:02x}")).collect();
+        if b.len() > 32 { format!("\\x{hex}…") } else { format!("\\x{hex}") }
+    } else {
+        let hex: String = b.iter().map(|byte| format!("{byte:02x}")).collect();
+        format!("\\x{hex}")
+    }
+}
+
+fn decode_pg_cell(row: &PgRow, i: usize, preview: bool) -> std::result::Result<Option<String>, String> {
+    let type_name = row.columns().get(i)
+        .map(|c| c.type_info().name())
+        .unwrap_or("");
+
+    let raw = match row.try_get_raw(i) {
+        Ok(v) if v.is_null() => return Ok(None),
+        Ok(v) => Some(v),
+        Err(_) => None,
+    };
+
+    let decoded = raw.and_then(|_| {
+        match type_name {
+            "JSONB" | "JSON" =>
+                row.try_get::<serde_json::Value, _>(i).ok()
+                    .map(|j| if preview { serde_json::to_string_pretty(&j).unwrap_or_else(|_| j.to_string()) } else { j.to_string() }),
+            "BYTEA" =>
+                row.try_get::<Vec<u8>, _>(i).ok().map(|b| hex_bytes(&b, preview)),
+            "TIMESTAMPTZ" =>
+                row.try_get::<sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc>, _>(i).ok()
+                    .map(|v| v.to_rfc3339()),
+            "TIMESTAMP" =>
+                row.try_get::<sqlx::types::chrono::NaiveDateTime, _>(i).ok()
+                    .map(|v| v.to_string()),
+            "DATE" =>
+                row.try_get::<sqlx::types::chrono::NaiveDate, _>(i).ok()
+                    .map(|v| v.to_string()),
+            "TIME" | "TIMETZ" =>
+                row.try_get::<sqlx::types::chrono::NaiveTime, _>(i).ok()
+                    .map(|v| v.to_string()),
+            "UUID" =>
+                row.try_get::<sqlx::types::Uuid, _>(i).ok()
+                    .map(|v| v.to_string()),
+            "INT2" =>
+                row.try_get::<i16, _>(i).ok().map(|v| v.to_string()),
+            "FLOAT4" =>
+                row.try_get::<f32, _>(i).ok().map(|v| v.to_string()),
+            "NUMERIC" | "DECIMAL" =>
+                row.try_get::<rust_decimal::Decimal, _>(i).ok().map(|v| v.to_string()),
+            "MONEY" =>
+                // Binary: int64 representing cents (scale=2, always)
+                row.try_get_unchecked::<i64, _>(i).ok().map(|cents| {
+                    let whole = cents / 100;
+                    let frac = (cents % 100).unsigned_abs();
+                    format!("{whole}.{frac:02}")
+                }),
+            "INET" | "CIDR" =>
+                // Binary format: family(1) bits(1) is_cidr(1) addr_len(1) addr(N)
+                row.try_get_unchecked::<Vec<u8>, _>(i).ok().and_then(|bytes| {
+                    if bytes.len() < 4 { return None; }
+                    let family = bytes[0];
+                    let bits = bytes[1];
+                    let is_cidr = bytes[2];
+                    let addr_len = bytes[3] as usize;
+                    if bytes.len() < 4 + addr_len { return None; }
+                    let addr = &bytes[4..4 + addr_len];
+                    match (family, addr_len) {
+                        (2, 4) => {
+                            let ip = format!("{}.{}.{}.{}", addr[0], addr[1], addr[2], addr[3]);
+                            if is_cidr != 0 || bits != 32 { Some(format!("{ip}/{bits}")) } else { Some(ip) }
+                        }
+                        (3, 16) => {
+                            let ip = addr.chunks_exact(2)
+                                .map(|c| format!("{:x}", u16::from_be_bytes([c[0], c[1]])))
+                                .collect::<Vec<_>>().join(":");
+                            if is_cidr != 0 || bits != 128 { Some(format!("{ip}/{bits}")) } else { Some(ip) }
+                        }
+                        _ => None,
+                    }
+                }),
+            "MACADDR" =>
+                row.try_get_unchecked::<Vec<u8>, _>(i).ok().and_then(|bytes| {
+                    if bytes.len() == 6 {
+                        Some(bytes.iter().map(|b| format!("{b:02x}")).collect::<Vec<_>>().join(":"))
+                    } else { None }
+                }),
+            "MACADDR8" =>
+                row.try_get_unchecked::<Vec<u8>, _>(i).ok().and_then(|bytes| {
+                    if bytes.len() == 8 {
+                        Some(bytes.iter().map(|b| format!("{b:02x}")).collect::<Vec<_>>().join(":"))
+                    } else { None }
+                }),
+            name if name.ends_with("[]") => {
+                let base = name.trim_end_matches("[]");
+                // Preview keeps the compact `[a, b]` grid format; data mode emits
+                // JSON so the array survives a round-trip through jsonb/TEXT.
+                let fmt = |v: Vec<String>| format!("[{}]", v.join(if preview { ", " } else { "," }));
+                match base {
+                    "INT2" => row.try_get::<Vec<i16>, _>(i).ok().map(|v| fmt(v.iter().map(|x| x.to_string()).collect())),
+                    "INT4" => row.try_get::<Vec<i32>, _>(i).ok().map(|v| fmt(v.iter().map(|x| x.to_string()).collect())),
+                    "INT8" => row.try_get::<Vec<i64>, _>(i).ok().map(|v| fmt(v.iter().map(|x| x.to_string()).collect())),
+                    "FLOAT4" => row.try_get::<Vec<f32>, _>(i).ok().map(|v| fmt(v.iter().map(|x| x.to_string()).collect())),
+                    "FLOAT8" => row.try_get::<Vec<f64>, _>(i).ok().map(|v| fmt(v.iter().map(|x| x.to_string()).collect())),
+                    "BOOL" => row.try_get::<Vec<bool>, _>(i).ok().map(|v| fmt(v.iter().map(|x| x.to_string()).collect())),
+                    "TEXT" | "VARCHAR" | "BPCHAR" | "NAME" => row.try_get::<Vec<String>, _>(i).ok().map(|v| {
+                        if preview {
+                            fmt(v.iter().map(|s| format!("{s:?}")).collect())
+                        } else {
+                            serde_json::to_string(&v).unwrap_or_default()
+                        }
+                    }),
+                    "UUID" => row.try_get::<Vec<sqlx::types::Uuid>, _>(i).ok().map(|v| {
+                        if preview { fmt(v.iter().map(|x| x.to_string()).collect()) } else { fmt(v.iter().map(|x| format!("\"{x}\"")).collect()) }
+                    }),
+                    "NUMERIC" | "DECIMAL" => row.try_get::<Vec<rust_decimal::Decimal>, _>(i).ok().map(|v| fmt(v.iter().map(|x| x.to_string()).collect())),
+                    _ => None,
+                }
+            },
+            _ =>
+                row.try_get::<String, _>(i).ok()
+                    .or_else(|| row.try_get::<i64, _>(i).map(|v| v.to_string()).ok())
+                    .or_else(|| row.try_get::<i32, _>(i).map(|v| v.to_string()).ok())
+                    .or_else(|| row.try_get::<f64, _>(i).map(|v| v.to_string()).ok())
+                    .or_else(|| row.try_get::<bool, _>(i).map(|v| v.to_string()).ok())
+                    // Custom enum / domain types: postgres wire-encodes them as
+                    // plain UTF-8 bytes, so try an unchecked String decode.
+                    // Filter out null bytes to avoid garbage from binary types
+                    // (OID/interval/etc. typically contain 0x00 bytes).
+                    .or_else(|| row.try_get_unchecked::<String, _>(i).ok()
+                        .filter(|s| !s.contains('\0'))),
+        }
+    });
+
+    decoded.map(Some).ok_or_else(|| type_name.to_lowercase())
+}
+
+fn decode_sqlite_cell(row: &SqliteRow, i: usize, preview: bool) -> std::result::Result<Option<String>, String> {
+    let type_name = row.columns().get(i)
+        .map(|c| c.type_info().name())
+        .unwrap_or("");
+
+    let raw = match row.try_get_raw(i) {
+        Ok(v) if v.is_null() => return Ok(None),
+        Ok(v) => Some(v),
+        Err(_) => None,
+    };
+
+    let decoded = raw.and_then(|_| {
+        match type_name {
+            "BLOB" =>
+                row.try_get::<Vec<u8>, _>(i).ok().map(|b| hex_bytes(&b, preview)),
+            _ =>
+                row.try_get::<String, _>(i).ok().map(|s| {
+                    if preview && (s.starts_with('{') || s.starts_with('[')) {
+                        serde_json::from_str::<serde_json::Value>(&s).ok()
+                            .map(|v| serde_json::to_string_pretty(&v).unwrap_or(s.clone()))
+                            .unwrap_or(s)
+                    } else { s }
+                })
+                .or_else(|| row.try_get::<i64, _>(i).map(|v| v.to_string()).ok())
+                .or_else(|| row.try_get::<f64, _>(i).map(|v| v.to_string()).ok()),
+        }
+    });
+
+    decoded.map(Some).ok_or_else(|| type_name.to_lowercase())
+}
+
 impl DatabaseConnection {
-    pub async fn execute_query(&self, sql: &str, has_order_by: bool, is_select: bool) -> Result<QueryResult> {
+    /// Stream every row of `sql` without a row cap.
+    pub fn fetch_rows<'a>(&'a self, sql: &'a str) -> BoxStream<'a, Result<DbRow>> {
+        self.fetch_rows_with(sql, &[])
+    }
+
+    /// [`fetch_rows`](Self::fetch_rows) with `params` bound to the native
+    /// numbered placeholders in `sql`.
+    pub fn fetch_rows_with<'a>(&'a self, sql: &'a str, params: &'a [ParamValue]) -> BoxStream<'a, Result<DbRow>> {
+        match self {
+            DatabaseConnection::Postgres(pool) => bind_values(sqlx::query(sql), params).fetch(pool)
+                .map_ok(DbRow::Postgres)
+                .map_err(Into::into)
+                .boxed(),
+            DatabaseConnection::Sqlite(pool) => bind_values(sqlx::query(sql), params).fetch(pool)
+                .map_ok(DbRow::Sqlite)
+                .map_err(Into::into)
+                .boxed(),
+        }
+    }
+
+    /// Column names and driver type names of `sql`, without fetching any rows.
+    pub async fn describe_columns(&self, sql: &str) -> Result<(Vec<String>, Vec<String>)> {
+        use sqlx::Executor;
+
+        fn names<C: Column>(cols: &[C]) -> (Vec<String>, Vec<String>) {
+            cols.iter()
+                .map(|c| (c.name().to_string(), c.type_info().name().to_string()))
+                .unzip()
+        }
+        match self {
+            DatabaseConnection::Postgres(pool) => Ok(names(pool.describe(sql).await?.columns())),
+            DatabaseConnection::Sqlite(pool) => Ok(names(pool.describe(sql).await?.columns())),
+        }
+    }
+
+    /// Run `sql` with `params` bound to its native numbered placeholders.
+    pub async fn execute_query(
+        &self,
+        sql: &str,
+        params: &[ParamValue],
+        has_order_by: bool,
+        is_select: bool,
+    ) -> Result<QueryResult> {
+        self.execute_query_with(sql, params, has_order_by, is_select, |_| {}).await
+    }
+
+    /// [`execute_query`](Self::execute_query), calling `on_progress` every
+    /// `PROGRESS_INTERVAL` rows a SELECT reads.
+    pub async fn execute_query_with(
+        &self,
+        sql: &str,
+        params: &[ParamValue],
+        has_order_by: bool,
+        is_select: bool,
+        on_progress: impl FnMut(FetchProgress<'_>),
+    ) -> Result<QueryResult> {
         let start = std::time::Instant::now();
 
         // For non-SELECT (INSERT/UPDATE/DELETE/etc.), use execute() to get affected row count.
         if !is_select {
             let affected = match self {
-                DatabaseConnection::Postgres(pool) => sqlx::query(sql).execute(pool).await?.rows_affected(),
-                DatabaseConnection::Sqlite(pool) => sqlx::query(sql).execute(pool).await?.rows_affected(),
+                DatabaseConnection::Postgres(pool) => bind_values(sqlx::query(sql), params).execute(pool).await?.rows_affected(),
+                DatabaseConnection::Sqlite(pool) => bind_values(sqlx::query(sql), params).execute(pool).await?.rows_affected(),
             };
-            let execution_time_ms = start.elapsed().as_millis();
-            return Ok(QueryResult {
-                columns: Vec::new(),
-                column_types: Vec::new(),
-                rows: Vec::new(),
-                row_count: 0,
-                execution_time_ms,
-                truncated: false,
-                has_order_by: true,
-                affected_rows: Some(affected),
-            });
+            return Ok(affected_result(affected, start));
         }
+        read_page(self.fetch_rows_with(sql, params), has_order_by, start, on_progress).await
+    }
 
+    /// [`execute_query_with`](Self::execute_query_with) inside a transaction
+    /// that is always rolled back. On Postgres the transaction is also
+    /// `READ ONLY`, so the database refuses any write the statement attempts
+    /// and the statement can't switch that back.
+    pub async fn execute_read_only_with(
+        &self,
+        sql: &str,
+        params: &[ParamValue],
+        has_order_by: bool,
+        is_select: bool,
+        on_progress: impl FnMut(FetchProgress<'_>),
+    ) -> Result<QueryResult> {
+        let start = std::time::Instant::now();
         match self {
             DatabaseConnection::Postgres(pool) => {
-                let mut stream = sqlx::query(sql).fetch(pool);
-                let mut collected = Vec::with_capacity(DEFAULT_ROW_LIMIT + 1);
-                while let Some(row) = stream.try_next().await? {
-                    collected.push(row);
-                    if collected.len() > DEFAULT_ROW_LIMIT { break; }
-                }
-                drop(stream);
-                let execution_time_ms = start.elapsed().as_millis();
-
-                let truncated = collected.len() > DEFAULT_ROW_LIMIT;
-                if truncated { collected.pop(); }
-
-                let (columns, column_types): (Vec<String>, Vec<String>) = if let Some(row) = collected.first() {
-                    row.columns()
-                        .iter()
-                        .map(|c| (c.name().to_string(), c.type_info().name().to_string()))
-                        .unzip()
+                let mut tx = pool.begin().await?;
+                sqlx::query("SET TRANSACTION READ ONLY").execute(&mut *tx).await?;
+                let query = bind_values(sqlx::query(sql), params);
+                let result = if is_select {
+                    let rows = query.fetch(&mut *tx).map_ok(DbRow::Postgres).map_err(Into::into).boxed();
+                    read_page(rows, has_order_by, start, on_progress).await?
                 } else {
-                    (Vec::new(), Vec::new())
+                    affected_result(query.execute(&mut *tx).await?.rows_affected(), start)
                 };
-
-                let rows: Vec<Vec<String>> = collected.iter().map(|row| {
-                    (0..row.columns().len()).map(|i| {
-                        let type_name = row.columns().get(i)
-                            .map(|c| c.type_info().name())
-                            .unwrap_or("");
-
-                        let decoded = row.try_get_raw(i).ok().and_then(|v| {
-                            if v.is_null() {
-                                return Some("NULL".to_string());
-                            }
-                            match type_name {
-                                "JSONB" | "JSON" =>
-                                    row.try_get::<serde_json::Value, _>(i).ok()
-                                        .map(|j| serde_json::to_string_pretty(&j).unwrap_or_else(|_| j.to_string())),
-                                "BYTEA" =>
-                                    row.try_get::<Vec<u8>, _>(i).ok().map(|b| {
-                                        let hex: String = b.iter().take(32).map(|byte| format!("{byte:02x}")).collect();
-                                        if b.len() > 32 { format!("\\x{hex}…") } else { format!("\\x{hex}") }
-                                    }),
-                                "TIMESTAMPTZ" =>
-                                    row.try_get::<sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc>, _>(i).ok()
-                                        .map(|v| v.to_rfc3339()),
-                                "TIMESTAMP" =>
-                                    row.try_get::<sqlx::types::chrono::NaiveDateTime, _>(i).ok()
-                                        .map(|v| v.to_string()),
-                                "DATE" =>
-                                    row.try_get::<sqlx::types::chrono::NaiveDate, _>(i).ok()
-                                        .map(|v| v.to_string()),
-                                "TIME" | "TIMETZ" =>
-                                    row.try_get::<sqlx::types::chrono::NaiveTime, _>(i).ok()
-                                        .map(|v| v.to_string()),
-                                "UUID" =>
-                                    row.try_get::<sqlx::types::Uuid, _>(i).ok()
-                                        .map(|v| v.to_string()),
-                                "INT2" =>
-                                    row.try_get::<i16, _>(i).ok().map(|v| v.to_string()),
-                                "FLOAT4" =>
-                                    row.try_get::<f32, _>(i).ok().map(|v| v.to_string()),
-                                "NUMERIC" | "DECIMAL" =>
-                                    row.try_get::<rust_decimal::Decimal, _>(i).ok().map(|v| v.to_string()),
-                                "MONEY" =>
-                                    // Binary: int64 representing cents (scale=2, always)
-                                    row.try_get_unchecked::<i64, _>(i).ok().map(|cents| {
-                                        let whole = cents / 100;
-                                        let frac = (cents % 100).unsigned_abs();
-                                        format!("{whole}.{frac:02}")
-                                    }),
-                                "INET" | "CIDR" =>
-                                    // Binary format: family(1) bits(1) is_cidr(1) addr_len(1) addr(N)
-                                    row.try_get_unchecked::<Vec<u8>, _>(i).ok().and_then(|bytes| {
-                                        if bytes.len() < 4 { return None; }
-                                        let family = bytes[0];
-                                        let bits = bytes[1];
-                                        let is_cidr = bytes[2];
-                                        let addr_len = bytes[3] as usize;
-                                        if bytes.len() < 4 + addr_len { return None; }
-                                        let addr = &bytes[4..4 + addr_len];
-                                        match (family, addr_len) {
-                                            (2, 4) => {
-                                                let ip = format!("{}.{}.{}.{}", addr[0], addr[1], addr[2], addr[3]);
-                                                if is_cidr != 0 || bits != 32 { Some(format!("{ip}/{bits}")) } else { Some(ip) }
-                                            }
-                                            (3, 16) => {
-                                                let ip = addr.chunks_exact(2)
-                                                    .map(|c| format!("{:x}", u16::from_be_bytes([c[0], c[1]])))
-                                                    .collect::<Vec<_>>().join(":");
-                                                if is_cidr != 0 || bits != 128 { Some(format!("{ip}/{bits}")) } else { Some(ip) }
-                                            }
-                                            _ => None,
-                                        }
-                                    }),
-                                "MACADDR" =>
-                                    row.try_get_unchecked::<Vec<u8>, _>(i).ok().and_then(|bytes| {
-                                        if bytes.len() == 6 {
-                                            Some(bytes.iter().map(|b| format!("{b:02x}")).collect::<Vec<_>>().join(":"))
-                                        } else { None }
-                                    }),
-                                "MACADDR8" =>
-                                    row.try_get_unchecked::<Vec<u8>, _>(i).ok().and_then(|bytes| {
-                                        if bytes.len() == 8 {
-                                            Some(bytes.iter().map(|b| format!("{b:02x}")).collect::<Vec<_>>().join(":"))
-                                        } else { None }
-                                    }),
-                                name if name.ends_with("[]") => {
-                                    let base = name.trim_end_matches("[]");
-                                    let fmt = |v: Vec<String>| format!("[{}]", v.join(", "));
-                                    match base {
-                                        "INT2" => row.try_get::<Vec<i16>, _>(i).ok().map(|v| fmt(v.iter().map(|x| x.to_string()).collect())),
-                                        "INT4" => row.try_get::<Vec<i32>, _>(i).ok().map(|v| fmt(v.iter().map(|x| x.to_string()).collect())),
-                                        "INT8" => row.try_get::<Vec<i64>, _>(i).ok().map(|v| fmt(v.iter().map(|x| x.to_string()).collect())),
-                                        "FLOAT4" => row.try_get::<Vec<f32>, _>(i).ok().map(|v| fmt(v.iter().map(|x| x.to_string()).collect())),
-                                        "FLOAT8" => row.try_get::<Vec<f64>, _>(i).ok().map(|v| fmt(v.iter().map(|x| x.to_string()).collect())),
-                                        "BOOL" => row.try_get::<Vec<bool>, _>(i).ok().map(|v| fmt(v.iter().map(|x| x.to_string()).collect())),
-                                        "TEXT" | "VARCHAR" | "BPCHAR" | "NAME" => row.try_get::<Vec<String>, _>(i).ok().map(|v| fmt(v.iter().map(|s| format!("{s:?}")).collect())),
-                                        "UUID" => row.try_get::<Vec<sqlx::types::Uuid>, _>(i).ok().map(|v| fmt(v.iter().map(|x| x.to_string()).collect())),
-                                        "NUMERIC" | "DECIMAL" => row.try_get::<Vec<rust_decimal::Decimal>, _>(i).ok().map(|v| fmt(v.iter().map(|x| x.to_string()).collect())),
-                                        _ => None,
-                                    }
-                                },
-                                _ =>
-                                    row.try_get::<String, _>(i).ok()
-                                        .or_else(|| row.try_get::<i64, _>(i).map(|v| v.to_string()).ok())
-                                        .or_else(|| row.try_get::<i32, _>(i).map(|v| v.to_string()).ok())
-                                        .or_else(|| row.try_get::<f64, _>(i).map(|v| v.to_string()).ok())
-                                        .or_else(|| row.try_get::<bool, _>(i).map(|v| v.to_string()).ok())
-                                        // Custom enum / domain types: postgres wire-encodes them as
-                                        // plain UTF-8 bytes, so try an unchecked String decode.
-                                        // Filter out null bytes to avoid garbage from binary types
-                                        // (OID/interval/etc. typically contain 0x00 bytes).
-                                        .or_else(|| row.try_get_unchecked::<String, _>(i).ok()
-                                            .filter(|s| !s.contains('\0'))),
-                            }
-                        });
-
-                        decoded.unwrap_or_else(|| format!("<{}>", type_name.to_lowercase()))
-                    }).collect()
-                }).collect();
-
-                let row_count = rows.len();
-                Ok(QueryResult { columns, column_types, rows, row_count, execution_time_ms, truncated, has_order_by, affected_rows: None })
+                tx.rollback().await?;
+                Ok(result)
             }
             DatabaseConnection::Sqlite(pool) => {
-                let mut stream = sqlx::query(sql).fetch(pool);
-                let mut collected = Vec::with_capacity(DEFAULT_ROW_LIMIT + 1);
-                while let Some(row) = stream.try_next().await? {
-                    collected.push(row);
-                    if collected.len() > DEFAULT_ROW_LIMIT { break; }
-                }
-                drop(stream);
-                let execution_time_ms = start.elapsed().as_millis();
-
-                let truncated = collected.len() > DEFAULT_ROW_LIMIT;
-                if truncated { collected.pop(); }
-
-                let (columns, column_types): (Vec<String>, Vec<String>) = if let Some(row) = collected.first() {
-                    row.columns()
-                        .iter()
-                        .map(|c| (c.name().to_string(), c.type_info().name().to_string()))
-                        .unzip()
+                let mut tx = pool.begin().await?;
+                let query = bind_values(sqlx::query(sql), params);
+                let result = if is_select {
+                    let rows = query.fetch(&mut *tx).map_ok(DbRow::Sqlite).map_err(Into::into).boxed();
+                    read_page(rows, has_order_by, start, on_progress).await?
                 } else {
-                    (Vec::new(), Vec::new())
+                    affected_result(query.execute(&mut *tx).await?.rows_affected(), start)
                 };
-
-                let rows: Vec<Vec<String>> = collected.iter().map(|row| {
-                    (0..row.columns().len()).map(|i| {
-                        let type_name = row.columns().get(i)
-                            .map(|c| c.type_info().name())
-                            .unwrap_or("");
-
-                        let decoded = row.try_get_raw(i).ok().and_then(|v| {
-                            if v.is_null() {
-                                return Some("NULL".to_string());
-                            }
-                            match type_name {
-                                "BLOB" =>
-                                    row.try_get::<Vec<u8>, _>(i).ok().map(|b| {
-                                        let hex: String = b.iter().take(32).map(|byte| format!("{byte:02x}")).collect();
-                                        if b.len() > 32 { format!("\\x{hex}…") } else { format!("\\x{hex}") }
-                                    }),
-                                _ =>
-                                    row.try_get::<String, _>(i).ok().map(|s| {
-                                        if s.starts_with('{') || s.starts_with('[') {
-                                            serde_json::from_str::<serde_json::Value>(&s).ok()
-                                                .map(|v| serde_json::to_string_pretty(&v).unwrap_or(s.clone()))
-                                                .unwrap_or(s)
-                                        } else { s }
-                                    })
-                                    .or_else(|| row.try_get::<i64, _>(i).map(|v| v.to_string()).ok())
-                                    .or_else(|| row.try_get::<f64, _>(i).map(|v| v.to_string()).ok()),
-                            }
-                        });
-
-                        decoded.unwrap_or_else(|| format!("<{}>", type_name.to_lowercase()))
-                    }).collect()
-                }).collect();
-
-                let row_count = rows.len();
-                Ok(QueryResult { columns, column_types, rows, row_count, execution_time_ms, truncated, has_order_by, affected_rows: None })
+                tx.rollback().await?;
+                Ok(result)
             }
         }
     }
 }
+
+fn affected_result(affected: u64, start: std::time::Instant) -> QueryResult {
+    QueryResult {
+        columns: Vec::new(),
+        column_types: Vec::new(),
+        rows: Vec::new(),
+        row_count: 0,
+        execution_time_ms: start.elapsed().as_millis(),
+        truncated: false,
+        has_order_by: true,
+        affected_rows: Some(affected),
+    }
+}
+
+/// Read up to `DEFAULT_ROW_LIMIT` rows of `stream`, calling `on_progress`
+/// every `PROGRESS_INTERVAL` rows.
+async fn read_page(
+    mut stream: BoxStream<'_, Result<DbRow>>,
+    has_order_by: bool,
+    start: std::time::Instant,
+    mut on_progress: impl FnMut(FetchProgress<'_>),
+) -> Result<QueryResult> {
+    // Rows are decoded as they arrive, so the ones read so far can be shown.
+    let (mut columns, mut column_types) = (Vec::new(), Vec::new());
+    let mut rows: Vec<Vec<String>> = Vec::with_capacity(DEFAULT_ROW_LIMIT + 1);
+    while let Some(row) = stream.try_next().await? {
+        if rows.is_empty() {
+            (columns, column_types) = row.columns();
+        }
+        rows.push(row.decode().into_iter()
+            .map(|v| v.unwrap_or_else(|| "NULL".to_string()))
+            .collect());
+        if rows.len() > DEFAULT_ROW_LIMIT { break; }
+        if rows.len().is_multiple_of(PROGRESS_INTERVAL) {
+            on_progress(FetchProgress { columns: &columns, column_types: &column_types, rows: &rows });
+        }
+    }
+    drop(stream);
+    let execution_time_ms = start.elapsed().as_millis();
+
+    let truncated = rows.len() > DEFAULT_ROW_LIMIT;
+    if truncated { rows.pop(); }
+
+    let row_count = rows.len();
+    Ok(QueryResult { columns, column_types, rows, row_count, execution_time_ms, truncated, has_order_by, affected_rows: None })
+}
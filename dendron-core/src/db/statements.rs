@@ -0,0 +1,261 @@
+//! INSERT / UPSERT / UPDATE / DELETE statements for rows of a result set.
+//!
+//! Identifiers go through [`quote_ident`] and values are rendered as literals
+//! of their column's [`ColumnKind`], so integers stay unquoted and bytea
+//! survives as bytes rather than text.
+//!
+//! The results grid shows shortened values, so rows are re-read from the
+//! table by primary key before statements are written
+//! ([`StatementGenerator::lossless_rows`]).
+
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, Result};
+use crate::query::quote_ident;
+use super::bulk::qualified_name;
+use super::types::ColumnKind;
+use super::DatabaseConnection;
+
+/// Render a lossless cell as a SQL literal for a column of `kind`.
+///
+/// Values that don't look like their declared kind are emitted as quoted
+/// strings and left for the database to coerce.
+pub fn sql_literal(kind: ColumnKind, value: Option<&str>, postgres: bool) -> String {
+    let Some(s) = value else { return "NULL".to_string() };
+    match kind {
+        k if k.is_numeric() && s.parse::<f64>().is_ok_and(f64::is_finite) => s.to_string(),
+        ColumnKind::Boolean => match (s, postgres) {
+            ("true" | "t" | "1", true) => "TRUE".to_string(),
+            ("false" | "f" | "0", true) => "FALSE".to_string(),
+            ("true" | "t" | "1", false) => "1".to_string(),
+            ("false" | "f" | "0", false) => "0".to_string(),
+            _ => quote_literal(s),
+        },
+        ColumnKind::Bytes => match s.strip_prefix("\\x") {
+            Some(hex) if hex.bytes().all(|b| b.is_ascii_hexdigit()) => {
+                if postgres { format!("'\\x{hex}'::bytea") } else { format!("X'{hex}'") }
+            }
+            _ => quote_literal(s),
+        },
+        _ => quote_literal(s),
+    }
+}
+
+/// A Postgres array literal (`'{1,2}'`) from an array carried as JSON text,
+/// or `None` if `json` isn't a JSON array of scalars or of such arrays.
+pub(crate) fn array_literal(json: &str) -> Option<String> {
+    let serde_json::Value::Array(items) = serde_json::from_str(json).ok()? else { return None };
+    array_text(&items).map(|text| quote_literal(&text))
+}
+
+fn array_text(items: &[serde_json::Value]) -> Option<String> {
+    let elements = items.iter()
+        .map(|item| match item {
+            serde_json::Value::Null => Some("NULL".to_string()),
+            serde_json::Value::String(s) => Some(format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))),
+            serde_json::Value::Number(_) | serde_json::Value::Bool(_) => Some(item.to_string()),
+            serde_json::Value::Array(inner) => array_text(inner),
+            serde_json::Value::Object(_) => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(format!("{{{}}}", elements.join(",")))
+}
+
+/// Quote a string literal, doubling embedded single quotes.
+pub fn quote_literal(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatementKind {
+    /// One multi-row INSERT.
+    Insert,
+    /// INSERT ... ON CONFLICT (pk) DO UPDATE, valid on Postgres and SQLite 3.24+.
+    Upsert,
+    /// One UPDATE per row, matched on the primary key.
+    Update,
+    /// One DELETE per row, matched on the primary key.
+    Delete,
+}
+
+/// Generates statements against one table from rows shaped like its columns.
+pub struct StatementGenerator {
+    target: String,
+    columns: Vec<String>,
+    kinds: Vec<ColumnKind>,
+    /// Postgres array columns, which need `'{...}'` rather than JSON.
+    arrays: Vec<bool>,
+    pk_columns: Vec<String>,
+    postgres: bool,
+}
+
+impl StatementGenerator {
+    /// `column_types` are driver or catalog type names, as in `QueryResult`.
+    pub fn new(
+        schema: Option<&str>,
+        table: &str,
+        columns: Vec<String>,
+        column_types: &[String],
+        postgres: bool,
+    ) -> Self {
+        let target = match schema {
+            Some(schema) => qualified_name(schema, table),
+            None => quote_ident(table),
+        };
+        let kinds = (0..columns.len())
+            .map(|i| column_types.get(i).map_or(ColumnKind::Text, |t| ColumnKind::from_type_name(t)))
+            .collect();
+        let arrays = (0..columns.len())
+            .map(|i| postgres && column_types.get(i).is_some_and(|t| t.ends_with("[]")))
+            .collect();
+        Self { target, columns, kinds, arrays, pk_columns: Vec::new(), postgres }
+    }
+
+    /// Primary key columns; required for every kind except INSERT.
+    pub fn with_primary_key(mut self, pk_columns: Vec<String>) -> Self {
+        self.pk_columns = pk_columns;
+        self
+    }
+
+    /// Full values for rows as the results grid shows them, `None` being NULL.
+    ///
+    /// With a primary key, each row is read again from the table by its key.
+    /// Without one the grid's text is used as is, which is refused if a
+    /// binary value in it was shortened.
+    pub async fn lossless_rows(
+        &self,
+        conn: &DatabaseConnection,
+        grid_rows: Vec<Vec<Option<String>>>,
+    ) -> Result<Vec<Vec<Option<String>>>> {
+        if self.pk_columns.is_empty() {
+            for row in &grid_rows {
+                let shortened = row.iter().enumerate().find(|(i, v)| {
+                    self.kinds.get(*i) == Some(&ColumnKind::Bytes) && v.as_deref().is_some_and(|s| s.ends_with('…'))
+                });
+                if let Some((i, _)) = shortened {
+                    return Err(AppError::InvalidInput(format!(
+                        "'{}' is shortened in the grid and {} has no primary key to read it again by",
+                        self.columns[i], self.target
+                    )));
+                }
+            }
+            return Ok(grid_rows);
+        }
+
+        let pk = self.pk_indexes()?;
+        let mut rows = Vec::with_capacity(grid_rows.len());
+        for grid_row in &grid_rows {
+            let sql = format!("SELECT * FROM {} WHERE {}", self.target, self.pk_predicate(&pk, grid_row)?);
+            let row = conn.fetch_rows(&sql).try_next().await?.ok_or_else(|| AppError::InvalidInput(
+                format!("A selected row is no longer in {}", self.target)
+            ))?;
+            let (names, _) = row.columns();
+            let values = row.decode_lossless()?;
+            rows.push(self.columns.iter()
+                .map(|c| names.iter().position(|n| n == c)
+                    .map(|j| values[j].clone())
+                    .ok_or_else(|| AppError::ColumnNotFound(c.clone(), self.target.clone())))
+                .collect::<Result<Vec<_>>>()?);
+        }
+        Ok(rows)
+    }
+
+    /// Statements for `rows`, each ending in `;` and separated by newlines.
+    /// Values must be lossless, as from [`lossless_rows`](Self::lossless_rows).
+    pub fn generate(&self, kind: StatementKind, rows: &[Vec<Option<String>>]) -> Result<String> {
+        if rows.is_empty() {
+            return Err(AppError::InvalidInput("No rows selected".into()));
+        }
+        if let Some(row) = rows.iter().find(|r| r.len() != self.columns.len()) {
+            return Err(AppError::InvalidInput(format!(
+                "Row has {} values but there are {} columns", row.len(), self.columns.len()
+            )));
+        }
+        match kind {
+            StatementKind::Insert => Ok(self.insert(rows)),
+            StatementKind::Upsert => self.upsert(rows),
+            StatementKind::Update => rows.iter().map(|r| self.update(r)).collect::<Result<Vec<_>>>().map(|s| s.join("\n")),
+            StatementKind::Delete => rows.iter().map(|r| self.delete(r)).collect::<Result<Vec<_>>>().map(|s| s.join("\n")),
+        }
+    }
+
+    fn literal(&self, i: usize, value: &Option<String>) -> String {
+        match value.as_deref() {
+            Some(s) if self.arrays[i] => array_literal(s).unwrap_or_else(|| quote_literal(s)),
+            v => sql_literal(self.kinds[i], v, self.postgres),
+        }
+    }
+
+    fn insert_prefix_and_values(&self, rows: &[Vec<Option<String>>]) -> String {
+        let cols: Vec<String> = self.columns.iter().map(|c| quote_ident(c)).collect();
+        let values: Vec<String> = rows.iter()
+            .map(|row| {
+                let vals: Vec<String> = row.iter().enumerate().map(|(i, v)| self.literal(i, v)).collect();
+                format!("({})", vals.join(", "))
+            })
+            .collect();
+        format!("INSERT INTO {} ({}) VALUES\n  {}", self.target, cols.join(", "), values.join(",\n  "))
+    }
+
+    fn insert(&self, rows: &[Vec<Option<String>>]) -> String {
+        format!("{};", self.insert_prefix_and_values(rows))
+    }
+
+    fn upsert(&self, rows: &[Vec<Option<String>>]) -> Result<String> {
+        let pk = self.pk_indexes()?;
+        let conflict: Vec<String> = self.pk_columns.iter().map(|c| quote_ident(c)).collect();
+        let updates: Vec<String> = self.columns.iter().enumerate()
+            .filter(|(i, _)| !pk.contains(i))
+            .map(|(_, c)| format!("{0} = EXCLUDED.{0}", quote_ident(c)))
+            .collect();
+        let action = if updates.is_empty() {
+            "DO NOTHING".to_string()
+        } else {
+            format!("DO UPDATE SET {}", updates.join(", "))
+        };
+        Ok(format!(
+            "{}\nON CONFLICT ({}) {};",
+            self.insert_prefix_and_values(rows), conflict.join(", "), action
+        ))
+    }
+
+    fn update(&self, row: &[Option<String>]) -> Result<String> {
+        let pk = self.pk_indexes()?;
+        let sets: Vec<String> = self.columns.iter().enumerate()
+            .filter(|(i, _)| !pk.contains(i))
+            .map(|(i, c)| format!("{} = {}", quote_ident(c), self.literal(i, &row[i])))
+            .collect();
+        if sets.is_empty() {
+            return Err(AppError::InvalidInput("Every column is part of the primary key; nothing to update".into()));
+        }
+        Ok(format!("UPDATE {} SET {} WHERE {};", self.target, sets.join(", "), self.pk_predicate(&pk, row)?))
+    }
+
+    fn delete(&self, row: &[Option<String>]) -> Result<String> {
+        let pk = self.pk_indexes()?;
+        Ok(format!("DELETE FROM {} WHERE {};", self.target, self.pk_predicate(&pk, row)?))
+    }
+
+    /// Positions of the primary key columns within the row.
+    fn pk_indexes(&self) -> Result<Vec<usize>> {
+        if self.pk_columns.is_empty() {
+            return Err(AppError::InvalidInput(format!("{} has no primary key", self.target)));
+        }
+        self.pk_columns.iter()
+            .map(|pk| self.columns.iter().position(|c| c == pk)
+                .ok_or_else(|| AppError::ColumnNotFound(pk.clone(), self.target.clone())))
+            .collect()
+    }
+
+    fn pk_predicate(&self, pk: &[usize], row: &[Option<String>]) -> Result<String> {
+        pk.iter()
+            .map(|&i| match &row[i] {
+                None => Err(AppError::InvalidInput(format!("Primary key column '{}' is NULL", self.columns[i]))),
+                v => Ok(format!("{} = {}", quote_ident(&self.columns[i]), self.literal(i, v))),
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(|preds| preds.join(" AND "))
+    }
+}
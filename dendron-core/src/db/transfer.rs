@@ -0,0 +1,169 @@
+//! Copy a table (or the result of a SELECT) from one connection into another.
+
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{AppError, Result};
+use super::bulk::{create_table_sql, qualified_name, BulkInserter};
+use super::types::ColumnKind;
+use super::DatabaseConnection;
+
+pub const DEFAULT_TRANSFER_BATCH_SIZE: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TransferSource {
+    /// Every row of `schema.table`.
+    Table { schema: String, table: String },
+    /// The rows of an arbitrary SELECT.
+    Query { sql: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferOptions {
+    /// Destination schema; defaults to `public` (Postgres) or `main` (SQLite).
+    #[serde(default)]
+    pub dest_schema: Option<String>,
+    pub dest_table: String,
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// Create the destination table from the source column types when absent.
+    #[serde(default = "default_true")]
+    pub create_if_missing: bool,
+}
+
+fn default_batch_size() -> usize {
+    DEFAULT_TRANSFER_BATCH_SIZE
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferProgress {
+    pub rows_copied: u64,
+    /// Known up front only for `TransferSource::Table`.
+    pub total_rows: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferSummary {
+    pub rows_copied: u64,
+    pub created_table: bool,
+    pub execution_time_ms: u128,
+}
+
+/// Stream rows out of `source` and insert them into `dest` in batches.
+///
+/// The whole copy runs in one destination transaction: cancelling via `token`
+/// or hitting an error leaves the destination untouched, including any table
+/// this call created. `on_progress` fires after every batch.
+pub async fn transfer_table<F>(
+    source: &DatabaseConnection,
+    dest: &DatabaseConnection,
+    from: &TransferSource,
+    options: &TransferOptions,
+    token: &CancellationToken,
+    mut on_progress: F,
+) -> Result<TransferSummary>
+where
+    F: FnMut(&TransferProgress),
+{
+    let start = std::time::Instant::now();
+    let batch_size = options.batch_size.max(1);
+
+    let (select_sql, total_rows) = match from {
+        TransferSource::Table { schema, table } => {
+            let name = qualified_name(schema, table);
+            let total = source.count_rows(&format!("SELECT COUNT(*) FROM {name}")).await.ok();
+            (format!("SELECT * FROM {name}"), total)
+        }
+        TransferSource::Query { sql } => (sql.trim_end().trim_end_matches(';').to_string(), None),
+    };
+
+    let (source_columns, source_types) = source.describe_columns(&select_sql).await?;
+    if source_columns.is_empty() {
+        return Err(AppError::InvalidInput("Source query returns no columns".into()));
+    }
+
+    let dest_schema = options.dest_schema.clone().unwrap_or_else(|| dest.default_schema().to_string());
+    let exists = dest.get_tables_lazy(&dest_schema).await?
+        .iter()
+        .any(|(name, _)| *name == options.dest_table);
+
+    let columns: Vec<(String, ColumnKind)> = if exists {
+        // Map onto the existing table's columns; every source column must exist.
+        let dest_columns = dest.get_columns_lazy(&dest_schema, &options.dest_table).await?;
+        source_columns.iter().map(|name| {
+            dest_columns.iter()
+                .find(|c| c.name == *name)
+                .map(|c| (name.clone(), ColumnKind::from_type_name(&c.data_type)))
+                .ok_or_else(|| AppError::ColumnNotFound(name.clone(), options.dest_table.clone()))
+        }).collect::<Result<_>>()?
+    } else if options.create_if_missing {
+        source_columns.iter().cloned()
+            .zip(source_types.iter().map(|t| ColumnKind::from_type_name(t)))
+            .collect()
+    } else {
+        return Err(AppError::TableNotFound(options.dest_table.clone()));
+    };
+
+    let mut inserter = BulkInserter::begin(dest, &dest_schema, &options.dest_table, columns.clone()).await?;
+    if !exists {
+        inserter.execute(&create_table_sql(&dest_schema, &options.dest_table, &columns, dest.is_postgres())).await?;
+    }
+
+    let mut progress = TransferProgress { rows_copied: 0, total_rows };
+    let mut stream = source.fetch_rows(&select_sql);
+    let mut batch = Vec::with_capacity(batch_size);
+
+    loop {
+        let next = tokio::select! {
+            _ = token.cancelled() => return Err(AppError::QueryCancelled),
+            next = stream.try_next() => next?,
+        };
+        let done = next.is_none();
+        if let Some(row) = next {
+            batch.push(row.decode_lossless()?);
+        }
+        if batch.len() >= batch_size || (done && !batch.is_empty()) {
+            let inserted = tokio::select! {
+                _ = token.cancelled() => return Err(AppError::QueryCancelled),
+                res = inserter.insert(&batch) => res?,
+            };
+            batch.clear();
+            progress.rows_copied += inserted;
+            on_progress(&progress);
+        }
+        if done {
+            break;
+        }
+    }
+    drop(stream);
+
+    inserter.commit().await?;
+
+    Ok(TransferSummary {
+        rows_copied: progress.rows_copied,
+        created_table: !exists,
+        execution_time_ms: start.elapsed().as_millis(),
+    })
+}
+
+impl DatabaseConnection {
+    /// Schema that unqualified table names resolve to: `public` or `main`.
+    pub fn default_schema(&self) -> &'static str {
+        if self.is_postgres() { "public" } else { "main" }
+    }
+
+    /// Run a single-value `COUNT(*)`-style query.
+    pub async fn count_rows(&self, sql: &str) -> Result<u64> {
+        let count: i64 = match self {
+            DatabaseConnection::Postgres(pool) => sqlx::query_scalar(sql).fetch_one(pool).await?,
+            DatabaseConnection::Sqlite(pool) => sqlx::query_scalar(sql).fetch_one(pool).await?,
+        };
+        Ok(count.max(0) as u64)
+    }
+}
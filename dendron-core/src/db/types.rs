@@ -0,0 +1,110 @@
+//! Backend-neutral column types.
+//!
+//! Driver type names (`INT4`, `TIMESTAMPTZ`, SQLite's declared `VARCHAR(20)`)
+//! and information_schema names (`character varying`) all collapse onto a
+//! [`ColumnKind`], which knows how to spell itself in DDL for either backend.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnKind {
+    SmallInt,
+    Integer,
+    BigInt,
+    Real,
+    Double,
+    Numeric,
+    Boolean,
+    Text,
+    Bytes,
+    Date,
+    Time,
+    Timestamp,
+    TimestampTz,
+    Uuid,
+    Json,
+}
+
+impl ColumnKind {
+    /// Map a driver or catalog type name onto a kind. Unknown types are `Text`.
+    pub fn from_type_name(name: &str) -> Self {
+        // Arrays are carried as JSON text between backends.
+        if Self::is_array_type(name) {
+            return ColumnKind::Json;
+        }
+        let upper = name.trim().to_uppercase();
+        // Strip a length/precision suffix: VARCHAR(20), NUMERIC(10,2).
+        let base = upper.split('(').next().unwrap_or("").trim();
+        match base {
+            "INT2" | "SMALLINT" | "SMALLSERIAL" => ColumnKind::SmallInt,
+            "INT4" | "INT" | "INTEGER" | "SERIAL" | "MEDIUMINT" => ColumnKind::Integer,
+            "INT8" | "BIGINT" | "BIGSERIAL" => ColumnKind::BigInt,
+            "FLOAT4" | "REAL" => ColumnKind::Real,
+            "FLOAT8" | "DOUBLE PRECISION" | "DOUBLE" | "FLOAT" => ColumnKind::Double,
+            "NUMERIC" | "DECIMAL" | "MONEY" => ColumnKind::Numeric,
+            "BOOL" | "BOOLEAN" => ColumnKind::Boolean,
+            "BYTEA" | "BLOB" => ColumnKind::Bytes,
+            "DATE" => ColumnKind::Date,
+            "TIME" | "TIMETZ" | "TIME WITHOUT TIME ZONE" | "TIME WITH TIME ZONE" => ColumnKind::Time,
+            "TIMESTAMP" | "DATETIME" | "TIMESTAMP WITHOUT TIME ZONE" => ColumnKind::Timestamp,
+            "TIMESTAMPTZ" | "TIMESTAMP WITH TIME ZONE" => ColumnKind::TimestampTz,
+            "UUID" => ColumnKind::Uuid,
+            "JSON" | "JSONB" => ColumnKind::Json,
+            _ => ColumnKind::Text,
+        }
+    }
+
+    /// Whether a driver (`INT4[]`) or catalog (`ARRAY`) type name is a
+    /// Postgres array, which [`from_type_name`](Self::from_type_name) folds
+    /// into `Json`.
+    pub fn is_array_type(name: &str) -> bool {
+        let upper = name.trim().to_uppercase();
+        upper.ends_with("[]") || upper == "ARRAY"
+    }
+
+    /// Column type to use in CREATE TABLE on the given backend.
+    pub fn sql_type(&self, postgres: bool) -> &'static str {
+        if postgres {
+            match self {
+                ColumnKind::SmallInt => "smallint",
+                ColumnKind::Integer => "integer",
+                ColumnKind::BigInt => "bigint",
+                ColumnKind::Real => "real",
+                ColumnKind::Double => "double precision",
+                ColumnKind::Numeric => "numeric",
+                ColumnKind::Boolean => "boolean",
+                ColumnKind::Text => "text",
+                ColumnKind::Bytes => "bytea",
+                ColumnKind::Date => "date",
+                ColumnKind::Time => "time",
+                ColumnKind::Timestamp => "timestamp",
+                ColumnKind::TimestampTz => "timestamptz",
+                ColumnKind::Uuid => "uuid",
+                ColumnKind::Json => "jsonb",
+            }
+        } else {
+            // SQLite only cares about affinity; keep declared names readable.
+            match self {
+                ColumnKind::SmallInt | ColumnKind::Integer | ColumnKind::BigInt => "INTEGER",
+                ColumnKind::Boolean => "BOOLEAN",
+                ColumnKind::Real | ColumnKind::Double => "REAL",
+                ColumnKind::Numeric => "NUMERIC",
+                ColumnKind::Bytes => "BLOB",
+                ColumnKind::Date => "DATE",
+                ColumnKind::Time => "TIME",
+                ColumnKind::Timestamp | ColumnKind::TimestampTz => "DATETIME",
+                ColumnKind::Uuid | ColumnKind::Text => "TEXT",
+                ColumnKind::Json => "JSON",
+            }
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(self, ColumnKind::SmallInt | ColumnKind::Integer | ColumnKind::BigInt)
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        self.is_integer() || matches!(self, ColumnKind::Real | ColumnKind::Double | ColumnKind::Numeric)
+    }
+}
@@ -18,6 +18,12 @@ pub enum AppError {
     TransactionError(String),
     #[error("No active database connection")]
     NoConnection,
+    #[error("No active connection for this tab")]
+    NoTabConnection,
+    #[error("Connection '{0}' is not open")]
+    ConnectionNotOpen(String),
+    #[error("{0}")]
+    PolicyViolation(String),
     #[error("Failed to load schema: {0}")]
     SchemaLoadFailed(String),
     #[error("Table '{0}' not found")]
@@ -84,6 +90,12 @@ pub enum AppError {
     #[error(transparent)]
     Csv(#[from] csv::Error),
     #[error(transparent)]
+    Xlsx(#[from] rust_xlsxwriter::XlsxError),
+    #[error(transparent)]
+    Arrow(#[from] arrow::error::ArrowError),
+    #[error(transparent)]
+    Parquet(#[from] parquet::errors::ParquetError),
+    #[error(transparent)]
     Base64(#[from] base64::DecodeError),
 }
 
@@ -0,0 +1,302 @@
+//! Parquet and Arrow IPC writers.
+//!
+//! Column kinds map onto an Arrow schema; rows are buffered into record
+//! batches of `row_group_size` and written one row group at a time, so only
+//! one batch is ever held in memory.
+//!
+//! Numeric columns become `Decimal128(38, 18)`: result columns don't carry
+//! their declared scale, so every value gets 18 fractional digits, rounded
+//! half away from zero beyond that. Only a value with more than 20 integer
+//! digits fails the export.
+
+use std::io::Write;
+use std::sync::Arc;
+
+use arrow::array::{
+    ArrayRef, BinaryArray, BooleanArray, Date32Array, Decimal128Array, Float32Array, Float64Array,
+    Int16Array, Int32Array, Int64Array, StringArray, Time64MicrosecondArray, TimestampMicrosecondArray,
+};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, GzipLevel, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+
+use super::{column_kinds, ExportOptions, RowWriter};
+use crate::db::bulk::decode_hex;
+use crate::db::types::ColumnKind;
+use crate::error::{AppError, Result};
+
+pub const DEFAULT_ROW_GROUP_SIZE: usize = 65_536;
+
+/// Widest decimal Arrow's 128-bit type holds.
+const DECIMAL_PRECISION: u8 = 38;
+
+/// Fractional digits kept for numeric columns.
+const DECIMAL_SCALE: u8 = 18;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParquetCompression {
+    None,
+    #[default]
+    Snappy,
+    Gzip,
+    Zstd,
+    Lz4,
+}
+
+impl ParquetCompression {
+    fn codec(self) -> Compression {
+        match self {
+            ParquetCompression::None => Compression::UNCOMPRESSED,
+            ParquetCompression::Snappy => Compression::SNAPPY,
+            ParquetCompression::Gzip => Compression::GZIP(GzipLevel::default()),
+            ParquetCompression::Zstd => Compression::ZSTD(ZstdLevel::default()),
+            ParquetCompression::Lz4 => Compression::LZ4_RAW,
+        }
+    }
+}
+
+/// Which container the batches go into.
+#[derive(Debug, Clone, Copy)]
+pub enum Container {
+    Parquet(ParquetCompression),
+    /// Arrow IPC file format (Feather v2), uncompressed.
+    ArrowIpc,
+}
+
+enum Sink<W: Write + Send> {
+    Parquet(ArrowWriter<W>),
+    Ipc(FileWriter<W>),
+}
+
+pub struct ColumnarRowWriter<W: Write + Send> {
+    container: Container,
+    row_group_size: usize,
+    /// SQLite stores every integer in 64 bits and every real as a double.
+    sqlite: bool,
+    /// Output until the first batch fixes the schema and opens the sink.
+    out: Option<W>,
+    sink: Option<Sink<W>>,
+    columns: Vec<String>,
+    kinds: Vec<ColumnKind>,
+    schema: Option<SchemaRef>,
+    pending: Vec<Vec<Option<String>>>,
+}
+
+impl<W: Write + Send> ColumnarRowWriter<W> {
+    pub fn new(out: W, container: Container, options: &ExportOptions) -> Self {
+        Self {
+            container,
+            row_group_size: options.row_group_size.max(1),
+            sqlite: !options.postgres,
+            out: Some(out),
+            sink: None,
+            columns: Vec::new(),
+            kinds: Vec::new(),
+            schema: None,
+            pending: Vec::new(),
+        }
+    }
+
+    fn open(&mut self) -> Result<SchemaRef> {
+        if let Some(schema) = &self.schema {
+            return Ok(schema.clone());
+        }
+        let fields: Vec<Field> = self.columns.iter().zip(&self.kinds)
+            .map(|(name, kind)| {
+                let data_type = match kind {
+                    ColumnKind::SmallInt | ColumnKind::Integer if self.sqlite => DataType::Int64,
+                    ColumnKind::Real if self.sqlite => DataType::Float64,
+                    k => arrow_type(*k),
+                };
+                Field::new(name, data_type, true)
+            })
+            .collect();
+        let schema: SchemaRef = Arc::new(Schema::new(fields));
+
+        let out = self.out.take().expect("sink opened once");
+        self.sink = Some(match self.container {
+            Container::Parquet(compression) => {
+                let props = WriterProperties::builder()
+                    .set_compression(compression.codec())
+                    .set_max_row_group_size(self.row_group_size)
+                    .build();
+                Sink::Parquet(ArrowWriter::try_new(out, schema.clone(), Some(props))?)
+            }
+            Container::ArrowIpc => Sink::Ipc(FileWriter::try_new(out, &schema)?),
+        });
+        self.schema = Some(schema.clone());
+        Ok(schema)
+    }
+
+    fn flush_batch(&mut self) -> Result<()> {
+        let schema = self.open()?;
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let rows = std::mem::take(&mut self.pending);
+        let arrays = schema.fields().iter().enumerate()
+            .map(|(i, field)| build_array(field, rows.iter().map(|r| r[i].as_deref())))
+            .collect::<Result<Vec<_>>>()?;
+        let batch = RecordBatch::try_new(schema, arrays)?;
+        match self.sink.as_mut().expect("sink is open") {
+            Sink::Parquet(w) => w.write(&batch)?,
+            Sink::Ipc(w) => w.write(&batch)?,
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write + Send> RowWriter for ColumnarRowWriter<W> {
+    fn begin(&mut self, columns: &[String], column_types: &[String]) -> Result<()> {
+        self.columns = columns.to_vec();
+        self.kinds = column_kinds(column_types);
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[Option<String>]) -> Result<()> {
+        self.pending.push(row.to_vec());
+        if self.pending.len() >= self.row_group_size {
+            self.flush_batch()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.flush_batch()?;
+        match self.sink.take().expect("sink is open") {
+            Sink::Parquet(w) => {
+                w.close()?;
+            }
+            Sink::Ipc(mut w) => {
+                w.finish()?;
+                w.get_mut().flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn arrow_type(kind: ColumnKind) -> DataType {
+    match kind {
+        ColumnKind::SmallInt => DataType::Int16,
+        ColumnKind::Integer => DataType::Int32,
+        ColumnKind::BigInt => DataType::Int64,
+        ColumnKind::Real => DataType::Float32,
+        ColumnKind::Double => DataType::Float64,
+        ColumnKind::Numeric => DataType::Decimal128(DECIMAL_PRECISION, DECIMAL_SCALE as i8),
+        ColumnKind::Boolean => DataType::Boolean,
+        ColumnKind::Bytes => DataType::Binary,
+        ColumnKind::Date => DataType::Date32,
+        ColumnKind::Time => DataType::Time64(TimeUnit::Microsecond),
+        ColumnKind::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, None),
+        ColumnKind::TimestampTz => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+        ColumnKind::Text | ColumnKind::Uuid | ColumnKind::Json => DataType::Utf8,
+    }
+}
+
+fn build_array<'a, I>(field: &Field, values: I) -> Result<ArrayRef>
+where
+    I: Iterator<Item = Option<&'a str>>,
+{
+    // Parse every non-NULL value with `parse`, failing with the column name.
+    fn typed<'a, T, I, F>(field: &Field, values: I, parse: F) -> Result<Vec<Option<T>>>
+    where
+        I: Iterator<Item = Option<&'a str>>,
+        F: Fn(&str) -> Option<T>,
+    {
+        values.map(|v| match v {
+            None => Ok(None),
+            Some(s) => parse(s).map(Some).ok_or_else(|| AppError::InvalidInput(format!(
+                "Column '{}': cannot convert '{}' to {}", field.name(), s, field.data_type()
+            ))),
+        }).collect()
+    }
+
+    let array: ArrayRef = match field.data_type() {
+        DataType::Int16 => Arc::new(Int16Array::from(typed(field, values, |s| s.parse().ok())?)),
+        DataType::Int32 => Arc::new(Int32Array::from(typed(field, values, |s| s.parse().ok())?)),
+        DataType::Int64 => Arc::new(Int64Array::from(typed(field, values, |s| s.parse().ok())?)),
+        DataType::Float32 => Arc::new(Float32Array::from(typed(field, values, |s| s.parse().ok())?)),
+        DataType::Float64 => Arc::new(Float64Array::from(typed(field, values, |s| s.parse().ok())?)),
+        DataType::Decimal128(precision, scale) => {
+            let scale = *scale as u8;
+            let values = typed(field, values, |s| parse_decimal(s, scale))?;
+            Arc::new(Decimal128Array::from(values).with_precision_and_scale(*precision, scale as i8)?)
+        }
+        DataType::Boolean => Arc::new(BooleanArray::from(typed(field, values, |s| match s {
+            "true" | "t" | "1" => Some(true),
+            "false" | "f" | "0" => Some(false),
+            _ => None,
+        })?)),
+        DataType::Binary => {
+            let bytes = typed(field, values, decode_hex)?;
+            Arc::new(BinaryArray::from_iter(bytes))
+        }
+        DataType::Date32 => {
+            let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid date");
+            Arc::new(Date32Array::from(typed(field, values, |s| {
+                NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()
+                    .map(|d| d.signed_duration_since(epoch).num_days() as i32)
+            })?))
+        }
+        DataType::Time64(_) => {
+            let midnight = NaiveTime::from_hms_opt(0, 0, 0).expect("valid time");
+            Arc::new(Time64MicrosecondArray::from(typed(field, values, |s| {
+                NaiveTime::parse_from_str(s, "%H:%M:%S%.f").ok()
+                    .and_then(|t| t.signed_duration_since(midnight).num_microseconds())
+            })?))
+        }
+        DataType::Timestamp(_, None) => Arc::new(TimestampMicrosecondArray::from(typed(field, values, |s| {
+            ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"].iter()
+                .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+                .map(|dt| dt.and_utc().timestamp_micros())
+        })?)),
+        DataType::Timestamp(_, Some(tz)) => {
+            let micros = typed(field, values, |s| {
+                DateTime::parse_from_rfc3339(s).ok()
+                    .or_else(|| DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f%#z").ok())
+                    .map(|dt| dt.timestamp_micros())
+            })?;
+            Arc::new(TimestampMicrosecondArray::from(micros).with_timezone(tz.clone()))
+        }
+        _ => Arc::new(StringArray::from_iter(values)),
+    };
+    Ok(array)
+}
+
+/// Parse a plain decimal string into an integer scaled by `10^scale`,
+/// rounding digits past `scale` half away from zero.
+fn parse_decimal(s: &str, scale: u8) -> Option<i128> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+    if int.is_empty() && frac.is_empty()
+        || !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let frac = frac.trim_end_matches('0');
+    let (kept, dropped) = frac.split_at(frac.len().min(scale as usize));
+    let int = int.trim_start_matches('0');
+    if int.len() + scale as usize > DECIMAL_PRECISION as usize {
+        return None;
+    }
+    let mut text = format!("{int}{kept}");
+    text.extend(std::iter::repeat_n('0', scale as usize - kept.len()));
+    let mut value: i128 = if text.is_empty() { 0 } else { text.parse().ok()? };
+    if dropped.starts_with(['5', '6', '7', '8', '9']) {
+        value += 1;
+    }
+    if value >= 10i128.pow(DECIMAL_PRECISION as u32) {
+        return None;
+    }
+    Some(if negative { -value } else { value })
+}
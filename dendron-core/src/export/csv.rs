@@ -0,0 +1,34 @@
+//! CSV writer.
+
+use std::io::Write;
+
+use super::RowWriter;
+use crate::error::Result;
+
+pub struct CsvRowWriter<W: Write> {
+    inner: ::csv::Writer<W>,
+}
+
+impl<W: Write> CsvRowWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self { inner: ::csv::Writer::from_writer(out) }
+    }
+}
+
+impl<W: Write> RowWriter for CsvRowWriter<W> {
+    fn begin(&mut self, columns: &[String], _column_types: &[String]) -> Result<()> {
+        self.inner.write_record(columns)?;
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[Option<String>]) -> Result<()> {
+        // NULL becomes an empty field, matching what most CSV consumers expect.
+        self.inner.write_record(row.iter().map(|v| v.as_deref().unwrap_or("")))?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.inner.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+//! HTML `<table>` fragment writer.
+
+use std::io::Write;
+
+use super::{column_kinds, RowWriter};
+use crate::db::types::ColumnKind;
+use crate::error::Result;
+
+pub struct HtmlRowWriter<W: Write> {
+    out: W,
+    kinds: Vec<ColumnKind>,
+}
+
+impl<W: Write> HtmlRowWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self { out, kinds: Vec::new() }
+    }
+}
+
+fn escape(value: &str) -> String {
+    let mut s = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => s.push_str("&amp;"),
+            '<' => s.push_str("&lt;"),
+            '>' => s.push_str("&gt;"),
+            '"' => s.push_str("&quot;"),
+            '\'' => s.push_str("&#39;"),
+            c => s.push(c),
+        }
+    }
+    s
+}
+
+impl<W: Write> RowWriter for HtmlRowWriter<W> {
+    fn begin(&mut self, columns: &[String], column_types: &[String]) -> Result<()> {
+        self.kinds = column_kinds(column_types);
+        writeln!(self.out, "<table>")?;
+        write!(self.out, "<thead><tr>")?;
+        for col in columns {
+            write!(self.out, "<th>{}</th>", escape(col))?;
+        }
+        writeln!(self.out, "</tr></thead>")?;
+        writeln!(self.out, "<tbody>")?;
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[Option<String>]) -> Result<()> {
+        write!(self.out, "<tr>")?;
+        for (i, value) in row.iter().enumerate() {
+            let numeric = self.kinds.get(i).is_some_and(|k| k.is_numeric());
+            match value {
+                None => write!(self.out, "<td class=\"null\">NULL</td>")?,
+                Some(v) if numeric => write!(self.out, "<td class=\"num\">{}</td>", escape(v))?,
+                Some(v) => write!(self.out, "<td>{}</td>", escape(v))?,
+            }
+        }
+        writeln!(self.out, "</tr>")?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        writeln!(self.out, "</tbody>")?;
+        writeln!(self.out, "</table>")?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+//! JSON array and NDJSON writers, typed by column kind.
+
+use std::io::Write;
+
+use serde::Serialize;
+
+use super::{column_kinds, RowWriter};
+use crate::db::types::ColumnKind;
+use crate::error::Result;
+
+/// Largest integer a JSON consumer using doubles can hold exactly.
+const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+/// Typed JSON for a lossless cell of the given kind.
+///
+/// Integers become numbers unless they exceed 2^53 - 1, numerics always stay
+/// strings, and JSON columns are embedded as nested values. Anything that
+/// doesn't parse as its declared kind falls back to a string.
+pub fn json_value(kind: ColumnKind, value: Option<&str>) -> serde_json::Value {
+    use serde_json::Value;
+    let Some(s) = value else { return Value::Null };
+    let typed = match kind {
+        ColumnKind::SmallInt | ColumnKind::Integer | ColumnKind::BigInt => s.parse::<i64>().ok()
+            .filter(|n| n.unsigned_abs() <= MAX_SAFE_INTEGER as u64)
+            .map(Value::from),
+        ColumnKind::Real | ColumnKind::Double => s.parse::<f64>().ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number),
+        ColumnKind::Boolean => match s {
+            "true" | "t" | "1" => Some(Value::Bool(true)),
+            "false" | "f" | "0" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        ColumnKind::Json => serde_json::from_str(s).ok(),
+        _ => None,
+    };
+    typed.unwrap_or_else(|| Value::String(s.to_string()))
+}
+
+/// One row as a JSON object, keys in column order.
+struct JsonRecord<'a> {
+    columns: &'a [String],
+    kinds: &'a [ColumnKind],
+    row: &'a [Option<String>],
+}
+
+impl Serialize for JsonRecord<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(Some(self.columns.len()))?;
+        for ((col, kind), val) in self.columns.iter().zip(self.kinds).zip(self.row) {
+            map.serialize_entry(col, &json_value(*kind, val.as_deref()))?;
+        }
+        map.end()
+    }
+}
+
+/// A JSON array of objects, one per row.
+pub struct JsonRowWriter<W: Write> {
+    out: W,
+    columns: Vec<String>,
+    kinds: Vec<ColumnKind>,
+    first: bool,
+}
+
+impl<W: Write> JsonRowWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self { out, columns: Vec::new(), kinds: Vec::new(), first: true }
+    }
+}
+
+impl<W: Write> RowWriter for JsonRowWriter<W> {
+    fn begin(&mut self, columns: &[String], column_types: &[String]) -> Result<()> {
+        self.columns = columns.to_vec();
+        self.kinds = column_kinds(column_types);
+        self.out.write_all(b"[")?;
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[Option<String>]) -> Result<()> {
+        self.out.write_all(if self.first { b"\n  " } else { b",\n  " })?;
+        self.first = false;
+        serde_json::to_writer(&mut self.out, &JsonRecord { columns: &self.columns, kinds: &self.kinds, row })?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.out.write_all(if self.first { b"]\n" } else { b"\n]\n" })?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// One JSON object per line.
+pub struct NdjsonRowWriter<W: Write> {
+    out: W,
+    columns: Vec<String>,
+    kinds: Vec<ColumnKind>,
+}
+
+impl<W: Write> NdjsonRowWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self { out, columns: Vec::new(), kinds: Vec::new() }
+    }
+}
+
+impl<W: Write> RowWriter for NdjsonRowWriter<W> {
+    fn begin(&mut self, columns: &[String], column_types: &[String]) -> Result<()> {
+        self.columns = columns.to_vec();
+        self.kinds = column_kinds(column_types);
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[Option<String>]) -> Result<()> {
+        serde_json::to_writer(&mut self.out, &JsonRecord { columns: &self.columns, kinds: &self.kinds, row })?;
+        self.out.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+//! GitHub-flavoured Markdown table writer, for pasting into PRs and tickets.
+
+use std::io::Write;
+
+use super::{column_kinds, RowWriter};
+use crate::error::Result;
+
+pub struct MarkdownRowWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> MarkdownRowWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self { out }
+    }
+}
+
+/// Escape pipes and fold newlines so a value stays inside its cell.
+fn cell(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+impl<W: Write> RowWriter for MarkdownRowWriter<W> {
+    fn begin(&mut self, columns: &[String], column_types: &[String]) -> Result<()> {
+        let header: Vec<String> = columns.iter().map(|c| cell(c)).collect();
+        writeln!(self.out, "| {} |", header.join(" | "))?;
+        // Right-align numeric columns.
+        let rule: Vec<&str> = column_kinds(column_types).iter()
+            .map(|k| if k.is_numeric() { "---:" } else { "---" })
+            .collect();
+        writeln!(self.out, "| {} |", rule.join(" | "))?;
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[Option<String>]) -> Result<()> {
+        let cells: Vec<String> = row.iter()
+            .map(|v| v.as_deref().map(cell).unwrap_or_else(|| "NULL".to_string()))
+            .collect();
+        writeln!(self.out, "| {} |", cells.join(" | "))?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,320 @@
+//! Writing query results to files.
+//!
+//! Every format implements [`RowWriter`]. [`export_query`] re-runs a SELECT
+//! and streams every row through one straight to disk, so exports aren't
+//! limited to the rows the results grid has loaded; [`export_results`]
+//! renders [`ResultRows`] that are already in memory.
+
+pub mod columnar;
+pub mod csv;
+pub mod html;
+pub mod json;
+pub mod markdown;
+pub mod sql;
+pub mod xlsx;
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use crate::db::types::ColumnKind;
+use crate::db::DatabaseConnection;
+use crate::error::{AppError, Result};
+use crate::query::{split_statements, SqlAnalyzer};
+
+/// Emit a progress update every this many rows.
+pub const PROGRESS_INTERVAL_ROWS: u64 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Ndjson,
+    Markdown,
+    Html,
+    Xlsx,
+    Sql,
+    Parquet,
+    /// Arrow IPC file format.
+    Arrow,
+}
+
+impl ExportFormat {
+    pub fn writer<'a, W: Write + Send + 'a>(&self, out: W, options: &ExportOptions) -> Box<dyn RowWriter + Send + 'a> {
+        match self {
+            ExportFormat::Csv => Box::new(csv::CsvRowWriter::new(out)),
+            ExportFormat::Json => Box::new(json::JsonRowWriter::new(out)),
+            ExportFormat::Ndjson => Box::new(json::NdjsonRowWriter::new(out)),
+            ExportFormat::Markdown => Box::new(markdown::MarkdownRowWriter::new(out)),
+            ExportFormat::Html => Box::new(html::HtmlRowWriter::new(out)),
+            ExportFormat::Xlsx => Box::new(xlsx::XlsxRowWriter::new(out)),
+            ExportFormat::Sql => Box::new(sql::SqlRowWriter::new(out, options)),
+            ExportFormat::Parquet => Box::new(columnar::ColumnarRowWriter::new(
+                out,
+                columnar::Container::Parquet(options.compression),
+                options,
+            )),
+            ExportFormat::Arrow => Box::new(columnar::ColumnarRowWriter::new(
+                out,
+                columnar::Container::ArrowIpc,
+                options,
+            )),
+        }
+    }
+
+    /// Whether the output is text that can go to the clipboard.
+    pub fn is_text(&self) -> bool {
+        !matches!(self, ExportFormat::Xlsx | ExportFormat::Parquet | ExportFormat::Arrow)
+    }
+}
+
+/// Settings used by some formats; the rest ignore them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportOptions {
+    /// Target table of a SQL script.
+    #[serde(default = "default_table")]
+    pub table: String,
+    /// Rows per INSERT statement in a SQL script.
+    #[serde(default = "default_insert_batch_size")]
+    pub insert_batch_size: usize,
+    /// Spell SQL literals for Postgres rather than SQLite. [`export_query`]
+    /// sets this from the connection.
+    #[serde(default)]
+    pub postgres: bool,
+    /// Parquet compression codec.
+    #[serde(default)]
+    pub compression: columnar::ParquetCompression,
+    /// Rows per Parquet row group / Arrow record batch.
+    #[serde(default = "default_row_group_size")]
+    pub row_group_size: usize,
+}
+
+fn default_table() -> String {
+    "exported".to_string()
+}
+
+fn default_insert_batch_size() -> usize {
+    sql::DEFAULT_INSERT_BATCH_SIZE
+}
+
+fn default_row_group_size() -> usize {
+    columnar::DEFAULT_ROW_GROUP_SIZE
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            table: default_table(),
+            insert_batch_size: sql::DEFAULT_INSERT_BATCH_SIZE,
+            postgres: false,
+            compression: columnar::ParquetCompression::default(),
+            row_group_size: columnar::DEFAULT_ROW_GROUP_SIZE,
+        }
+    }
+}
+
+/// The common interface of all exporters: a header, then rows, then a
+/// trailer. Cells are lossless text, `None` for NULL.
+pub trait RowWriter {
+    fn begin(&mut self, columns: &[String], column_types: &[String]) -> Result<()>;
+    fn write_row(&mut self, row: &[Option<String>]) -> Result<()>;
+    fn finish(&mut self) -> Result<()>;
+}
+
+/// Rows held in memory for [`export_results`], read losslessly (see
+/// [`DbRow::decode_lossless`]) rather than taken from the results grid,
+/// whose cells are display text. Collects rows as a [`RowWriter`].
+///
+/// [`DbRow::decode_lossless`]: crate::db::postgres::DbRow::decode_lossless
+#[derive(Debug, Clone, Default)]
+pub struct ResultRows {
+    pub columns: Vec<String>,
+    pub column_types: Vec<String>,
+    /// `None` is NULL.
+    pub rows: Vec<Vec<Option<String>>>,
+}
+
+impl RowWriter for ResultRows {
+    fn begin(&mut self, columns: &[String], column_types: &[String]) -> Result<()> {
+        self.columns = columns.to_vec();
+        self.column_types = column_types.to_vec();
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[Option<String>]) -> Result<()> {
+        self.rows.push(row.to_vec());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportProgress {
+    pub rows_written: u64,
+    pub bytes_written: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSummary {
+    pub rows_written: u64,
+    pub bytes_written: u64,
+    pub execution_time_ms: u128,
+}
+
+/// Run `sql` and stream every row into `path`.
+///
+/// Only a single statement that reads is accepted, since the query runs
+/// again. On error or cancellation the partial file is removed.
+pub async fn export_query<F>(
+    conn: &DatabaseConnection,
+    sql: &str,
+    path: &Path,
+    format: ExportFormat,
+    options: &ExportOptions,
+    token: &CancellationToken,
+    on_progress: F,
+) -> Result<ExportSummary>
+where
+    F: FnMut(&ExportProgress),
+{
+    // sqlx runs every statement in the string, so a trailing write would run
+    // along with the query.
+    if split_statements(sql, conn.dialect()).len() > 1 {
+        return Err(AppError::InvalidInput("Export one statement at a time".into()));
+    }
+    if !SqlAnalyzer::new(conn.dialect(), sql).is_read_only() {
+        return Err(AppError::InvalidInput("Only queries that read can be exported to a file".into()));
+    }
+    let file = File::create(path)
+        .map_err(|e| AppError::FileWriteFailed(path.display().to_string(), e.to_string()))?;
+    let result = stream_to_file(conn, sql, file, format, options, token, on_progress).await;
+    if result.is_err() {
+        let _ = std::fs::remove_file(path);
+    }
+    result
+}
+
+async fn stream_to_file<F>(
+    conn: &DatabaseConnection,
+    sql: &str,
+    file: File,
+    format: ExportFormat,
+    options: &ExportOptions,
+    token: &CancellationToken,
+    mut on_progress: F,
+) -> Result<ExportSummary>
+where
+    F: FnMut(&ExportProgress),
+{
+    let start = std::time::Instant::now();
+    let sql = sql.trim_end().trim_end_matches(';');
+
+    // Column names come from the statement, so an empty result still gets a header.
+    let (columns, column_types) = conn.describe_columns(sql).await?;
+
+    let counter = CountingWriter::new(BufWriter::new(file));
+    let bytes = counter.count.clone();
+    let options = ExportOptions { postgres: conn.is_postgres(), ..options.clone() };
+    let mut writer = format.writer(counter, &options);
+    writer.begin(&columns, &column_types)?;
+
+    let mut progress = ExportProgress { rows_written: 0, bytes_written: 0 };
+    let mut stream = conn.fetch_rows(sql);
+    loop {
+        let row = tokio::select! {
+            _ = token.cancelled() => return Err(AppError::QueryCancelled),
+            row = stream.try_next() => row?,
+        };
+        let Some(row) = row else { break };
+        writer.write_row(&row.decode_lossless()?)?;
+        progress.rows_written += 1;
+        if progress.rows_written.is_multiple_of(PROGRESS_INTERVAL_ROWS) {
+            progress.bytes_written = bytes.load(Ordering::Relaxed);
+            on_progress(&progress);
+        }
+    }
+    drop(stream);
+
+    writer.finish()?;
+    progress.bytes_written = bytes.load(Ordering::Relaxed);
+    on_progress(&progress);
+
+    Ok(ExportSummary {
+        rows_written: progress.rows_written,
+        bytes_written: progress.bytes_written,
+        execution_time_ms: start.elapsed().as_millis(),
+    })
+}
+
+/// Render rows already in memory as text.
+pub fn export_results(results: &ResultRows, format: ExportFormat, options: &ExportOptions) -> Result<String> {
+    if !format.is_text() {
+        return Err(AppError::InvalidInput("This format is binary; save it to a file instead".into()));
+    }
+    let mut buf = Vec::new();
+    write_results(results, format, options, &mut buf)?;
+    String::from_utf8(buf).map_err(|e| AppError::InvalidInput(e.to_string()))
+}
+
+/// Write rows already in memory to `path`.
+pub fn save_results(results: &ResultRows, format: ExportFormat, options: &ExportOptions, path: &Path) -> Result<()> {
+    let file = File::create(path)
+        .map_err(|e| AppError::FileWriteFailed(path.display().to_string(), e.to_string()))?;
+    write_results(results, format, options, BufWriter::new(file))
+}
+
+fn write_results<W: Write + Send>(
+    results: &ResultRows,
+    format: ExportFormat,
+    options: &ExportOptions,
+    out: W,
+) -> Result<()> {
+    if results.rows.is_empty() {
+        return Err(AppError::NoResultsToExport);
+    }
+    let mut writer = format.writer(out, options);
+    writer.begin(&results.columns, &results.column_types)?;
+    for row in &results.rows {
+        writer.write_row(row)?;
+    }
+    writer.finish()
+}
+
+/// Counts bytes on their way to the inner writer.
+struct CountingWriter<W> {
+    inner: W,
+    count: Arc<AtomicU64>,
+}
+
+impl<W> CountingWriter<W> {
+    fn new(inner: W) -> Self {
+        Self { inner, count: Default::default() }
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Driver type names to kinds, for writers that format by type.
+pub(crate) fn column_kinds(column_types: &[String]) -> Vec<ColumnKind> {
+    column_types.iter().map(|t| ColumnKind::from_type_name(t)).collect()
+}
@@ -0,0 +1,79 @@
+//! SQL script writer: multi-row INSERT statements with dialect-aware literals.
+
+use std::io::Write;
+
+use super::{column_kinds, ExportOptions, RowWriter};
+use crate::db::statements::{array_literal, sql_literal};
+use crate::db::types::ColumnKind;
+use crate::error::Result;
+use crate::query::quote_ident;
+
+pub const DEFAULT_INSERT_BATCH_SIZE: usize = 100;
+
+pub struct SqlRowWriter<W: Write> {
+    out: W,
+    table: String,
+    postgres: bool,
+    batch_size: usize,
+    kinds: Vec<ColumnKind>,
+    /// Postgres array columns, carried as JSON text but written as `'{...}'`.
+    arrays: Vec<bool>,
+    /// `INSERT INTO t (cols) VALUES`, written before each batch.
+    prefix: String,
+    rows_in_statement: usize,
+}
+
+impl<W: Write> SqlRowWriter<W> {
+    pub fn new(out: W, options: &ExportOptions) -> Self {
+        Self {
+            out,
+            table: options.table.clone(),
+            postgres: options.postgres,
+            batch_size: options.insert_batch_size.max(1),
+            kinds: Vec::new(),
+            arrays: Vec::new(),
+            prefix: String::new(),
+            rows_in_statement: 0,
+        }
+    }
+}
+
+impl<W: Write> RowWriter for SqlRowWriter<W> {
+    fn begin(&mut self, columns: &[String], column_types: &[String]) -> Result<()> {
+        self.kinds = column_kinds(column_types);
+        self.arrays = column_types.iter().map(|t| self.postgres && ColumnKind::is_array_type(t)).collect();
+        let cols: Vec<String> = columns.iter().map(|c| quote_ident(c)).collect();
+        self.prefix = format!("INSERT INTO {} ({}) VALUES", quote_ident(&self.table), cols.join(", "));
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[Option<String>]) -> Result<()> {
+        if self.rows_in_statement == 0 {
+            write!(self.out, "{}\n  ", self.prefix)?;
+        } else {
+            write!(self.out, ",\n  ")?;
+        }
+        let values: Vec<String> = row.iter().zip(self.kinds.iter().zip(&self.arrays))
+            .map(|(v, (k, array))| match v {
+                Some(s) if *array => array_literal(s).unwrap_or_else(|| sql_literal(*k, v.as_deref(), self.postgres)),
+                _ => sql_literal(*k, v.as_deref(), self.postgres),
+            })
+            .collect();
+        write!(self.out, "({})", values.join(", "))?;
+
+        self.rows_in_statement += 1;
+        if self.rows_in_statement == self.batch_size {
+            writeln!(self.out, ";")?;
+            self.rows_in_statement = 0;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if self.rows_in_statement > 0 {
+            writeln!(self.out, ";")?;
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+}
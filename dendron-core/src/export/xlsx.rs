@@ -0,0 +1,115 @@
+//! XLSX workbook writer: one sheet, bold frozen header row, typed cells.
+//!
+//! Rows are flushed to a temp file as they arrive (constant-memory mode); the
+//! zipped workbook is produced by [`RowWriter::finish`].
+
+use std::io::Write;
+
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
+
+use super::{column_kinds, RowWriter};
+use crate::db::types::ColumnKind;
+use crate::error::{AppError, Result};
+
+/// Rows in a worksheet, including the header.
+const MAX_ROWS: u32 = 1_048_576;
+
+/// Largest integer a spreadsheet cell (a double) holds exactly.
+const MAX_EXACT_INTEGER: i64 = (1 << 53) - 1;
+
+pub struct XlsxRowWriter<W: Write> {
+    out: W,
+    workbook: Workbook,
+    kinds: Vec<ColumnKind>,
+    next_row: u32,
+    date_format: Format,
+    time_format: Format,
+    timestamp_format: Format,
+}
+
+impl<W: Write> XlsxRowWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            workbook: Workbook::new(),
+            kinds: Vec::new(),
+            next_row: 0,
+            date_format: Format::new().set_num_format("yyyy-mm-dd"),
+            time_format: Format::new().set_num_format("hh:mm:ss"),
+            timestamp_format: Format::new().set_num_format("yyyy-mm-dd hh:mm:ss"),
+        }
+    }
+}
+
+impl<W: Write> RowWriter for XlsxRowWriter<W> {
+    fn begin(&mut self, columns: &[String], column_types: &[String]) -> Result<()> {
+        self.kinds = column_kinds(column_types);
+        let bold = Format::new().set_bold();
+        let sheet = self.workbook.add_worksheet_with_constant_memory();
+        sheet.set_freeze_panes(1, 0)?;
+        for (col, name) in (0u16..).zip(columns) {
+            sheet.write_string_with_format(0, col, name, &bold)?;
+        }
+        self.next_row = 1;
+        Ok(())
+    }
+
+    fn write_row(&mut self, row: &[Option<String>]) -> Result<()> {
+        if self.next_row >= MAX_ROWS {
+            return Err(AppError::InvalidInput(format!(
+                "XLSX sheets hold at most {} rows; use CSV or Parquet for larger results",
+                MAX_ROWS - 1
+            )));
+        }
+        let r = self.next_row;
+        let sheet = self.workbook.worksheet_from_index(0)?;
+        for ((col, value), kind) in (0u16..).zip(row).zip(&self.kinds) {
+            let Some(s) = value else { continue };
+            match kind {
+                ColumnKind::SmallInt | ColumnKind::Integer | ColumnKind::BigInt => {
+                    match s.parse::<i64>() {
+                        Ok(n) if n.unsigned_abs() <= MAX_EXACT_INTEGER as u64 => sheet.write_number(r, col, n as f64)?,
+                        _ => sheet.write_string(r, col, s)?,
+                    };
+                }
+                ColumnKind::Real | ColumnKind::Double | ColumnKind::Numeric => {
+                    match s.parse::<f64>() {
+                        Ok(n) if n.is_finite() => sheet.write_number(r, col, n)?,
+                        _ => sheet.write_string(r, col, s)?,
+                    };
+                }
+                ColumnKind::Boolean => {
+                    match s.as_str() {
+                        "true" | "t" | "1" => sheet.write_boolean(r, col, true)?,
+                        "false" | "f" | "0" => sheet.write_boolean(r, col, false)?,
+                        _ => sheet.write_string(r, col, s)?,
+                    };
+                }
+                ColumnKind::Date | ColumnKind::Time | ColumnKind::Timestamp => {
+                    let format = match kind {
+                        ColumnKind::Date => &self.date_format,
+                        ColumnKind::Time => &self.time_format,
+                        _ => &self.timestamp_format,
+                    };
+                    match ExcelDateTime::parse_from_str(s) {
+                        Ok(dt) => sheet.write_datetime_with_format(r, col, &dt, format)?,
+                        Err(_) => sheet.write_string(r, col, s)?,
+                    };
+                }
+                // Excel has no time zones; keep the offset visible as text.
+                _ => {
+                    sheet.write_string(r, col, s)?;
+                }
+            }
+        }
+        self.next_row += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let bytes = self.workbook.save_to_buffer()?;
+        self.out.write_all(&bytes)?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
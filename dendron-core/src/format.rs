@@ -0,0 +1,134 @@
+//! SQL formatting via `sqlformat`.
+//!
+//! Comments survive formatting; a selected range is formatted in place and
+//! re-indented to the column it started at.
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+use sqlformat::{FormatOptions, Indent, QueryParams};
+
+use crate::error::{AppError, Result};
+
+/// Formatter settings, persisted in [`Settings`](crate::config::Settings).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SqlFormatOptions {
+    /// Spaces per indentation level.
+    #[serde(default = "default_indent_width")]
+    pub indent_width: u8,
+    /// Uppercase reserved keywords; when off, keyword case is left alone.
+    #[serde(default = "default_true")]
+    pub uppercase_keywords: bool,
+    /// Blank lines between statements.
+    #[serde(default = "default_lines_between_statements")]
+    pub lines_between_statements: u8,
+    /// Parenthesised blocks and argument lists shorter than this stay on one line.
+    #[serde(default = "default_max_line_width")]
+    pub max_line_width: usize,
+}
+
+fn default_indent_width() -> u8 {
+    2
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_lines_between_statements() -> u8 {
+    1
+}
+
+fn default_max_line_width() -> usize {
+    80
+}
+
+impl Default for SqlFormatOptions {
+    fn default() -> Self {
+        Self {
+            indent_width: default_indent_width(),
+            uppercase_keywords: true,
+            lines_between_statements: default_lines_between_statements(),
+            max_line_width: default_max_line_width(),
+        }
+    }
+}
+
+impl SqlFormatOptions {
+    fn to_sqlformat(&self) -> FormatOptions<'static> {
+        FormatOptions {
+            indent: Indent::Spaces(self.indent_width),
+            uppercase: self.uppercase_keywords.then_some(true),
+            // sqlformat counts line breaks, not blank lines.
+            lines_between_queries: self.lines_between_statements.saturating_add(1),
+            max_inline_block: self.max_line_width,
+            max_inline_arguments: Some(self.max_line_width),
+            max_inline_top_level: Some(self.max_line_width),
+            ..FormatOptions::default()
+        }
+    }
+}
+
+/// Format a whole script.
+pub fn format_sql(sql: &str, options: &SqlFormatOptions) -> String {
+    sqlformat::format(sql, &QueryParams::None, &options.to_sqlformat())
+}
+
+/// Format only `range` (byte offsets) of `sql` and return the whole text.
+///
+/// Whitespace around the selection is kept, and continuation lines are
+/// indented to the column where the selection starts when it begins a line.
+pub fn format_sql_range(sql: &str, range: Range<usize>, options: &SqlFormatOptions) -> Result<String> {
+    if range.start > range.end
+        || range.end > sql.len()
+        || !sql.is_char_boundary(range.start)
+        || !sql.is_char_boundary(range.end)
+    {
+        return Err(AppError::InvalidInput(format!("Invalid range {}..{}", range.start, range.end)));
+    }
+
+    let selected = &sql[range.clone()];
+    let body = selected.trim();
+    if body.is_empty() {
+        return Ok(sql.to_string());
+    }
+    let leading = &selected[..selected.len() - selected.trim_start().len()];
+    let trailing = &selected[selected.trim_end().len()..];
+
+    // Indentation of the line the formatted text starts on, if only
+    // whitespace precedes it there.
+    let body_start = range.start + leading.len();
+    let line_start = sql[..body_start].rfind('\n').map_or(0, |i| i + 1);
+    let prefix = &sql[line_start..body_start];
+    let indent = if prefix.chars().all(char::is_whitespace) { prefix } else { "" };
+
+    let formatted = format_sql(body, options);
+    let reindented = formatted
+        .lines()
+        .enumerate()
+        .map(|(i, line)| if i == 0 || line.is_empty() { line.to_string() } else { format!("{indent}{line}") })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Ok(format!("{}{}{}{}{}", &sql[..range.start], leading, reindented, trailing, &sql[range.end..]))
+}
+
+/// Convert a JavaScript string index (UTF-16 code units) to a byte offset.
+pub fn utf16_to_byte_offset(s: &str, utf16_offset: usize) -> Option<usize> {
+    let mut units = 0;
+    for (byte, c) in s.char_indices() {
+        if units == utf16_offset {
+            return Some(byte);
+        }
+        units += c.len_utf16();
+        if units > utf16_offset {
+            return None;
+        }
+    }
+    (units == utf16_offset).then_some(s.len())
+}
+
+/// Convert a byte offset to a JavaScript string index (UTF-16 code units).
+pub fn byte_to_utf16_offset(s: &str, byte_offset: usize) -> usize {
+    s[..byte_offset.min(s.len())].chars().map(char::len_utf16).sum()
+}
@@ -0,0 +1,241 @@
+//! Query history in a local SQLite database.
+//!
+//! Every statement run from the app is recorded with its outcome in
+//! `history.db` next to the config file. The statement text is indexed with
+//! FTS5; triggers keep the index in step with the table.
+
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions, SqliteRow};
+use sqlx::{Executor, QueryBuilder, Row, Sqlite};
+use std::path::{Path, PathBuf};
+use tokio::sync::OnceCell;
+
+use crate::audit::now_timestamp;
+use crate::config::Config;
+use crate::error::Result;
+
+/// Entries returned when the filter sets no limit.
+pub const DEFAULT_HISTORY_LIMIT: u32 = 200;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS history (
+    id INTEGER PRIMARY KEY,
+    timestamp TEXT NOT NULL,
+    connection TEXT,
+    tab_id INTEGER,
+    sql TEXT NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    row_count INTEGER,
+    success INTEGER NOT NULL,
+    error TEXT
+);
+CREATE INDEX IF NOT EXISTS history_timestamp ON history (timestamp);
+CREATE INDEX IF NOT EXISTS history_connection ON history (connection, timestamp);
+CREATE VIRTUAL TABLE IF NOT EXISTS history_fts USING fts5 (sql, content = 'history', content_rowid = 'id');
+CREATE TRIGGER IF NOT EXISTS history_ai AFTER INSERT ON history BEGIN
+    INSERT INTO history_fts (rowid, sql) VALUES (new.id, new.sql);
+END;
+CREATE TRIGGER IF NOT EXISTS history_ad AFTER DELETE ON history BEGIN
+    INSERT INTO history_fts (history_fts, rowid, sql) VALUES ('delete', old.id, old.sql);
+END;
+";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: i64,
+    /// UTC, `YYYY-MM-DDTHH:MM:SS.mmmZ`.
+    pub timestamp: String,
+    pub connection: Option<String>,
+    pub tab_id: Option<u32>,
+    pub sql: String,
+    pub duration_ms: u64,
+    /// Rows returned by a SELECT, or rows affected by anything else.
+    pub row_count: Option<u64>,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// A finished statement to record; the store assigns id and timestamp.
+#[derive(Debug, Clone)]
+pub struct HistoryRecord<'a> {
+    pub connection: Option<&'a str>,
+    pub tab_id: Option<u32>,
+    pub sql: &'a str,
+    pub duration_ms: u64,
+    pub outcome: std::result::Result<Option<u64>, &'a str>,
+}
+
+/// Which entries to return; every set field must match.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryFilter {
+    /// Words the statement must contain; the last may be a prefix.
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub connection: Option<String>,
+    /// Inclusive lower bound, compared as text (`2024-05-01` works).
+    #[serde(default)]
+    pub since: Option<String>,
+    /// Exclusive upper bound, compared as text.
+    #[serde(default)]
+    pub until: Option<String>,
+    #[serde(default)]
+    pub errors_only: bool,
+    #[serde(default)]
+    pub limit: Option<u32>,
+    #[serde(default)]
+    pub offset: Option<u32>,
+}
+
+pub struct HistoryStore {
+    /// Created on first use, so the store can be opened outside a runtime.
+    pool: OnceCell<SqlitePool>,
+    path: PathBuf,
+}
+
+impl HistoryStore {
+    /// Open (creating if needed) the database at `path`. Connects on first
+    /// use, so this does no I/O beyond creating the parent directory.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(Self { pool: OnceCell::new(), path })
+    }
+
+    async fn pool(&self) -> &SqlitePool {
+        self.pool.get_or_init(|| async {
+            let options = SqliteConnectOptions::new()
+                .filename(&self.path)
+                .create_if_missing(true);
+            SqlitePoolOptions::new()
+                .max_connections(1)
+                .after_connect(|conn, _| Box::pin(async move {
+                    conn.execute(SCHEMA).await?;
+                    Ok(())
+                }))
+                .connect_lazy_with(options)
+        }).await
+    }
+
+    /// `history.db` in the config directory.
+    pub fn open_default() -> Option<Self> {
+        Config::config_dir().and_then(|dir| Self::open(dir.join("history.db")).ok())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Store a finished statement. Returns the new entry's id.
+    pub async fn record(&self, record: &HistoryRecord<'_>) -> Result<i64> {
+        let (row_count, error) = match record.outcome {
+            Ok(rows) => (rows.map(|n| n as i64), None),
+            Err(e) => (None, Some(e)),
+        };
+        let id = sqlx::query(
+            "INSERT INTO history (timestamp, connection, tab_id, sql, duration_ms, row_count, success, error) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(now_timestamp())
+        .bind(record.connection)
+        .bind(record.tab_id)
+        .bind(record.sql)
+        .bind(record.duration_ms as i64)
+        .bind(row_count)
+        .bind(record.outcome.is_ok())
+        .bind(error)
+        .execute(self.pool().await)
+        .await?
+        .last_insert_rowid();
+        Ok(id)
+    }
+
+    /// Add bare statements carried over from the old config-file history,
+    /// given newest first. They get the current time and no outcome.
+    pub async fn import_legacy(&self, queries: &[String]) -> Result<()> {
+        let timestamp = now_timestamp();
+        let mut tx = self.pool().await.begin().await?;
+        for sql in queries.iter().rev() {
+            sqlx::query(
+                "INSERT INTO history (timestamp, sql, duration_ms, success) VALUES (?, ?, 0, 1)",
+            )
+            .bind(&timestamp)
+            .bind(sql)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Matching entries, newest first.
+    pub async fn search(&self, filter: &HistoryFilter) -> Result<Vec<HistoryEntry>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT id, timestamp, connection, tab_id, sql, duration_ms, row_count, success, error \
+             FROM history WHERE 1 = 1",
+        );
+        if let Some(text) = filter.text.as_deref().and_then(fts_query) {
+            query.push(" AND id IN (SELECT rowid FROM history_fts WHERE history_fts MATCH ")
+                .push_bind(text)
+                .push(")");
+        }
+        if let Some(connection) = &filter.connection {
+            query.push(" AND connection = ").push_bind(connection);
+        }
+        if let Some(since) = &filter.since {
+            query.push(" AND timestamp >= ").push_bind(since);
+        }
+        if let Some(until) = &filter.until {
+            query.push(" AND timestamp < ").push_bind(until);
+        }
+        if filter.errors_only {
+            query.push(" AND success = 0");
+        }
+        query.push(" ORDER BY id DESC LIMIT ")
+            .push_bind(filter.limit.unwrap_or(DEFAULT_HISTORY_LIMIT))
+            .push(" OFFSET ")
+            .push_bind(filter.offset.unwrap_or(0));
+
+        let rows = query.build().fetch_all(self.pool().await).await?;
+        rows.iter().map(entry_from_row).collect()
+    }
+
+    pub async fn delete(&self, id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM history WHERE id = ?")
+            .bind(id)
+            .execute(self.pool().await)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn clear(&self) -> Result<()> {
+        sqlx::query("DELETE FROM history").execute(self.pool().await).await?;
+        Ok(())
+    }
+}
+
+fn entry_from_row(row: &SqliteRow) -> Result<HistoryEntry> {
+    Ok(HistoryEntry {
+        id: row.try_get("id")?,
+        timestamp: row.try_get("timestamp")?,
+        connection: row.try_get("connection")?,
+        tab_id: row.try_get("tab_id")?,
+        sql: row.try_get("sql")?,
+        duration_ms: row.try_get::<i64, _>("duration_ms")? as u64,
+        row_count: row.try_get::<Option<i64>, _>("row_count")?.map(|n| n as u64),
+        success: row.try_get("success")?,
+        error: row.try_get("error")?,
+    })
+}
+
+/// FTS5 query matching every word of `text`, the last as a prefix. Words are
+/// quoted so operators and punctuation in user input are taken literally.
+fn fts_query(text: &str) -> Option<String> {
+    let words: Vec<String> = text.split_whitespace()
+        .map(|w| format!("\"{}\"", w.replace('"', "\"\"")))
+        .collect();
+    let last = words.last()?;
+    Some(format!("{} {}*", words[..words.len() - 1].join(" "), last).trim_start().to_string())
+}
@@ -0,0 +1,222 @@
+//! CSV / TSV reader for [`import_rows`](super::import_rows).
+
+use std::fs::File;
+use std::path::Path;
+
+use encoding_rs_io::{DecodeReaderBytes, DecodeReaderBytesBuilder};
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use crate::db::types::ColumnKind;
+use crate::db::DatabaseConnection;
+use crate::error::{AppError, Result};
+use super::{import_rows, infer_kind, ImportOptions, ImportProgress, ImportSummary, RowError, SourceRow};
+
+pub const DEFAULT_SAMPLE_SIZE: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvOptions {
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+    #[serde(default = "default_true")]
+    pub has_header: bool,
+    #[serde(default = "default_quote")]
+    pub quote: char,
+    /// WHATWG encoding label, e.g. `windows-1252` or `shift_jis`. UTF-8 when
+    /// unset; a byte-order mark always wins.
+    #[serde(default)]
+    pub encoding: Option<String>,
+    /// Cells exactly equal to this become NULL.
+    #[serde(default)]
+    pub null_marker: String,
+    /// Rows read to infer column types.
+    #[serde(default = "default_sample_size")]
+    pub sample_size: usize,
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+fn default_quote() -> char {
+    '"'
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_sample_size() -> usize {
+    DEFAULT_SAMPLE_SIZE
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: default_delimiter(),
+            has_header: true,
+            quote: default_quote(),
+            encoding: None,
+            null_marker: String::new(),
+            sample_size: DEFAULT_SAMPLE_SIZE,
+        }
+    }
+}
+
+impl CsvOptions {
+    pub fn tsv() -> Self {
+        Self { delimiter: '\t', ..Self::default() }
+    }
+}
+
+/// Column names, inferred types and the first rows of a file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvPreview {
+    pub columns: Vec<String>,
+    pub kinds: Vec<ColumnKind>,
+    pub rows: Vec<Vec<Option<String>>>,
+    pub errors: Vec<RowError>,
+}
+
+/// Read the header and up to `sample_size` rows, inferring a type per column.
+pub fn preview_csv(path: &Path, options: &CsvOptions) -> Result<CsvPreview> {
+    let (columns, records) = read_csv(path, options)?;
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for record in records.take(options.sample_size) {
+        match record {
+            Ok((_, cells)) => rows.push(cells),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    let kinds = (0..columns.len())
+        .map(|i| infer_kind(rows.iter().filter_map(|r| r.get(i).and_then(|c| c.as_deref()))))
+        .collect();
+
+    Ok(CsvPreview { columns, kinds, rows, errors })
+}
+
+/// Import a CSV/TSV file into a table. Types for a new table are inferred
+/// from the first `sample_size` rows.
+pub async fn import_csv<F>(
+    conn: &DatabaseConnection,
+    path: &Path,
+    csv_options: &CsvOptions,
+    options: &ImportOptions,
+    token: &CancellationToken,
+    on_progress: F,
+) -> Result<ImportSummary>
+where
+    F: FnMut(&ImportProgress),
+{
+    let preview = preview_csv(path, csv_options)?;
+    let (columns, records) = read_csv(path, csv_options)?;
+    import_rows(conn, &columns, &preview.kinds, records, options, token, on_progress).await
+}
+
+/// Open `path` and return its column names plus an iterator of numbered rows.
+///
+/// Without a header row, columns are named `column1`, `column2`, ...
+pub fn read_csv(path: &Path, options: &CsvOptions) -> Result<(Vec<String>, CsvRows)> {
+    if !options.delimiter.is_ascii() || !options.quote.is_ascii() {
+        return Err(AppError::InvalidInput("Delimiter and quote must be ASCII characters".into()));
+    }
+
+    let encoding = match &options.encoding {
+        Some(label) => Some(
+            encoding_rs::Encoding::for_label(label.as_bytes())
+                .ok_or_else(|| AppError::InvalidInput(format!("Unknown encoding '{label}'")))?,
+        ),
+        None => None,
+    };
+
+    let file = File::open(path)
+        .map_err(|e| AppError::FileReadFailed(path.display().to_string(), e.to_string()))?;
+    let decoder = DecodeReaderBytesBuilder::new()
+        .encoding(encoding)
+        .build(file);
+
+    let reader = ::csv::ReaderBuilder::new()
+        .delimiter(options.delimiter as u8)
+        .quote(options.quote as u8)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(decoder);
+
+    let mut records = reader.into_records();
+    let first = match records.next() {
+        Some(record) => Some(record?),
+        None => None,
+    };
+
+    let (columns, pending): (Vec<String>, _) = match first {
+        None => return Err(AppError::InvalidInput(format!("'{}' is empty", path.display()))),
+        Some(header) if options.has_header => {
+            (header.iter().map(|h| h.trim().to_string()).collect(), None)
+        }
+        Some(record) => {
+            let names = (1..=record.len()).map(|i| format!("column{i}")).collect();
+            (names, Some(record))
+        }
+    };
+
+    let rows = CsvRows {
+        records,
+        pending,
+        width: columns.len(),
+        null_marker: options.null_marker.clone(),
+        done: false,
+    };
+    Ok((columns, rows))
+}
+
+/// Numbered rows of a CSV file; see [`read_csv`].
+pub struct CsvRows {
+    records: ::csv::StringRecordsIntoIter<DecodeReaderBytes<File, Vec<u8>>>,
+    /// First data row when the file has no header.
+    pending: Option<::csv::StringRecord>,
+    width: usize,
+    null_marker: String,
+    done: bool,
+}
+
+impl CsvRows {
+    fn convert(&self, record: ::csv::StringRecord) -> SourceRow {
+        let line = record.position().map(|p| p.line()).unwrap_or_default();
+        if record.len() != self.width {
+            return Err(RowError {
+                line,
+                message: format!("expected {} fields, found {}", self.width, record.len()),
+            });
+        }
+        let cells = record.iter()
+            .map(|c| if c == self.null_marker { None } else { Some(c.to_string()) })
+            .collect();
+        Ok((line, cells))
+    }
+}
+
+impl Iterator for CsvRows {
+    type Item = SourceRow;
+
+    fn next(&mut self) -> Option<SourceRow> {
+        if let Some(record) = self.pending.take() {
+            return Some(self.convert(record));
+        }
+        if self.done {
+            return None;
+        }
+        match self.records.next()? {
+            Ok(record) => Some(self.convert(record)),
+            Err(e) => {
+                let line = e.position().map(|p| p.line()).unwrap_or_default();
+                // Bad UTF-8 or quoting is local to one record; I/O errors end the file.
+                if matches!(e.kind(), ::csv::ErrorKind::Io(_)) {
+                    self.done = true;
+                }
+                Some(Err(RowError { line, message: e.to_string() }))
+            }
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! JSON array and newline-delimited JSON reader for [`import_rows`](super::import_rows).
+//!
+//! Each record must be an object. Its top-level keys become columns; nested
+//! objects and arrays are kept as JSON text and land in a `jsonb` (Postgres)
+//! or `JSON` (SQLite) column.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::mpsc::{self, SyncSender};
+
+use serde::de::{Deserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio_util::sync::CancellationToken;
+
+use crate::db::types::ColumnKind;
+use crate::db::DatabaseConnection;
+use crate::error::{AppError, Result};
+use super::{import_rows, infer_kind, ImportOptions, ImportProgress, ImportSummary, RowError};
+
+pub const DEFAULT_SAMPLE_SIZE: usize = 1000;
+
+/// Array elements parsed ahead of the importer.
+const RECORDS_IN_FLIGHT: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonFormat {
+    /// `[` as the first non-whitespace byte means an array, anything else NDJSON.
+    #[default]
+    Auto,
+    Array,
+    Ndjson,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonOptions {
+    #[serde(default)]
+    pub format: JsonFormat,
+    /// Records read to discover columns and infer their types. Keys that first
+    /// appear after the sample are ignored.
+    #[serde(default = "default_sample_size")]
+    pub sample_size: usize,
+}
+
+fn default_sample_size() -> usize {
+    DEFAULT_SAMPLE_SIZE
+}
+
+impl Default for JsonOptions {
+    fn default() -> Self {
+        Self { format: JsonFormat::Auto, sample_size: DEFAULT_SAMPLE_SIZE }
+    }
+}
+
+/// Columns discovered in the sample, with inferred types.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonPreview {
+    pub columns: Vec<String>,
+    pub kinds: Vec<ColumnKind>,
+}
+
+/// Discover columns (union of top-level keys, in first-seen order) and infer
+/// their types from the first `sample_size` records.
+pub fn preview_json(path: &Path, options: &JsonOptions) -> Result<JsonPreview> {
+    let mut columns: Vec<String> = Vec::new();
+    let mut samples: Vec<Vec<&Value>> = Vec::new();
+
+    let mut first_error = None;
+    let records: Vec<Value> = read_records(path, options.format)?
+        .take(options.sample_size)
+        .filter_map(|r| match r {
+            Ok((_, v)) => Some(v),
+            Err(e) => {
+                first_error.get_or_insert(e);
+                None
+            }
+        })
+        .collect();
+
+    for record in &records {
+        let Some(obj) = record.as_object() else { continue };
+        for (key, value) in obj {
+            let i = match columns.iter().position(|c| c == key) {
+                Some(i) => i,
+                None => {
+                    columns.push(key.clone());
+                    samples.push(Vec::new());
+                    columns.len() - 1
+                }
+            };
+            samples[i].push(value);
+        }
+    }
+    if columns.is_empty() {
+        if let Some(e) = first_error {
+            return Err(AppError::InvalidInput(format!("Record {}: {}", e.line, e.message)));
+        }
+        return Err(AppError::InvalidInput(format!("No JSON objects found in '{}'", path.display())));
+    }
+
+    let kinds = samples.iter().map(|values| {
+        if values.iter().any(|v| v.is_object() || v.is_array()) {
+            return ColumnKind::Json;
+        }
+        let texts: Vec<String> = values.iter().filter_map(|v| scalar_text(v)).collect();
+        infer_kind(texts.iter().map(String::as_str))
+    }).collect();
+
+    Ok(JsonPreview { columns, kinds })
+}
+
+/// Import a JSON array or NDJSON file into a table.
+pub async fn import_json<F>(
+    conn: &DatabaseConnection,
+    path: &Path,
+    json_options: &JsonOptions,
+    options: &ImportOptions,
+    token: &CancellationToken,
+    on_progress: F,
+) -> Result<ImportSummary>
+where
+    F: FnMut(&ImportProgress),
+{
+    let preview = preview_json(path, json_options)?;
+    let columns = preview.columns.clone();
+    let rows = read_records(path, json_options.format)?.map(move |record| {
+        let (line, value) = record?;
+        let obj = value.as_object().ok_or_else(|| RowError {
+            line,
+            message: "record is not a JSON object".to_string(),
+        })?;
+        let cells = columns.iter()
+            .map(|c| obj.get(c).and_then(cell_text))
+            .collect();
+        Ok((line, cells))
+    });
+    import_rows(conn, &preview.columns, &preview.kinds, rows, options, token, on_progress).await
+}
+
+/// Text for a scalar; `None` for null and for nested values.
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Cell text for any value: nested objects/arrays are compact JSON.
+fn cell_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::Object(_) | Value::Array(_) => Some(value.to_string()),
+        scalar => scalar_text(scalar),
+    }
+}
+
+type Record = std::result::Result<(u64, Value), RowError>;
+type Records = Box<dyn Iterator<Item = Record> + Send>;
+
+/// Numbered records: line numbers for NDJSON, 1-based indexes for arrays.
+fn read_records(path: &Path, format: JsonFormat) -> Result<Records> {
+    let file = File::open(path)
+        .map_err(|e| AppError::FileReadFailed(path.display().to_string(), e.to_string()))?;
+    let mut reader = BufReader::new(file);
+
+    let format = match format {
+        JsonFormat::Auto => {
+            let starts_with_bracket = loop {
+                let buf = reader.fill_buf()?;
+                match buf.iter().position(|b| !b.is_ascii_whitespace()) {
+                    Some(i) => break buf[i] == b'[',
+                    None if buf.is_empty() => break false,
+                    None => {
+                        let len = buf.len();
+                        reader.consume(len);
+                    }
+                }
+            };
+            if starts_with_bracket { JsonFormat::Array } else { JsonFormat::Ndjson }
+        }
+        f => f,
+    };
+
+    match format {
+        JsonFormat::Array => Ok(array_records(reader)),
+        _ => Ok(Box::new(reader.lines().zip(1..).filter_map(|(line, n)| {
+            match line {
+                Ok(l) if l.trim().is_empty() => None,
+                Ok(l) => Some(serde_json::from_str(&l)
+                    .map(|v| (n, v))
+                    .map_err(|e| RowError { line: n, message: e.to_string() })),
+                Err(e) => Some(Err(RowError { line: n, message: e.to_string() })),
+            }
+        }))),
+    }
+}
+
+/// Elements of a top-level JSON array, parsed one at a time on a reader
+/// thread. A syntax error is reported against the record it falls in and
+/// ends the array, since nothing after it can be located.
+fn array_records(reader: BufReader<File>) -> Records {
+    let (tx, rx) = mpsc::sync_channel(RECORDS_IN_FLIGHT);
+    std::thread::spawn(move || {
+        let mut stopped = false;
+        let result = serde_json::Deserializer::from_reader(reader)
+            .deserialize_seq(RecordSender { tx: &tx, stopped: &mut stopped });
+        if let (Err(e), false) = (result, stopped) {
+            let _ = tx.send(Err(RowError { line: 1, message: e.to_string() }));
+        }
+    });
+    Box::new(rx.into_iter())
+}
+
+/// Sends each array element as it is parsed, stopping early at a bad
+/// element or once the receiver is gone. `stopped` records that the array
+/// was reached, so the error serde reports after an early stop is dropped.
+struct RecordSender<'a> {
+    tx: &'a SyncSender<Record>,
+    stopped: &'a mut bool,
+}
+
+impl<'de> Visitor<'de> for RecordSender<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a JSON array of records")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        *self.stopped = true;
+        for index in 1.. {
+            let record = match seq.next_element::<Value>() {
+                Ok(Some(value)) => Ok((index, value)),
+                Ok(None) => break,
+                Err(e) => Err(RowError { line: index, message: e.to_string() }),
+            };
+            let failed = record.is_err();
+            if self.tx.send(record).is_err() || failed {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,409 @@
+//! Loading external data files into a table.
+//!
+//! Format readers (CSV, JSON) turn a file into column names plus numbered rows
+//! of text cells; [`import_rows`] does the rest: maps columns onto a new or
+//! existing table, validates each value against the column type, and loads
+//! the survivors through [`BulkInserter`] in one transaction.
+
+pub mod csv;
+pub mod json;
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+use tokio_util::sync::CancellationToken;
+
+use crate::db::bulk::{create_table_sql, BulkInserter};
+use crate::db::types::ColumnKind;
+use crate::db::DatabaseConnection;
+use crate::error::{AppError, Result};
+
+pub const DEFAULT_IMPORT_BATCH_SIZE: usize = 1000;
+pub const DEFAULT_MAX_ERRORS: usize = 100;
+
+/// Where imported rows go and how source columns line up with the table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportOptions {
+    /// Defaults to `public` (Postgres) or `main` (SQLite).
+    #[serde(default)]
+    pub schema: Option<String>,
+    pub table: String,
+    /// Create the table from inferred column types when it doesn't exist.
+    #[serde(default)]
+    pub create_table: bool,
+    /// Source column → table column. When empty, every source column maps
+    /// onto the table column of the same name; otherwise unmapped source
+    /// columns are skipped.
+    #[serde(default)]
+    pub column_map: HashMap<String, String>,
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// Abort (and roll back) once this many rows have been rejected.
+    #[serde(default = "default_max_errors")]
+    pub max_errors: usize,
+    /// Validate and return up to this many would-be rows without writing.
+    #[serde(default)]
+    pub dry_run: Option<usize>,
+}
+
+fn default_batch_size() -> usize {
+    DEFAULT_IMPORT_BATCH_SIZE
+}
+
+fn default_max_errors() -> usize {
+    DEFAULT_MAX_ERRORS
+}
+
+/// A source column after mapping: which table column it feeds and as what type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportColumn {
+    pub source: String,
+    pub target: String,
+    pub kind: ColumnKind,
+    /// The target is a Postgres array column (its kind is `Json`). Values
+    /// are JSON arrays or `{a,b}` literals.
+    #[serde(default)]
+    pub array: bool,
+}
+
+/// A rejected row. `line` is the 1-based line in the source file (record
+/// number for JSON arrays).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RowError {
+    pub line: u64,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportProgress {
+    pub rows_imported: u64,
+    pub rows_rejected: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub rows_imported: u64,
+    /// For a dry run: whether the table would be created.
+    pub created_table: bool,
+    pub columns: Vec<ImportColumn>,
+    pub errors: Vec<RowError>,
+    /// Dry run only: the normalized rows that would be inserted, in `columns` order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub preview: Vec<Vec<Option<String>>>,
+    pub execution_time_ms: u128,
+}
+
+/// One source row: its line number and a cell per source column.
+pub type SourceRow = std::result::Result<(u64, Vec<Option<String>>), RowError>;
+
+/// Load `rows` into the table described by `options`.
+///
+/// `columns`/`kinds` describe the source (kinds are the inferred types, used
+/// only when the table has to be created). Rows that fail to parse or don't
+/// fit their column are reported in [`ImportSummary::errors`] and skipped; a
+/// database error aborts the whole import. With [`ImportOptions::dry_run`]
+/// nothing is written and the summary carries the would-be rows instead.
+pub async fn import_rows<I, F>(
+    conn: &DatabaseConnection,
+    columns: &[String],
+    kinds: &[ColumnKind],
+    rows: I,
+    options: &ImportOptions,
+    token: &CancellationToken,
+    mut on_progress: F,
+) -> Result<ImportSummary>
+where
+    I: IntoIterator<Item = SourceRow>,
+    F: FnMut(&ImportProgress),
+{
+    let start = std::time::Instant::now();
+    let batch_size = options.batch_size.max(1);
+
+    let (plan, schema, created_table) = plan_columns(conn, columns, kinds, options).await?;
+    let indexes: Vec<usize> = plan.iter()
+        .map(|c| columns.iter().position(|s| *s == c.source).unwrap_or_default())
+        .collect();
+    let target_columns: Vec<(String, ColumnKind)> = plan.iter()
+        .map(|c| (c.target.clone(), c.kind))
+        .collect();
+
+    if let Some(limit) = options.dry_run {
+        let mut preview = Vec::new();
+        let mut errors = Vec::new();
+        for row in rows {
+            if preview.len() >= limit {
+                break;
+            }
+            match row {
+                Ok((line, cells)) => match prepare_row(&cells, &indexes, &plan) {
+                    Ok(row) => preview.push(row),
+                    Err(message) => errors.push(RowError { line, message }),
+                },
+                Err(e) => errors.push(e),
+            }
+        }
+        return Ok(ImportSummary {
+            rows_imported: 0,
+            created_table,
+            columns: plan,
+            errors,
+            preview,
+            execution_time_ms: start.elapsed().as_millis(),
+        });
+    }
+
+    let mut inserter = BulkInserter::begin(conn, &schema, &options.table, target_columns.clone()).await?;
+    if created_table {
+        inserter.execute(&create_table_sql(&schema, &options.table, &target_columns, conn.is_postgres())).await?;
+    }
+
+    let mut progress = ImportProgress { rows_imported: 0, rows_rejected: 0 };
+    let mut errors = Vec::new();
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut batch_lines = (0, 0);
+
+    let mut rows = rows.into_iter().peekable();
+    while rows.peek().is_some() || !batch.is_empty() {
+        if token.is_cancelled() {
+            return Err(AppError::QueryCancelled);
+        }
+
+        match rows.next() {
+            Some(Ok((line, cells))) => match prepare_row(&cells, &indexes, &plan) {
+                Ok(row) => {
+                    if batch.is_empty() { batch_lines.0 = line; }
+                    batch_lines.1 = line;
+                    batch.push(row);
+                }
+                Err(message) => errors.push(RowError { line, message }),
+            },
+            Some(Err(e)) => errors.push(e),
+            None => {}
+        }
+        progress.rows_rejected = errors.len() as u64;
+        if errors.len() > options.max_errors {
+            return Err(AppError::InvalidInput(format!(
+                "Import aborted after {} rejected rows (last at line {}: {})",
+                errors.len(),
+                errors.last().map(|e| e.line).unwrap_or_default(),
+                errors.last().map(|e| e.message.as_str()).unwrap_or_default(),
+            )));
+        }
+
+        if batch.len() >= batch_size || (rows.peek().is_none() && !batch.is_empty()) {
+            let inserted = tokio::select! {
+                _ = token.cancelled() => return Err(AppError::QueryCancelled),
+                res = inserter.copy_rows(&batch) => res.map_err(|e| AppError::QueryFailed(
+                    format!("Lines {}-{}: {}", batch_lines.0, batch_lines.1, e)
+                ))?,
+            };
+            batch.clear();
+            progress.rows_imported += inserted;
+            on_progress(&progress);
+        }
+    }
+
+    inserter.commit().await?;
+
+    Ok(ImportSummary {
+        rows_imported: progress.rows_imported,
+        created_table,
+        columns: plan,
+        errors,
+        preview: Vec::new(),
+        execution_time_ms: start.elapsed().as_millis(),
+    })
+}
+
+/// Work out which source columns go where. Returns the plan, the resolved
+/// schema, and whether the table must be created.
+pub async fn plan_columns(
+    conn: &DatabaseConnection,
+    columns: &[String],
+    kinds: &[ColumnKind],
+    options: &ImportOptions,
+) -> Result<(Vec<ImportColumn>, String, bool)> {
+    let schema = options.schema.clone().unwrap_or_else(|| conn.default_schema().to_string());
+    let exists = conn.get_tables_lazy(&schema).await?
+        .iter()
+        .any(|(name, _)| *name == options.table);
+
+    let wanted: Vec<(&String, String, ColumnKind)> = columns.iter().zip(kinds)
+        .filter_map(|(source, kind)| {
+            if options.column_map.is_empty() {
+                Some((source, source.clone(), *kind))
+            } else {
+                options.column_map.get(source).map(|target| (source, target.clone(), *kind))
+            }
+        })
+        .collect();
+    if wanted.is_empty() {
+        return Err(AppError::InvalidInput("No source columns are mapped to the table".into()));
+    }
+
+    if !exists {
+        if !options.create_table {
+            return Err(AppError::TableNotFound(options.table.clone()));
+        }
+        let plan = wanted.into_iter()
+            .map(|(source, target, kind)| ImportColumn { source: source.clone(), target, kind, array: false })
+            .collect();
+        return Ok((plan, schema, true));
+    }
+
+    let structure = conn.describe_table(&schema, &options.table).await?;
+    let plan = wanted.into_iter().map(|(source, target, _)| {
+        structure.columns.iter()
+            .find(|c| c.name == target)
+            .map(|c| ImportColumn {
+                source: source.clone(),
+                target: target.clone(),
+                kind: ColumnKind::from_type_name(&c.data_type),
+                array: ColumnKind::is_array_type(&c.data_type),
+            })
+            .ok_or_else(|| AppError::ColumnNotFound(target.clone(), options.table.clone()))
+    }).collect::<Result<_>>()?;
+    Ok((plan, schema, false))
+}
+
+/// Pick and normalize the mapped cells of one source row.
+fn prepare_row(
+    cells: &[Option<String>],
+    indexes: &[usize],
+    plan: &[ImportColumn],
+) -> std::result::Result<Vec<Option<String>>, String> {
+    indexes.iter().zip(plan).map(|(&i, col)| {
+        match cells.get(i).cloned().flatten() {
+            None => Ok(None),
+            Some(v) if col.array => normalize_array(&v)
+                .map(Some)
+                .map_err(|e| format!("column '{}': {}", col.source, e)),
+            Some(v) => normalize_value(col.kind, &v)
+                .map(Some)
+                .map_err(|e| format!("column '{}': {}", col.source, e)),
+        }
+    }).collect()
+}
+
+/// Check `value` fits `kind`, returning it in a form both backends accept.
+pub fn normalize_value(kind: ColumnKind, value: &str) -> std::result::Result<String, String> {
+    let v = value.trim();
+    let ok = match kind {
+        ColumnKind::SmallInt => v.parse::<i16>().is_ok(),
+        ColumnKind::Integer => v.parse::<i32>().is_ok(),
+        ColumnKind::BigInt => v.parse::<i64>().is_ok(),
+        ColumnKind::Real | ColumnKind::Double => v.parse::<f64>().is_ok(),
+        ColumnKind::Numeric => v.parse::<rust_decimal::Decimal>().is_ok() || v.parse::<f64>().is_ok(),
+        ColumnKind::Boolean => {
+            return parse_bool(v)
+                .map(|b| b.to_string())
+                .ok_or_else(|| format!("'{value}' is not a boolean"));
+        }
+        ColumnKind::Date => NaiveDate::parse_from_str(v, "%Y-%m-%d").is_ok(),
+        ColumnKind::Time => NaiveTime::parse_from_str(v, "%H:%M:%S%.f").is_ok()
+            || NaiveTime::parse_from_str(v, "%H:%M").is_ok(),
+        ColumnKind::Timestamp => parse_timestamp(v),
+        ColumnKind::TimestampTz => parse_timestamptz(v) || parse_timestamp(v),
+        ColumnKind::Uuid => sqlx::types::Uuid::parse_str(v).is_ok(),
+        ColumnKind::Json => serde_json::from_str::<serde_json::Value>(v).is_ok(),
+        // Text and binary keep the value verbatim, whitespace included.
+        ColumnKind::Text | ColumnKind::Bytes => return Ok(value.to_string()),
+    };
+    if ok {
+        Ok(v.to_string())
+    } else {
+        Err(format!("'{value}' is not a valid {}", kind.sql_type(true)))
+    }
+}
+
+/// Check `value` is an array for a Postgres array column: a JSON array,
+/// nested for more dimensions, or a `{a,b}` literal.
+fn normalize_array(value: &str) -> std::result::Result<String, String> {
+    let v = value.trim();
+    let ok = matches!(serde_json::from_str(v), Ok(serde_json::Value::Array(_)))
+        || (v.starts_with('{') && v.ends_with('}'));
+    if ok {
+        Ok(v.to_string())
+    } else {
+        Err(format!("'{value}' is not an array"))
+    }
+}
+
+/// Guess the narrowest column kind that every sampled value fits.
+///
+/// Numbers with leading zeros (zip codes, account numbers) stay `Text`.
+pub fn infer_kind<'a>(values: impl IntoIterator<Item = &'a str>) -> ColumnKind {
+    const CANDIDATES: &[ColumnKind] = &[
+        ColumnKind::Boolean,
+        ColumnKind::Integer,
+        ColumnKind::BigInt,
+        ColumnKind::Numeric,
+        ColumnKind::Double,
+        ColumnKind::Date,
+        ColumnKind::Timestamp,
+        ColumnKind::TimestampTz,
+        ColumnKind::Uuid,
+        ColumnKind::Json,
+    ];
+
+    let mut candidates: Vec<ColumnKind> = CANDIDATES.to_vec();
+    let mut seen_any = false;
+    for value in values {
+        let v = value.trim();
+        if v.is_empty() {
+            continue;
+        }
+        seen_any = true;
+        candidates.retain(|kind| fits(*kind, v));
+        if candidates.is_empty() {
+            return ColumnKind::Text;
+        }
+    }
+    if !seen_any {
+        return ColumnKind::Text;
+    }
+    candidates[0]
+}
+
+fn fits(kind: ColumnKind, v: &str) -> bool {
+    let digits = v.strip_prefix('-').unwrap_or(v);
+    let leading_zero = digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.");
+    match kind {
+        // Only words count as booleans; 0/1 columns are integers.
+        ColumnKind::Boolean => parse_bool(v).is_some() && v.parse::<i64>().is_err(),
+        ColumnKind::Integer => !leading_zero && v.parse::<i32>().is_ok(),
+        ColumnKind::BigInt => !leading_zero && v.parse::<i64>().is_ok(),
+        ColumnKind::Numeric => !leading_zero
+            && !v.contains(['e', 'E'])
+            && v.parse::<rust_decimal::Decimal>().is_ok(),
+        ColumnKind::Double => !leading_zero
+            && v.parse::<f64>().is_ok_and(|f| f.is_finite()),
+        ColumnKind::Date => NaiveDate::parse_from_str(v, "%Y-%m-%d").is_ok(),
+        ColumnKind::Timestamp => parse_timestamp(v),
+        ColumnKind::TimestampTz => parse_timestamptz(v),
+        ColumnKind::Uuid => v.len() == 36 && sqlx::types::Uuid::parse_str(v).is_ok(),
+        ColumnKind::Json => (v.starts_with('{') || v.starts_with('['))
+            && serde_json::from_str::<serde_json::Value>(v).is_ok(),
+        _ => false,
+    }
+}
+
+fn parse_bool(v: &str) -> Option<bool> {
+    match v.to_lowercase().as_str() {
+        "true" | "t" | "yes" | "y" | "1" => Some(true),
+        "false" | "f" | "no" | "n" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_timestamp(v: &str) -> bool {
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M"]
+        .iter()
+        .any(|fmt| NaiveDateTime::parse_from_str(v, fmt).is_ok())
+}
+
+fn parse_timestamptz(v: &str) -> bool {
+    DateTime::parse_from_rfc3339(v).is_ok()
+        || DateTime::parse_from_str(v, "%Y-%m-%d %H:%M:%S%.f%#z").is_ok()
+}
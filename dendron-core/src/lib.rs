@@ -1,9 +1,16 @@
+pub mod audit;
 pub mod config;
 pub mod db;
 pub mod error;
+pub mod export;
+pub mod format;
+pub mod history;
+pub mod import;
 pub mod migrations;
+pub mod params;
 pub mod project;
 pub mod query;
 pub mod schema_ops;
 pub mod security;
 pub mod team_config;
+pub mod workspace;
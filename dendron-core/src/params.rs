@@ -0,0 +1,232 @@
+//! Query parameters: placeholders in SQL, bound to typed values at run time.
+//!
+//! Recognized placeholders are `:name`, `$1` / `$name`, `?` / `?1` and
+//! `${var}` template variables. They are found with the sqlparser tokenizer,
+//! so text inside strings, quoted identifiers and comments is never mistaken
+//! for a parameter. Postgres' tokenizer reads a bare `?` as the jsonb
+//! operator, so `?` only counts as a placeholder for SQLite.
+//!
+//! Before running, every placeholder is rewritten to the connection's native
+//! numbered form (`$n` or `?n`) and the values are bound, never spliced into
+//! the SQL text.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+use sqlparser::tokenizer::{Token, Tokenizer};
+use sqlx::query::Query;
+use sqlx::{Database, Encode, Type};
+
+use crate::error::{AppError, Result};
+use crate::query::{LineIndex, SqlDialect};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamKind {
+    /// `:name`, or SQLite's `$name`.
+    Named,
+    /// `$1`, `?1`.
+    Positional,
+    /// A bare `?`, numbered by order of appearance.
+    Anonymous,
+    /// `${var}`.
+    Template,
+}
+
+/// A distinct parameter and everywhere it appears.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryParam {
+    /// As written, e.g. `:id`, `$1`, `${env}`; bare `?` become `?1`, `?2`, ...
+    pub name: String,
+    pub kind: ParamKind,
+    /// Byte ranges of each occurrence in the original SQL.
+    pub ranges: Vec<Range<usize>>,
+}
+
+/// A value for a parameter. Text and NULL are sent as text, so comparing
+/// them with a typed column may need a cast in the SQL (`:since::date`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum ParamValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl fmt::Display for ParamValue {
+    /// SQL-literal form, for logs.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamValue::Null => write!(f, "NULL"),
+            ParamValue::Bool(b) => write!(f, "{}", if *b { "TRUE" } else { "FALSE" }),
+            ParamValue::Int(n) => write!(f, "{}", n),
+            ParamValue::Float(x) => write!(f, "{}", x),
+            ParamValue::Text(s) => write!(f, "'{}'", s.replace('\'', "''")),
+        }
+    }
+}
+
+/// SQL with its placeholders rewritten to the target's native numbered form.
+#[derive(Debug, Clone)]
+pub struct ParameterizedSql {
+    /// Rewritten SQL; unchanged when there are no parameters.
+    pub sql: String,
+    /// In bind order: parameter `i` is `$i+1` / `?i+1` in `sql`.
+    pub params: Vec<QueryParam>,
+}
+
+impl ParameterizedSql {
+    pub fn new(sql: &str, dialect: SqlDialect) -> Self {
+        let params = detect_params(sql, dialect);
+        if params.is_empty() {
+            return Self { sql: sql.to_string(), params };
+        }
+
+        let mut occurrences: Vec<(Range<usize>, usize)> = params.iter()
+            .enumerate()
+            .flat_map(|(i, p)| p.ranges.iter().map(move |r| (r.clone(), i + 1)))
+            .collect();
+        occurrences.sort_by_key(|(r, _)| r.start);
+
+        let mut out = String::with_capacity(sql.len());
+        let mut last = 0;
+        for (range, index) in occurrences {
+            out.push_str(&sql[last..range.start]);
+            match dialect {
+                SqlDialect::Postgres => out.push('$'),
+                SqlDialect::Sqlite | SqlDialect::Generic => out.push('?'),
+            }
+            out.push_str(&index.to_string());
+            last = range.end;
+        }
+        out.push_str(&sql[last..]);
+        Self { sql: out, params }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// Values in bind order, looked up by parameter name.
+    pub fn values(&self, supplied: &HashMap<String, ParamValue>) -> Result<Vec<ParamValue>> {
+        let missing: Vec<&str> = self.params.iter()
+            .filter(|p| !supplied.contains_key(&p.name))
+            .map(|p| p.name.as_str())
+            .collect();
+        if !missing.is_empty() {
+            return Err(AppError::InvalidInput(format!("No value for {}", missing.join(", "))));
+        }
+        Ok(self.params.iter().map(|p| supplied[&p.name].clone()).collect())
+    }
+
+    /// `name = value` for each supplied parameter, for logs.
+    pub fn describe(&self, supplied: &HashMap<String, ParamValue>) -> String {
+        self.params.iter()
+            .filter_map(|p| supplied.get(&p.name).map(|v| format!("{} = {}", p.name, v)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Distinct parameters in order of first appearance. SQL that fails to
+/// tokenize has none.
+pub fn detect_params(sql: &str, dialect: SqlDialect) -> Vec<QueryParam> {
+    let parser_dialect = dialect.parser_dialect();
+    let mut tokens = Vec::new();
+    if Tokenizer::new(parser_dialect.as_ref(), sql)
+        .tokenize_with_location_into_buf(&mut tokens)
+        .is_err()
+    {
+        return Vec::new();
+    }
+
+    let lines = LineIndex::new(sql);
+    let starts: Vec<Option<usize>> = tokens.iter().map(|t| lines.byte_offset(t.location)).collect();
+    // A token ends where the next one starts.
+    let end_of = |i: usize| starts.get(i + 1).copied().flatten().unwrap_or(sql.len());
+
+    let mut params: Vec<QueryParam> = Vec::new();
+    let mut anonymous = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        let Some(start) = starts[i] else { i += 1; continue };
+        let next = |n: usize| tokens.get(i + n).map(|t| &t.token);
+        let found = match &tokens[i].token {
+            Token::Placeholder(p) if p == "$" => match (next(1), next(2), next(3)) {
+                (Some(Token::LBrace), Some(Token::Word(w)), Some(Token::RBrace)) => {
+                    Some((format!("${{{}}}", w.value), ParamKind::Template, 4))
+                }
+                _ => None,
+            },
+            Token::Placeholder(p) if p == "?" => {
+                anonymous += 1;
+                Some((format!("?{}", anonymous), ParamKind::Anonymous, 1))
+            }
+            Token::Placeholder(p) if p[1..].chars().all(|c| c.is_ascii_digit()) => {
+                Some((p.clone(), ParamKind::Positional, 1))
+            }
+            Token::Placeholder(p) => Some((p.clone(), ParamKind::Named, 1)),
+            Token::Colon if can_precede_named(i.checked_sub(1).map(|j| &tokens[j].token)) => {
+                match next(1) {
+                    Some(Token::Word(w)) if w.quote_style.is_none() => {
+                        Some((format!(":{}", w.value), ParamKind::Named, 2))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        match found {
+            Some((name, kind, len)) => {
+                let range = start..end_of(i + len - 1);
+                match params.iter_mut().find(|p| p.name == name) {
+                    Some(param) => param.ranges.push(range),
+                    None => params.push(QueryParam { name, kind, ranges: vec![range] }),
+                }
+                i += len;
+            }
+            None => i += 1,
+        }
+    }
+    params
+}
+
+/// A `:` right after a name, number or closing bracket is part of an
+/// expression (array slices such as `a[lo:hi]`), not a parameter.
+fn can_precede_named(prev: Option<&Token>) -> bool {
+    !matches!(
+        prev,
+        Some(Token::Word(_) | Token::Number(..) | Token::RParen | Token::RBracket
+            | Token::SingleQuotedString(_) | Token::DoubleQuotedString(_))
+    )
+}
+
+/// Bind `values` to `query` in order.
+pub(crate) fn bind_values<'q, DB>(
+    mut query: Query<'q, DB, <DB as Database>::Arguments<'q>>,
+    values: &'q [ParamValue],
+) -> Query<'q, DB, <DB as Database>::Arguments<'q>>
+where
+    DB: Database,
+    bool: Encode<'q, DB> + Type<DB>,
+    i64: Encode<'q, DB> + Type<DB>,
+    f64: Encode<'q, DB> + Type<DB>,
+    &'q str: Encode<'q, DB> + Type<DB>,
+    Option<&'q str>: Encode<'q, DB>,
+{
+    for value in values {
+        query = match value {
+            ParamValue::Null => query.bind(None::<&str>),
+            ParamValue::Bool(b) => query.bind(*b),
+            ParamValue::Int(n) => query.bind(*n),
+            ParamValue::Float(x) => query.bind(*x),
+            ParamValue::Text(s) => query.bind(s.as_str()),
+        };
+    }
+    query
+}
@@ -1,9 +1,20 @@
 //! Project management
 
 use std::path::{Path, PathBuf};
+use crate::config::{normalize_folder, SavedQuery};
 use crate::team_config::TeamConfig;
 use crate::migrations::{MigrationFramework, FRAMEWORKS};
 
+/// Team-shared queries live here, one `.sql` file each; subdirectories are folders.
+pub const PROJECT_QUERIES_DIR: &str = ".dendron/queries";
+
+/// A query discovered in the project's queries directory.
+#[derive(Clone, Debug)]
+pub struct ProjectQuery {
+    pub file: PathBuf,
+    pub query: SavedQuery,
+}
+
 #[derive(Clone, Debug)]
 pub struct Project {
     pub root: PathBuf,
@@ -67,6 +78,47 @@ impl Project {
         self.detected_framework.is_some()
     }
 
+    /// The nearest ancestor of `start` (or `start` itself) holding a
+    /// `.dendron.toml` or a `.dendron` directory.
+    pub fn discover(start: &Path) -> Option<Self> {
+        start.ancestors()
+            .find(|dir| dir.join(".dendron.toml").is_file() || dir.join(".dendron").is_dir())
+            .and_then(Self::open)
+    }
+
+    pub fn queries_dir(&self) -> PathBuf {
+        self.root.join(PROJECT_QUERIES_DIR)
+    }
+
+    /// Every `.sql` file under the queries directory, sorted by path.
+    ///
+    /// Leading `-- key: value` comment lines set `connection`, `tags` (comma
+    /// separated) and `description`; the whole file is the query text.
+    pub fn queries(&self) -> Vec<ProjectQuery> {
+        let dir = self.queries_dir();
+        let mut files = Vec::new();
+        collect_sql_files(&dir, &mut files);
+        files.sort();
+        files.into_iter()
+            .filter_map(|file| {
+                let text = match std::fs::read_to_string(&file) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        eprintln!("Warning: Failed to read {}: {}", file.display(), e);
+                        return None;
+                    }
+                };
+                let name = file.file_stem()?.to_string_lossy().into_owned();
+                let folder = file.parent()
+                    .and_then(|p| p.strip_prefix(&dir).ok())
+                    .and_then(|p| normalize_folder(&p.to_string_lossy()));
+                let mut query = SavedQuery { name, query: text, folder, ..Default::default() };
+                apply_query_header(&mut query);
+                Some(ProjectQuery { file, query })
+            })
+            .collect()
+    }
+
     pub fn relative_path(&self, path: &Path) -> Option<PathBuf> {
         path.strip_prefix(&self.root).ok().map(|p| p.to_path_buf())
     }
@@ -106,6 +158,45 @@ fn find_migration_dir(root: &Path, pattern: &str) -> Option<PathBuf> {
     }
 }
 
+fn collect_sql_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else { continue };
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if file_type.is_dir() {
+            collect_sql_files(&path, out);
+        } else if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("sql")) {
+            out.push(path);
+        }
+    }
+}
+
+/// Read `-- key: value` lines at the top of a query file into its metadata.
+fn apply_query_header(query: &mut SavedQuery) {
+    for line in query.query.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some(comment) = line.strip_prefix("--") else { break };
+        let Some((key, value)) = comment.split_once(':') else { continue };
+        let value = value.trim();
+        match key.trim().to_lowercase().as_str() {
+            "connection" if !value.is_empty() => query.connection = Some(value.to_string()),
+            "description" if !value.is_empty() => query.description = Some(value.to_string()),
+            "tags" => query.tags = value.split(',')
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .collect(),
+            _ => {}
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct ProjectState {
     pub current: Option<Project>,
@@ -1,19 +1,36 @@
 //! Query analysis for detecting destructive statements using sqlparser
 
-use sqlparser::dialect::{PostgreSqlDialect, SQLiteDialect, GenericDialect};
+use sqlparser::dialect::{Dialect, PostgreSqlDialect, SQLiteDialect, GenericDialect};
 use sqlparser::parser::Parser;
-use sqlparser::ast::{Statement, SetExpr, TableFactor, GroupByExpr};
+use sqlparser::ast::{
+    AlterColumnOperation, AlterTableOperation, BinaryOperator, ColumnOption, Expr, GroupByExpr,
+    JoinConstraint, JoinOperator, Query, Select, SelectItem, SetExpr, Statement, TableFactor, Value,
+};
+use sqlparser::tokenizer::{Location, Token, Tokenizer};
+use std::collections::HashMap;
+
+use crate::team_config::EnvironmentPolicy;
+use std::ops::Range;
 
 #[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum QueryType {
+    #[serde(alias = "select")]
     Select,
+    #[serde(alias = "insert")]
     Insert,
+    #[serde(alias = "update")]
     Update,
+    #[serde(alias = "delete")]
     Delete,
+    #[serde(alias = "drop")]
     Drop,
+    #[serde(alias = "truncate")]
     Truncate,
+    #[serde(alias = "alter")]
     Alter,
+    #[serde(alias = "create")]
     Create,
+    #[serde(alias = "other")]
     Other,
 }
 
@@ -43,22 +60,150 @@ impl QueryType {
     }
 }
 
-pub fn analyze_query(sql: &str) -> QueryType {
-    let dialects: Vec<Box<dyn sqlparser::dialect::Dialect>> = vec![
-        Box::new(PostgreSqlDialect {}),
-        Box::new(SQLiteDialect {}),
-        Box::new(GenericDialect {}),
-    ];
-
-    for dialect in dialects {
-        if let Ok(statements) = Parser::parse_sql(dialect.as_ref(), sql) {
-            if let Some(stmt) = statements.first() {
-                return classify_statement(stmt);
+/// The SQL dialect a statement is written in, taken from its connection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SqlDialect {
+    Postgres,
+    Sqlite,
+    Generic,
+}
+
+impl SqlDialect {
+    pub fn parser_dialect(&self) -> Box<dyn Dialect> {
+        match self {
+            SqlDialect::Postgres => Box::new(PostgreSqlDialect {}),
+            SqlDialect::Sqlite => Box::new(SQLiteDialect {}),
+            SqlDialect::Generic => Box::new(GenericDialect {}),
+        }
+    }
+}
+
+/// One parse of a SQL string in a known dialect.
+///
+/// The AST is kept so classification, ORDER BY detection and editability all
+/// answer from the same parse. When the SQL doesn't parse, classification
+/// falls back to the leading keyword.
+pub struct SqlAnalyzer {
+    dialect: SqlDialect,
+    sql: String,
+    statements: std::result::Result<Vec<Statement>, String>,
+}
+
+impl SqlAnalyzer {
+    pub fn new(dialect: SqlDialect, sql: &str) -> Self {
+        let statements = Parser::parse_sql(dialect.parser_dialect().as_ref(), sql)
+            .map_err(|e| e.to_string());
+        Self { dialect, sql: sql.to_string(), statements }
+    }
+
+    /// For SQL with no connection to take a dialect from: the first of
+    /// Postgres, SQLite and generic that parses it.
+    pub fn detect(sql: &str) -> Self {
+        let mut fallback = None;
+        for dialect in [SqlDialect::Postgres, SqlDialect::Sqlite, SqlDialect::Generic] {
+            let analyzer = Self::new(dialect, sql);
+            if analyzer.statements.is_ok() {
+                return analyzer;
             }
+            fallback.get_or_insert(analyzer);
         }
+        fallback.expect("at least one dialect tried")
+    }
+
+    pub fn dialect(&self) -> SqlDialect {
+        self.dialect
+    }
+
+    pub fn sql(&self) -> &str {
+        &self.sql
     }
 
-    analyze_query_fallback(sql)
+    /// The parsed statements, or `None` if the SQL didn't parse.
+    pub fn statements(&self) -> Option<&[Statement]> {
+        self.statements.as_deref().ok()
+    }
+
+    pub fn parse_error(&self) -> Option<&str> {
+        self.statements.as_ref().err().map(String::as_str)
+    }
+
+    /// Type of the first statement.
+    pub fn query_type(&self) -> QueryType {
+        match self.statements() {
+            Some(stmts) => stmts.first().map(classify_statement).unwrap_or(QueryType::Other),
+            None => analyze_query_fallback(&self.sql),
+        }
+    }
+
+    /// Type of every statement, in order.
+    pub fn statement_types(&self) -> Vec<QueryType> {
+        match self.statements() {
+            Some(stmts) => stmts.iter().map(classify_statement).collect(),
+            None => vec![analyze_query_fallback(&self.sql)],
+        }
+    }
+
+    pub fn most_dangerous_type(&self) -> QueryType {
+        most_dangerous(&self.statement_types())
+    }
+
+    /// Whether every statement only reads: queries without `INTO` or data-
+    /// modifying CTEs, EXPLAIN of such queries, and SHOW. SQL that doesn't
+    /// parse never counts as read-only.
+    pub fn is_read_only(&self) -> bool {
+        self.statements().is_some_and(|stmts| !stmts.is_empty() && stmts.iter().all(statement_only_reads))
+    }
+
+    /// Functions the SQL calls whose effects outlive a rolled-back read-only
+    /// transaction: changing settings, signalling other backends, session
+    /// locks, large objects, notifications, and running SQL given as text.
+    pub fn side_effect_calls(&self) -> Vec<String> {
+        let dialect = self.dialect.parser_dialect();
+        let Ok(tokens) = Tokenizer::new(dialect.as_ref(), &self.sql).tokenize() else {
+            return Vec::new();
+        };
+        let mut code = tokens.iter().filter(|t| !matches!(t, Token::Whitespace(_))).peekable();
+        let mut calls = Vec::new();
+        while let Some(token) = code.next() {
+            if let (Token::Word(word), Some(Token::LParen)) = (token, code.peek()) {
+                let name = word.value.to_lowercase();
+                if is_side_effect_function(&name) && !calls.contains(&name) {
+                    calls.push(name);
+                }
+            }
+        }
+        calls
+    }
+
+    /// Whether the first statement is a SELECT with its own ORDER BY.
+    /// Non-SELECTs and unparseable SQL count as ordered, so no warning is shown.
+    pub fn has_top_level_order_by(&self) -> bool {
+        match self.statements().map(|s| s.first()) {
+            Some(Some(Statement::Query(q))) => q.order_by.is_some(),
+            _ => true,
+        }
+    }
+
+    /// Whether the result set maps to a single base table that can be
+    /// UPDATEd, and which one.
+    pub fn editable_info(&self) -> EditableInfo {
+        let Some(stmts) = self.statements() else {
+            return EditableInfo::not_editable("Could not parse SQL");
+        };
+        if stmts.len() != 1 {
+            return EditableInfo::not_editable("Multiple statements");
+        }
+        match &stmts[0] {
+            Statement::Query(q) => check_query_editable(q),
+            _ => EditableInfo::not_editable("Not a SELECT query"),
+        }
+    }
+}
+
+/// Classify SQL whose dialect is unknown; prefer [`SqlAnalyzer::new`].
+pub fn analyze_query(sql: &str) -> QueryType {
+    SqlAnalyzer::detect(sql).query_type()
 }
 
 fn classify_statement(stmt: &Statement) -> QueryType {
@@ -79,6 +224,75 @@ fn classify_statement(stmt: &Statement) -> QueryType {
     }
 }
 
+fn statement_only_reads(stmt: &Statement) -> bool {
+    match stmt {
+        Statement::Query(query) => query_only_reads(query),
+        // EXPLAIN ANALYZE runs the statement, so it must only read too.
+        Statement::Explain { statement, .. } => statement_only_reads(statement),
+        Statement::ExplainTable { .. }
+        | Statement::ShowFunctions { .. }
+        | Statement::ShowVariable { .. }
+        | Statement::ShowStatus { .. }
+        | Statement::ShowVariables { .. }
+        | Statement::ShowCreate { .. }
+        | Statement::ShowColumns { .. }
+        | Statement::ShowDatabases { .. }
+        | Statement::ShowSchemas { .. }
+        | Statement::ShowTables { .. }
+        | Statement::ShowViews { .. }
+        | Statement::ShowCollation { .. } => true,
+        _ => false,
+    }
+}
+
+fn query_only_reads(query: &Query) -> bool {
+    query.with.as_ref().is_none_or(|with| with.cte_tables.iter().all(|cte| query_only_reads(&cte.query)))
+        && set_expr_only_reads(&query.body)
+}
+
+fn set_expr_only_reads(body: &SetExpr) -> bool {
+    match body {
+        SetExpr::Select(select) => select.into.is_none(),
+        SetExpr::Query(query) => query_only_reads(query),
+        SetExpr::SetOperation { left, right, .. } => set_expr_only_reads(left) && set_expr_only_reads(right),
+        SetExpr::Values(_) | SetExpr::Table(_) => true,
+        SetExpr::Insert(_) | SetExpr::Update(_) => false,
+    }
+}
+
+/// Postgres functions that act outside the transaction they run in.
+const SIDE_EFFECT_FUNCTIONS: &[&str] = &[
+    "set_config",
+    "pg_cancel_backend",
+    "pg_terminate_backend",
+    "pg_reload_conf",
+    "pg_rotate_logfile",
+    "pg_switch_wal",
+    "pg_promote",
+    "pg_notify",
+    "pg_log_backend_memory_contexts",
+    "query_to_xml",
+    "query_to_xmlschema",
+    "query_to_xml_and_xmlschema",
+];
+
+/// Families of them, matched by prefix.
+const SIDE_EFFECT_PREFIXES: &[&str] = &[
+    "pg_advisory_",
+    "pg_try_advisory_",
+    "pg_stat_reset",
+    "pg_create_",
+    "pg_drop_replication_slot",
+    "pg_replication_origin_",
+    "pg_file_",
+    "lo_",
+    "dblink",
+];
+
+fn is_side_effect_function(name: &str) -> bool {
+    SIDE_EFFECT_FUNCTIONS.contains(&name) || SIDE_EFFECT_PREFIXES.iter().any(|prefix| name.starts_with(prefix))
+}
+
 fn analyze_query_fallback(sql: &str) -> QueryType {
     let trimmed = sql.trim();
     let first_word = trimmed
@@ -100,50 +314,37 @@ fn analyze_query_fallback(sql: &str) -> QueryType {
     }
 }
 
+/// The riskiest of `types`: DROP first, then TRUNCATE, DELETE, UPDATE, ALTER,
+/// INSERT, CREATE and SELECT. `Other` when none of those is present.
+pub fn most_dangerous(types: &[QueryType]) -> QueryType {
+    [
+        QueryType::Drop,
+        QueryType::Truncate,
+        QueryType::Delete,
+        QueryType::Update,
+        QueryType::Alter,
+        QueryType::Insert,
+        QueryType::Create,
+        QueryType::Select,
+    ]
+    .into_iter()
+    .find(|t| types.contains(t))
+    .unwrap_or(QueryType::Other)
+}
+
+/// See [`SqlAnalyzer::has_top_level_order_by`]; dialect is guessed.
 pub fn has_top_level_order_by(sql: &str) -> bool {
-    let dialects: Vec<Box<dyn sqlparser::dialect::Dialect>> = vec![
-        Box::new(PostgreSqlDialect {}),
-        Box::new(SQLiteDialect {}),
-        Box::new(GenericDialect {}),
-    ];
-    for dialect in dialects {
-        if let Ok(statements) = Parser::parse_sql(dialect.as_ref(), sql) {
-            return match statements.first() {
-                Some(Statement::Query(q)) => q.order_by.is_some(),
-                _ => true, // non-SELECT: no warning needed
-            };
-        }
-    }
-    true // parse failed: assume fine, no warning
+    SqlAnalyzer::detect(sql).has_top_level_order_by()
 }
 
+/// See [`SqlAnalyzer::statement_types`]; dialect is guessed.
 pub fn analyze_multi_statement(sql: &str) -> Vec<QueryType> {
-    let dialects: Vec<Box<dyn sqlparser::dialect::Dialect>> = vec![
-        Box::new(PostgreSqlDialect {}),
-        Box::new(SQLiteDialect {}),
-        Box::new(GenericDialect {}),
-    ];
-
-    for dialect in dialects {
-        if let Ok(statements) = Parser::parse_sql(dialect.as_ref(), sql) {
-            return statements.iter().map(classify_statement).collect();
-        }
-    }
-
-    vec![analyze_query_fallback(sql)]
+    SqlAnalyzer::detect(sql).statement_types()
 }
 
+/// See [`SqlAnalyzer::most_dangerous_type`]; dialect is guessed.
 pub fn most_dangerous_type(sql: &str) -> QueryType {
-    let types = analyze_multi_statement(sql);
-    if types.iter().any(|t| *t == QueryType::Drop) { return QueryType::Drop; }
-    if types.iter().any(|t| *t == QueryType::Truncate) { return QueryType::Truncate; }
-    if types.iter().any(|t| *t == QueryType::Delete) { return QueryType::Delete; }
-    if types.iter().any(|t| *t == QueryType::Update) { return QueryType::Update; }
-    if types.iter().any(|t| *t == QueryType::Alter) { return QueryType::Alter; }
-    if types.iter().any(|t| *t == QueryType::Insert) { return QueryType::Insert; }
-    if types.iter().any(|t| *t == QueryType::Create) { return QueryType::Create; }
-    if types.iter().any(|t| *t == QueryType::Select) { return QueryType::Select; }
-    QueryType::Other
+    SqlAnalyzer::detect(sql).most_dangerous_type()
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
@@ -152,17 +353,104 @@ pub struct QuerySafetyCheck {
     pub is_dangerous_connection: bool,
     pub connection_name: String,
     pub requires_confirmation: bool,
+    /// Environment policy rules the SQL breaks; it must not run.
+    #[serde(default)]
+    pub violations: Vec<String>,
+    /// Text the user has to type before the SQL may run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typed_confirmation: Option<String>,
+    /// Most rows an UPDATE/DELETE may touch, checked with a dry run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_affected_rows: Option<u64>,
 }
 
 impl QuerySafetyCheck {
+    /// Check SQL whose dialect is unknown; prefer [`Self::from_analyzer`].
     pub fn check(sql: &str, connection_name: &str, is_dangerous_connection: bool) -> Self {
-        let query_type = most_dangerous_type(sql);
+        Self::from_analyzer(&SqlAnalyzer::detect(sql), connection_name, is_dangerous_connection)
+    }
+
+    pub fn from_analyzer(analyzer: &SqlAnalyzer, connection_name: &str, is_dangerous_connection: bool) -> Self {
+        Self::from_types(&analyzer.statement_types(), connection_name, is_dangerous_connection)
+    }
+
+    /// Check a write made of statements of `types` without SQL text to
+    /// analyze, like an inline edit or an import.
+    pub fn from_types(types: &[QueryType], connection_name: &str, is_dangerous_connection: bool) -> Self {
+        let query_type = most_dangerous(types);
         let requires_confirmation = query_type.is_destructive() && is_dangerous_connection;
         Self {
             query_type,
             is_dangerous_connection,
             connection_name: connection_name.to_string(),
             requires_confirmation,
+            violations: Vec::new(),
+            typed_confirmation: None,
+            max_affected_rows: None,
+        }
+    }
+
+    /// Apply the connection's environment policy. `database` is what the
+    /// user types when the policy asks for the database name.
+    pub fn with_policy(self, analyzer: &SqlAnalyzer, policy: &EnvironmentPolicy, database: &str) -> Self {
+        // Anything not known to only read counts as a write, so GRANT, COPY,
+        // CALL, SET and SQL that doesn't parse are held back too.
+        let mut check = self.with_write_policy(&analyzer.statement_types(), !analyzer.is_read_only(), policy, database);
+        if policy.require_where {
+            let unfiltered = SqlLinter::new(analyzer.dialect())
+                .lint(analyzer.sql())
+                .into_iter()
+                .filter(|f| matches!(
+                    f.rule,
+                    LintRule::UpdateWithoutWhere | LintRule::DeleteWithoutWhere | LintRule::TautologicalWhere
+                ));
+            for finding in unfiltered {
+                check.violations.push(format!("{} (WHERE is required in this environment)", finding.message));
+            }
+        }
+        check
+    }
+
+    /// The parts of the policy that go by statement type alone: statements
+    /// of `types`, which `writes` data or schema unless it only reads.
+    pub fn with_write_policy(
+        mut self,
+        types: &[QueryType],
+        writes: bool,
+        policy: &EnvironmentPolicy,
+        database: &str,
+    ) -> Self {
+        if policy.read_only && writes {
+            self.violations.push("This environment is read-only".to_string());
+        }
+        for forbidden in &policy.forbidden_statements {
+            if types.contains(forbidden) {
+                self.violations.push(format!(
+                    "{} statements are not allowed in this environment",
+                    format!("{forbidden:?}").to_uppercase()
+                ));
+            }
+        }
+        if policy.confirm_database_name && writes {
+            self.requires_confirmation = true;
+            self.typed_confirmation = Some(database.to_string());
+        }
+        self.max_affected_rows = policy.max_affected_rows;
+        self
+    }
+
+    pub fn is_blocked(&self) -> bool {
+        !self.violations.is_empty()
+    }
+
+    /// Error for an UPDATE/DELETE whose dry run touched too many rows.
+    pub fn check_affected_rows(&self, rows: u64) -> std::result::Result<(), String> {
+        match self.max_affected_rows {
+            Some(max) if rows > max => Err(format!(
+                "This statement would affect {rows} rows; the policy for '{}' allows at most {max}",
+                self.connection_name
+            )),
+            _ => Ok(()),
         }
     }
 
@@ -193,27 +481,10 @@ impl EditableInfo {
 }
 
 /// Analyse a SELECT to determine if its result set maps to a single base table
-/// that can be UPDATEd.  Returns the schema + table if editable.
+/// that can be UPDATEd.  Returns the schema + table if editable.  Dialect is
+/// guessed; prefer [`SqlAnalyzer::editable_info`].
 pub fn extract_source_table(sql: &str) -> EditableInfo {
-    let dialects: Vec<Box<dyn sqlparser::dialect::Dialect>> = vec![
-        Box::new(PostgreSqlDialect {}),
-        Box::new(SQLiteDialect {}),
-        Box::new(GenericDialect {}),
-    ];
-
-    for dialect in dialects {
-        if let Ok(stmts) = Parser::parse_sql(dialect.as_ref(), sql) {
-            if stmts.len() != 1 {
-                return EditableInfo::not_editable("Multiple statements");
-            }
-            return match &stmts[0] {
-                Statement::Query(q) => check_query_editable(q),
-                _ => EditableInfo::not_editable("Not a SELECT query"),
-            };
-        }
-    }
-
-    EditableInfo::not_editable("Could not parse SQL")
+    SqlAnalyzer::detect(sql).editable_info()
 }
 
 fn check_query_editable(query: &sqlparser::ast::Query) -> EditableInfo {
@@ -287,6 +558,380 @@ fn check_query_editable(query: &sqlparser::ast::Query) -> EditableInfo {
     }
 }
 
+/// One statement of a script, located by byte offsets.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct StatementSpan {
+    /// Statement text, trimmed, without its terminating `;`.
+    pub text: String,
+    /// Byte range of `text` in the script.
+    pub range: Range<usize>,
+    /// Byte range the statement owns: from just after the previous `;`
+    /// through its own. Regions of consecutive statements are contiguous.
+    pub region: Range<usize>,
+    /// 1-based lines of the first and last character of `text`.
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// Split a script into statements on top-level semicolons.
+///
+/// Uses sqlparser's tokenizer, so semicolons inside strings, quoted
+/// identifiers, dollar-quoted bodies and (nested) comments don't split.
+/// Regions holding only whitespace and comments are dropped. If the
+/// tokenizer fails (e.g. an unterminated string), everything from the last
+/// semicolon it saw becomes the final statement.
+pub fn split_statements(sql: &str, dialect: SqlDialect) -> Vec<StatementSpan> {
+    let parser_dialect = dialect.parser_dialect();
+    let mut tokens = Vec::new();
+    let failed = Tokenizer::new(parser_dialect.as_ref(), sql)
+        .tokenize_with_location_into_buf(&mut tokens)
+        .is_err();
+
+    let lines = LineIndex::new(sql);
+    let mut spans = Vec::new();
+    let mut region_start = 0;
+    let mut has_code = false;
+    for token in &tokens {
+        match token.token {
+            Token::SemiColon => {
+                let Some(at) = lines.byte_offset(token.location) else { continue };
+                if has_code {
+                    spans.push(lines.span(region_start..at + 1, at));
+                }
+                region_start = at + 1;
+                has_code = false;
+            }
+            Token::Whitespace(_) => {}
+            _ => has_code = true,
+        }
+    }
+    if (has_code || failed) && !sql[region_start..].trim().is_empty() {
+        spans.push(lines.span(region_start..sql.len(), sql.len()));
+    }
+    spans
+}
+
+/// The statement at byte `offset`: the one whose region contains it, the
+/// next one when it falls between statements, or the last one past the end.
+pub fn statement_at(sql: &str, dialect: SqlDialect, offset: usize) -> Option<StatementSpan> {
+    let mut spans = split_statements(sql, dialect);
+    match spans.iter().position(|s| offset < s.region.end) {
+        Some(i) => Some(spans.swap_remove(i)),
+        None => spans.pop(),
+    }
+}
+
+/// Byte offsets of line starts, for mapping tokenizer locations.
+pub(crate) struct LineIndex<'a> {
+    sql: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub(crate) fn new(sql: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(sql.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { sql, starts }
+    }
+
+    /// Tokenizer locations are 1-based lines and 1-based char columns.
+    pub(crate) fn byte_offset(&self, loc: Location) -> Option<usize> {
+        let start = *self.starts.get((loc.line as usize).checked_sub(1)?)?;
+        let col = (loc.column as usize).checked_sub(1)?;
+        self.sql[start..].char_indices().nth(col).map(|(i, _)| start + i)
+    }
+
+    fn line_of(&self, byte: usize) -> usize {
+        self.starts.partition_point(|&s| s <= byte)
+    }
+
+    /// Span for `region`, whose statement text ends at byte `text_end`.
+    fn span(&self, region: Range<usize>, text_end: usize) -> StatementSpan {
+        let sql = self.sql;
+        let raw = &sql[region.start..text_end];
+        let start = region.start + (raw.len() - raw.trim_start().len());
+        let end = region.start + raw.trim_end().len();
+        StatementSpan {
+            text: sql[start..end].to_string(),
+            range: start..end,
+            start_line: self.line_of(start),
+            end_line: self.line_of(end.saturating_sub(1).max(start)),
+            region,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintRule {
+    UpdateWithoutWhere,
+    DeleteWithoutWhere,
+    TautologicalWhere,
+    SelectStarWideTable,
+    DropWithoutIfExists,
+    JoinWithoutCondition,
+    /// Postgres: ALTER that rewrites the table under an ACCESS EXCLUSIVE lock.
+    TableRewrite,
+    /// Postgres: CREATE INDEX without CONCURRENTLY blocks writes.
+    NonConcurrentIndex,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct LintFinding {
+    pub rule: LintRule,
+    pub severity: LintSeverity,
+    pub message: String,
+    /// Index of the offending statement in the script.
+    pub statement: usize,
+    /// Byte range of that statement.
+    pub range: Range<usize>,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// Tables a `SELECT *` reads from, as `(schema, table)` written in the query.
+pub type TableRef = (Option<String>, String);
+
+/// `SELECT *` over more columns than this is flagged.
+pub const DEFAULT_WIDE_TABLE_COLUMNS: usize = 30;
+
+/// Functions whose value differs per row, so a column DEFAULT using them
+/// can't be stored as a constant.
+const VOLATILE_FUNCTIONS: &[&str] = &[
+    "random(", "clock_timestamp(", "timeofday(", "gen_random_uuid(",
+    "uuid_generate_v1(", "uuid_generate_v4(", "nextval(",
+];
+
+/// Walks each statement's AST for patterns that are usually mistakes or
+/// expensive on a live database.
+///
+/// Statements that don't parse produce no findings. Findings point at the
+/// whole statement; the AST carries no finer positions.
+pub struct SqlLinter {
+    dialect: SqlDialect,
+    wide_table_columns: usize,
+    table_widths: HashMap<TableRef, usize>,
+}
+
+impl SqlLinter {
+    pub fn new(dialect: SqlDialect) -> Self {
+        Self { dialect, wide_table_columns: DEFAULT_WIDE_TABLE_COLUMNS, table_widths: HashMap::new() }
+    }
+
+    pub fn with_wide_table_columns(mut self, columns: usize) -> Self {
+        self.wide_table_columns = columns;
+        self
+    }
+
+    /// Column count of a table from [`wildcard_tables`]; tables without one
+    /// are never reported as wide.
+    pub fn with_table_width(mut self, table: TableRef, columns: usize) -> Self {
+        self.table_widths.insert(table, columns);
+        self
+    }
+
+    pub fn lint(&self, sql: &str) -> Vec<LintFinding> {
+        let parser_dialect = self.dialect.parser_dialect();
+        let mut findings = Vec::new();
+        for (index, span) in split_statements(sql, self.dialect).into_iter().enumerate() {
+            let Ok(stmts) = Parser::parse_sql(parser_dialect.as_ref(), &span.text) else { continue };
+            for stmt in &stmts {
+                for (rule, severity, message) in self.check(stmt) {
+                    findings.push(LintFinding {
+                        rule,
+                        severity,
+                        message,
+                        statement: index,
+                        range: span.range.clone(),
+                        start_line: span.start_line,
+                        end_line: span.end_line,
+                    });
+                }
+            }
+        }
+        findings
+    }
+
+    fn check(&self, stmt: &Statement) -> Vec<(LintRule, LintSeverity, String)> {
+        let mut out = Vec::new();
+        match stmt {
+            Statement::Update { selection, .. } => match selection {
+                None => out.push((LintRule::UpdateWithoutWhere, LintSeverity::Error,
+                    "UPDATE without WHERE changes every row".to_string())),
+                Some(expr) if is_tautology(expr) => out.push((LintRule::TautologicalWhere, LintSeverity::Error,
+                    format!("WHERE {expr} is always true; UPDATE changes every row"))),
+                Some(_) => {}
+            },
+            Statement::Delete(delete) => match &delete.selection {
+                None => out.push((LintRule::DeleteWithoutWhere, LintSeverity::Error,
+                    "DELETE without WHERE removes every row".to_string())),
+                Some(expr) if is_tautology(expr) => out.push((LintRule::TautologicalWhere, LintSeverity::Error,
+                    format!("WHERE {expr} is always true; DELETE removes every row"))),
+                Some(_) => {}
+            },
+            Statement::Drop { if_exists: false, object_type, .. } => out.push((
+                LintRule::DropWithoutIfExists, LintSeverity::Info,
+                format!("DROP {object_type} without IF EXISTS fails if it is already gone"),
+            )),
+            Statement::Query(query) => self.check_set_expr(&query.body, &mut out),
+            Statement::CreateIndex(index) if self.dialect == SqlDialect::Postgres && !index.concurrently => out.push((
+                LintRule::NonConcurrentIndex, LintSeverity::Warning,
+                format!("CREATE INDEX blocks writes to {} until it finishes; consider CONCURRENTLY", index.table_name),
+            )),
+            Statement::AlterTable { name, operations, .. } if self.dialect == SqlDialect::Postgres => {
+                for op in operations {
+                    if let Some(reason) = rewrite_reason(op) {
+                        out.push((LintRule::TableRewrite, LintSeverity::Warning,
+                            format!("{reason} rewrites {name} under an ACCESS EXCLUSIVE lock")));
+                    }
+                }
+            }
+            _ => {}
+        }
+        out
+    }
+
+    fn check_set_expr(&self, body: &SetExpr, out: &mut Vec<(LintRule, LintSeverity, String)>) {
+        match body {
+            SetExpr::Select(select) => {
+                if select.from.len() > 1 && select.selection.is_none() {
+                    out.push((LintRule::JoinWithoutCondition, LintSeverity::Warning,
+                        "Tables listed in FROM without a WHERE produce every combination of rows".to_string()));
+                }
+                for from in &select.from {
+                    for join in &from.joins {
+                        if join_lacks_condition(&join.join_operator) {
+                            out.push((LintRule::JoinWithoutCondition, LintSeverity::Warning,
+                                format!("JOIN {} has no real join condition and matches every row", join.relation)));
+                        }
+                    }
+                }
+                if select.projection.iter().any(|item| matches!(item, SelectItem::Wildcard(_))) {
+                    for table in select_tables(select) {
+                        let width = self.table_widths.get(&table).copied().unwrap_or(0);
+                        if width > self.wide_table_columns {
+                            out.push((LintRule::SelectStarWideTable, LintSeverity::Info,
+                                format!("SELECT * reads all {width} columns of {}", table.1)));
+                        }
+                    }
+                }
+            }
+            SetExpr::Query(q) => self.check_set_expr(&q.body, out),
+            SetExpr::SetOperation { left, right, .. } => {
+                self.check_set_expr(left, out);
+                self.check_set_expr(right, out);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Tables read by a `SELECT *` anywhere at the top level of `sql`, to look
+/// up widths for [`SqlLinter::with_table_width`].
+pub fn wildcard_tables(sql: &str, dialect: SqlDialect) -> Vec<TableRef> {
+    fn walk(body: &SetExpr, out: &mut Vec<TableRef>) {
+        match body {
+            SetExpr::Select(select) if select.projection.iter().any(|i| matches!(i, SelectItem::Wildcard(_))) => {
+                for table in select_tables(select) {
+                    if !out.contains(&table) {
+                        out.push(table);
+                    }
+                }
+            }
+            SetExpr::Query(q) => walk(&q.body, out),
+            SetExpr::SetOperation { left, right, .. } => {
+                walk(left, out);
+                walk(right, out);
+            }
+            _ => {}
+        }
+    }
+    let mut out = Vec::new();
+    if let Some(stmts) = SqlAnalyzer::new(dialect, sql).statements() {
+        for stmt in stmts {
+            if let Statement::Query(q) = stmt {
+                walk(&q.body, &mut out);
+            }
+        }
+    }
+    out
+}
+
+/// Plain tables in a SELECT's FROM list and joins.
+fn select_tables(select: &Select) -> Vec<TableRef> {
+    select.from.iter()
+        .flat_map(|from| std::iter::once(&from.relation).chain(from.joins.iter().map(|j| &j.relation)))
+        .filter_map(|factor| match factor {
+            TableFactor::Table { name, .. } => match name.0.as_slice() {
+                [table] => Some((None, table.value.clone())),
+                [.., schema, table] => Some((Some(schema.value.clone()), table.value.clone())),
+                [] => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+fn join_lacks_condition(op: &JoinOperator) -> bool {
+    match op {
+        JoinOperator::Inner(c)
+        | JoinOperator::LeftOuter(c)
+        | JoinOperator::RightOuter(c)
+        | JoinOperator::FullOuter(c) => match c {
+            JoinConstraint::None => true,
+            JoinConstraint::On(expr) => is_tautology(expr),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Whether a WHERE clause holds for every (non-NULL) row: `1 = 1`, `TRUE`,
+/// `x = x`, or an OR with such a branch.
+fn is_tautology(expr: &Expr) -> bool {
+    match expr {
+        Expr::Value(Value::Boolean(b)) => *b,
+        Expr::Value(Value::Number(n, _)) => n.parse::<f64>().is_ok_and(|n| n != 0.0),
+        Expr::Nested(inner) => is_tautology(inner),
+        Expr::BinaryOp { left, op: BinaryOperator::Or, right } => is_tautology(left) || is_tautology(right),
+        Expr::BinaryOp { left, op: BinaryOperator::And, right } => is_tautology(left) && is_tautology(right),
+        Expr::BinaryOp { left, op: BinaryOperator::Eq | BinaryOperator::GtEq | BinaryOperator::LtEq, right } => {
+            left == right && !is_volatile(left)
+        }
+        _ => false,
+    }
+}
+
+fn is_volatile(expr: &Expr) -> bool {
+    let text = expr.to_string().to_lowercase();
+    VOLATILE_FUNCTIONS.iter().any(|f| text.contains(f))
+}
+
+/// Why an ALTER TABLE operation forces a full table rewrite on Postgres.
+fn rewrite_reason(op: &AlterTableOperation) -> Option<String> {
+    match op {
+        AlterTableOperation::AddColumn { column_def, .. } => column_def.options.iter().find_map(|opt| match &opt.option {
+            ColumnOption::Default(expr) if is_volatile(expr) => {
+                Some(format!("Adding column {} with volatile default {expr}", column_def.name))
+            }
+            _ => None,
+        }),
+        AlterTableOperation::AlterColumn { column_name, op: AlterColumnOperation::SetDataType { .. } } => {
+            Some(format!("Changing the type of {column_name}"))
+        }
+        _ => None,
+    }
+}
+
 /// Quote a SQL identifier, escaping embedded double-quotes.
 pub fn quote_ident(name: &str) -> String {
     format!("\"{}\"", name.replace('"', "\"\""))
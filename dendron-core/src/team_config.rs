@@ -4,6 +4,7 @@ use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 use std::env;
 use crate::error::Result;
+use crate::query::QueryType;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -14,6 +15,51 @@ pub struct TeamConfig {
     pub connections: Vec<TeamConnection>,
     #[serde(default)]
     pub environments: HashMap<String, Vec<TeamConnection>>,
+    /// Guardrails per environment name, e.g. `[policies.production]`.
+    #[serde(default)]
+    pub policies: HashMap<String, EnvironmentPolicy>,
+}
+
+/// Rules enforced on every query against connections in an environment.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct EnvironmentPolicy {
+    /// Reject anything that writes or changes the schema.
+    #[serde(default)]
+    pub read_only: bool,
+    /// Statement types that may not run at all, e.g. `["drop", "truncate"]`.
+    #[serde(default)]
+    pub forbidden_statements: Vec<QueryType>,
+    /// Reject UPDATE/DELETE without a (non-trivial) WHERE.
+    #[serde(default)]
+    pub require_where: bool,
+    /// Reject UPDATE/DELETE that would touch more rows than this.
+    #[serde(default)]
+    pub max_affected_rows: Option<u64>,
+    /// Make the user type the database name before anything that writes.
+    #[serde(default)]
+    pub confirm_database_name: bool,
+}
+
+impl EnvironmentPolicy {
+    /// The stricter of two policies, rule by rule.
+    pub fn merge(&self, other: &Self) -> Self {
+        let mut forbidden = self.forbidden_statements.clone();
+        for t in &other.forbidden_statements {
+            if !forbidden.contains(t) {
+                forbidden.push(t.clone());
+            }
+        }
+        Self {
+            read_only: self.read_only || other.read_only,
+            forbidden_statements: forbidden,
+            require_where: self.require_where || other.require_where,
+            max_affected_rows: match (self.max_affected_rows, other.max_affected_rows) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            },
+            confirm_database_name: self.confirm_database_name || other.confirm_database_name,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,6 +89,9 @@ pub struct TeamConnection {
     pub requires_vpn: bool,
     #[serde(default)]
     pub connection_string: Option<String>,
+    /// Sessions are opened read-only, so writes fail.
+    #[serde(default)]
+    pub read_only: bool,
 }
 
 impl TeamConfig {
@@ -81,6 +130,30 @@ impl TeamConfig {
         all
     }
 
+    /// Whether a connection listed here, under any environment, is marked
+    /// read-only.
+    pub fn is_read_only(&self, connection_name: &str) -> bool {
+        self.all_connections().iter().any(|c| c.name == connection_name && c.read_only)
+    }
+
+    /// Policy for a connection: every policy whose environment matches one of
+    /// its tags, or the environment it is listed under in this file, merged.
+    /// Names compare case-insensitively.
+    pub fn policy_for(&self, connection_name: &str, tags: &[String]) -> EnvironmentPolicy {
+        let mut envs: Vec<String> = tags.iter().map(|t| t.to_lowercase()).collect();
+        for conn in self.connections.iter().filter(|c| c.name == connection_name) {
+            envs.extend(conn.environment.as_ref().map(|e| e.to_lowercase()));
+        }
+        for (env, conns) in &self.environments {
+            if conns.iter().any(|c| c.name == connection_name) {
+                envs.push(env.to_lowercase());
+            }
+        }
+        self.policies.iter()
+            .filter(|(env, _)| envs.contains(&env.to_lowercase()))
+            .fold(EnvironmentPolicy::default(), |acc, (_, policy)| acc.merge(policy))
+    }
+
     pub fn connections_for_env(&self, env: &str) -> Vec<&TeamConnection> {
         self.environments
             .get(env)
@@ -105,6 +178,7 @@ impl TeamConnection {
             ssl_mode: self.ssl_mode.clone(),
             requires_vpn: self.requires_vpn,
             connection_string: self.connection_string.as_ref().map(|s| resolve_env(s)),
+            read_only: self.read_only,
         }
     }
 
@@ -0,0 +1,1069 @@
+//! The workspace: configuration, open connections and tabs, independent of
+//! any UI.
+//!
+//! A host (the desktop app, a CLI, an MCP server) keeps one [`Workspace`] and
+//! calls into it. Connections are opened by name from the saved config and
+//! live until closed; tabs refer to them by name and carry only their query
+//! lifecycle, so one connection can serve many tabs.
+
+mod snapshot;
+
+pub use snapshot::*;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+
+use futures::TryStreamExt;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+use crate::audit::{AuditEntry, AuditLog, PendingAudit};
+use crate::config::{Config, SavedConnection};
+use crate::db::bulk::qualified_name;
+use crate::db::connection::{ConnectionConfig, DatabaseConnection};
+use crate::db::dry_run::DryRunResult;
+use crate::db::postgres::{FetchProgress, QueryResult, DEFAULT_ROW_LIMIT};
+use crate::db::ssh::SshTunnel;
+use crate::db::transfer::{self, TransferOptions, TransferProgress, TransferSource, TransferSummary};
+use crate::db::statements::quote_literal;
+use crate::error::{AppError, Result};
+use crate::export::{self, ExportFormat, ExportOptions, ExportProgress, ExportSummary, ResultRows, RowWriter};
+use crate::history::{HistoryRecord, HistoryStore};
+use crate::import::csv::CsvOptions;
+use crate::import::json::JsonOptions;
+use crate::import::{self, ImportOptions, ImportProgress, ImportSummary};
+use crate::params::{ParamValue, ParameterizedSql};
+use crate::project::Project;
+use crate::query::{most_dangerous, quote_ident, QuerySafetyCheck, QueryType, SqlAnalyzer, SqlDialect};
+use crate::team_config::{EnvironmentPolicy, TeamConfig};
+
+/// An open, live database connection owned at the workspace level.
+/// Lives until explicitly closed — not tied to any tab lifecycle.
+pub struct OpenConnection {
+    pub conn: Arc<DatabaseConnection>,
+    pub is_dangerous: bool,
+    /// Saved tags, copied into audit entries.
+    pub tags: Vec<String>,
+    /// Guardrails from `.dendron.toml` for this connection's environment.
+    pub policy: EnvironmentPolicy,
+    /// Database name, typed to confirm writes when the policy asks for it.
+    pub database: String,
+    /// SSH tunnel kept alive for the lifetime of this connection.
+    pub _ssh_tunnel: Option<SshTunnel>,
+}
+
+impl OpenConnection {
+    /// Safety check for SQL on this connection, with its environment policy.
+    pub fn safety_check(&self, conn_name: &str, analyzer: &SqlAnalyzer) -> QuerySafetyCheck {
+        QuerySafetyCheck::from_analyzer(analyzer, conn_name, self.is_dangerous)
+            .with_policy(analyzer, &self.policy, &self.database)
+    }
+
+    /// Safety check for a write made without SQL text, running statements of
+    /// `types`: an inline edit, import or transfer.
+    pub fn write_check(&self, conn_name: &str, types: &[QueryType]) -> QuerySafetyCheck {
+        QuerySafetyCheck::from_types(types, conn_name, self.is_dangerous)
+            .with_write_policy(types, true, &self.policy, &self.database)
+    }
+}
+
+/// One inline edit: a cell of the row whose primary key is `pk_pairs`.
+pub struct CellUpdate<'a> {
+    pub schema: &'a str,
+    pub table: &'a str,
+    pub column: &'a str,
+    /// `None` sets NULL.
+    pub new_value: Option<&'a str>,
+    /// (column, value) pairs of the row's primary key.
+    pub pk_pairs: &'a [(String, String)],
+}
+
+/// Lightweight per-tab state — query lifecycle only.
+/// Tabs reference a connection by name; they don't own the pool.
+pub struct TabContext {
+    /// Name of the currently selected connection, or None.
+    pub connection_name: Option<String>,
+    cancel_token: Option<CancellationToken>,
+    query_id: u64,
+}
+
+impl TabContext {
+    pub fn new() -> Self {
+        Self {
+            connection_name: None,
+            cancel_token: None,
+            query_id: 0,
+        }
+    }
+
+    /// Register a new query. Returns (token, query_id).
+    /// The caller must pass query_id back to finish_query when done.
+    pub fn start_query(&mut self) -> (CancellationToken, u64) {
+        self.query_id += 1;
+        let token = CancellationToken::new();
+        self.cancel_token = Some(token.clone());
+        (token, self.query_id)
+    }
+
+    /// Clear the cancel slot only when the generation still matches.
+    pub fn finish_query(&mut self, query_id: u64) {
+        if self.query_id == query_id {
+            self.cancel_token = None;
+        }
+    }
+
+    /// Cancel any in-flight query.
+    pub fn cancel_current_query(&mut self) {
+        if let Some(token) = self.cancel_token.take() {
+            token.cancel();
+        }
+    }
+}
+
+impl Default for TabContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Workspace {
+    pub config: Mutex<Config>,
+    /// connection_name → live pool + tunnel (workspace-level, persistent)
+    pub connections: Mutex<HashMap<String, Arc<OpenConnection>>>,
+    /// tab_id → per-tab query lifecycle state
+    pub tabs: Mutex<HashMap<u32, TabContext>>,
+    /// `.dendron.toml` found above the working directory at startup.
+    pub team_config: Option<TeamConfig>,
+    /// Project last opened, or else the one around the working directory,
+    /// for its shared queries.
+    pub project: Mutex<Option<Project>>,
+    /// Where statements run on dangerous connections are recorded.
+    pub audit: Option<AuditLog>,
+    /// Every statement run from the workspace, with its outcome.
+    pub history: Option<HistoryStore>,
+    /// Open tabs as last reported by the editor, plus their last results.
+    pub snapshot: Mutex<WorkspaceSnapshot>,
+    snapshot_saver: Option<WorkspaceSaver>,
+}
+
+impl Workspace {
+    /// The user's workspace: saved config, team config for the working
+    /// directory, the project and tabs from last time.
+    pub fn load() -> Self {
+        Self::load_with_store(WorkspaceStore::open_default())
+    }
+
+    /// [`load`](Self::load) without tabs: none are restored and the saved
+    /// ones are left alone. For hosts that run one-off statements, such as
+    /// the CLI.
+    pub fn load_headless() -> Self {
+        Self::load_with_store(None)
+    }
+
+    fn load_with_store(store: Option<WorkspaceStore>) -> Self {
+        let snapshot = store.as_ref().map(WorkspaceStore::load).unwrap_or_default();
+        // Restored tabs remember their connection by name only; it is opened
+        // again the first time the tab runs something.
+        let tabs = snapshot.tabs.iter()
+            .map(|tab| {
+                let mut ctx = TabContext::new();
+                ctx.connection_name = tab.connection_name.clone();
+                (tab.id, ctx)
+            })
+            .collect();
+        Self {
+            config: Mutex::new(Config::load()),
+            connections: Mutex::new(HashMap::new()),
+            tabs: Mutex::new(tabs),
+            team_config: TeamConfig::find_and_load().map(|(_, config)| config),
+            project: Mutex::new(initial_project(&snapshot)),
+            audit: AuditLog::open_default(),
+            history: HistoryStore::open_default(),
+            snapshot: Mutex::new(snapshot),
+            snapshot_saver: store.map(|store| WorkspaceSaver::spawn(store, SAVE_DEBOUNCE)),
+        }
+    }
+
+    // ── Connections ──────────────────────────────────────────────────────────
+
+    /// Open the saved connection `name` (establish pool + tunnel).
+    /// Idempotent: if already open, returns it without re-connecting.
+    pub async fn open_connection(&self, name: &str) -> Result<Arc<OpenConnection>> {
+        // Check if already open — avoid holding the lock across await points.
+        if let Some(open) = self.connections.lock().await.get(name) {
+            return Ok(open.clone());
+        }
+
+        let saved = {
+            let config = self.config.lock().await;
+            config.connections.iter()
+                .find(|c| c.name() == name)
+                .ok_or_else(|| AppError::ConnectionNotFound(name.to_string()))?
+                .clone()
+        };
+
+        let (policy, database) = self.policy_and_database(&saved);
+        let (effective_host, effective_port, tunnel) = build_tunnel(&saved).await?;
+        let mut conn_config = saved_to_connection_config_with_host(&saved, effective_host, effective_port);
+        // `.dendron.toml` can make a connection read-only, never writable.
+        if self.team_config.as_ref().is_some_and(|team| team.is_read_only(name)) {
+            conn_config.force_read_only();
+        }
+        let db_conn = DatabaseConnection::connect(&conn_config).await?;
+
+        let open = Arc::new(OpenConnection {
+            conn: Arc::new(db_conn),
+            is_dangerous: saved.is_dangerous(),
+            tags: saved.tags().to_vec(),
+            policy,
+            database,
+            _ssh_tunnel: tunnel,
+        });
+        // Another caller may have opened it meanwhile; keep theirs.
+        let mut conns = self.connections.lock().await;
+        Ok(conns.entry(name.to_string()).or_insert(open).clone())
+    }
+
+    /// The environment policy for a saved connection, and the database name
+    /// its typed confirmation asks for.
+    fn policy_and_database(&self, saved: &SavedConnection) -> (EnvironmentPolicy, String) {
+        let policy = self.team_config.as_ref()
+            .map(|team| team.policy_for(saved.name(), saved.tags()))
+            .unwrap_or_default();
+        let database = match saved {
+            SavedConnection::Postgres { database, .. } => database.clone(),
+            SavedConnection::Sqlite { path, .. } => std::path::Path::new(path)
+                .file_name()
+                .map_or_else(|| path.clone(), |f| f.to_string_lossy().into_owned()),
+        };
+        (policy, database)
+    }
+
+    /// Close a named connection. Drops the pool and SSH tunnel.
+    /// Tabs that pointed to this connection keep their `connection_name`
+    /// and open it again the next time they run a query.
+    pub async fn close_connection(&self, name: &str) {
+        self.connections.lock().await.remove(name);
+    }
+
+    /// Names of all currently open (live) connections.
+    pub async fn open_connection_names(&self) -> Vec<String> {
+        self.connections.lock().await.keys().cloned().collect()
+    }
+
+    /// An already open connection.
+    pub async fn connection(&self, name: &str) -> Result<Arc<OpenConnection>> {
+        self.connections.lock().await.get(name)
+            .cloned()
+            .ok_or_else(|| AppError::ConnectionNotOpen(name.to_string()))
+    }
+
+    // ── Tabs ─────────────────────────────────────────────────────────────────
+
+    /// Point a tab at a connection (or clear it).
+    /// Creates the TabContext if it doesn't exist yet.
+    pub async fn set_tab_connection(&self, tab_id: u32, connection_name: Option<String>) {
+        {
+            let mut tabs = self.tabs.lock().await;
+            tabs.entry(tab_id).or_default().connection_name = connection_name.clone();
+        }
+        self.update_snapshot(|snapshot| {
+            if let Some(tab) = snapshot.tab_mut(tab_id) {
+                tab.connection_name = connection_name;
+            }
+        }).await;
+    }
+
+    pub async fn tab_connection_name(&self, tab_id: u32) -> Option<String> {
+        self.tabs.lock().await.get(&tab_id).and_then(|ctx| ctx.connection_name.clone())
+    }
+
+    /// The tab's connection if it is open; never connects.
+    pub async fn tab_connection(&self, tab_id: u32) -> Option<Arc<OpenConnection>> {
+        let name = self.tab_connection_name(tab_id).await?;
+        self.connections.lock().await.get(&name).cloned()
+    }
+
+    /// Dialect of the tab's connection, or generic when it has none open.
+    pub async fn tab_dialect(&self, tab_id: u32) -> SqlDialect {
+        self.tab_connection(tab_id).await.map_or(SqlDialect::Generic, |open| open.conn.dialect())
+    }
+
+    /// The tab's connection and its name, opening it if it was closed or
+    /// never opened since the tab was restored.
+    pub async fn require_tab_connection(&self, tab_id: u32) -> Result<(String, Arc<OpenConnection>)> {
+        let name = self.tab_connection_name(tab_id).await.ok_or(AppError::NoTabConnection)?;
+        let open = self.open_connection(&name).await?;
+        Ok((name, open))
+    }
+
+    /// Run `task` as the tab's current query: `cancel_query` on the tab
+    /// cancels the token it is given.
+    pub async fn run_in_tab<T, F, Fut>(&self, tab_id: u32, task: F) -> Result<T>
+    where
+        F: FnOnce(CancellationToken) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let (token, query_id) = self.tabs.lock().await.entry(tab_id).or_default().start_query();
+        let result = task(token).await;
+        // Clear the token only if our generation is still current.
+        if let Some(ctx) = self.tabs.lock().await.get_mut(&tab_id) {
+            ctx.finish_query(query_id);
+        }
+        result
+    }
+
+    /// Cancel whatever the tab is running.
+    pub async fn cancel_query(&self, tab_id: u32) {
+        if let Some(ctx) = self.tabs.lock().await.get_mut(&tab_id) {
+            ctx.cancel_current_query();
+        }
+    }
+
+    // ── Queries ──────────────────────────────────────────────────────────────
+
+    /// Run `sql` on the tab's connection with placeholders bound from
+    /// `params`. SELECTs are paged from `offset`, at most
+    /// `DEFAULT_ROW_LIMIT` rows at a time. Policy checks, audit, history and
+    /// the tab's result summary are all handled here. `confirmation` is what
+    /// the user typed when the policy asks for the database name.
+    pub async fn execute_query(
+        &self,
+        tab_id: u32,
+        sql: &str,
+        offset: u64,
+        params: &HashMap<String, ParamValue>,
+        confirmation: Option<&str>,
+    ) -> Result<QueryResult> {
+        self.execute_query_with(tab_id, sql, offset, params, confirmation, |_| {}).await
+    }
+
+    /// [`execute_query`](Self::execute_query), reporting the rows a SELECT
+    /// has read so far to `on_progress` while the page loads.
+    pub async fn execute_query_with(
+        &self,
+        tab_id: u32,
+        sql: &str,
+        offset: u64,
+        params: &HashMap<String, ParamValue>,
+        confirmation: Option<&str>,
+        on_progress: impl FnMut(FetchProgress<'_>) + Send,
+    ) -> Result<QueryResult> {
+        self.run_query(tab_id, sql, offset, params, Access::Checked(confirmation), on_progress).await
+    }
+
+    /// Run a statement that only reads on the tab's connection, for callers
+    /// no person stands behind. It runs in a rolled-back read-only
+    /// transaction; statements that write, and calls whose effects escape
+    /// the transaction (see [`SqlAnalyzer::side_effect_calls`]), are refused.
+    pub async fn execute_read_only(
+        &self,
+        tab_id: u32,
+        sql: &str,
+        params: &HashMap<String, ParamValue>,
+    ) -> Result<QueryResult> {
+        self.run_query(tab_id, sql, 0, params, Access::ReadOnly, |_| {}).await
+    }
+
+    async fn run_query(
+        &self,
+        tab_id: u32,
+        sql: &str,
+        offset: u64,
+        params: &HashMap<String, ParamValue>,
+        access: Access<'_>,
+        on_progress: impl FnMut(FetchProgress<'_>) + Send,
+    ) -> Result<QueryResult> {
+        // Strip trailing semicolons so the SQL can be safely embedded as a subquery.
+        let sql = sql.trim_end().trim_end_matches(';');
+        let (conn_name, open) = self.require_tab_connection(tab_id).await?;
+        let conn = open.conn.clone();
+
+        // Placeholders become the connection's numbered form; the rewritten SQL
+        // is what gets analyzed and run.
+        let parameterized = ParameterizedSql::new(sql, conn.dialect());
+        let analyzer = SqlAnalyzer::new(conn.dialect(), &parameterized.sql);
+        let safety = open.safety_check(&conn_name, &analyzer);
+        let is_select = analyzer.query_type() == QueryType::Select;
+        let has_order_by = !is_select || analyzer.has_top_level_order_by();
+        let effective_sql = if is_select {
+            format!("SELECT * FROM ({}) q LIMIT {} OFFSET {offset}", parameterized.sql, DEFAULT_ROW_LIMIT + 1)
+        } else {
+            parameterized.sql.clone()
+        };
+
+        let audit = start_audit(&open, &conn_name, sql, &parameterized, params, analyzer.most_dangerous_type());
+
+        let started = Instant::now();
+        let result = self.run_in_tab(tab_id, |token| async move {
+            let run = async {
+                let values = parameterized.values(params)?;
+                let confirmation = match access {
+                    Access::Checked(confirmation) => confirmation,
+                    Access::ReadOnly => {
+                        if !analyzer.is_read_only() {
+                            return Err(AppError::InvalidInput("Only statements that read can run here".into()));
+                        }
+                        if let Some(call) = analyzer.side_effect_calls().first() {
+                            return Err(AppError::InvalidInput(format!(
+                                "{call}() has effects outside the transaction and can't run here"
+                            )));
+                        }
+                        return conn.execute_read_only_with(&effective_sql, &values, has_order_by, is_select, on_progress).await;
+                    }
+                };
+                authorize_write(&safety, confirmation)?;
+                // The row limit is checked by running the statement once in a
+                // rolled-back transaction first.
+                if safety.max_affected_rows.is_some()
+                    && matches!(analyzer.query_type(), QueryType::Update | QueryType::Delete)
+                {
+                    let preview = conn.dry_run(&parameterized.sql, &values, 0).await?;
+                    safety.check_affected_rows(preview.rows_affected).map_err(AppError::PolicyViolation)?;
+                }
+                conn.execute_query_with(&effective_sql, &values, has_order_by, is_select, on_progress).await
+            };
+            tokio::select! {
+                res = run => res,
+                _ = token.cancelled() => Err(AppError::QueryCancelled),
+            }
+        }).await;
+
+        let outcome = result.as_ref().map_err(ToString::to_string);
+        if let Some(audit) = audit {
+            self.record_audit(&audit.finish(outcome.as_ref().map(|r| r.affected_rows).map_err(String::as_str)));
+        }
+        self.record_history(&HistoryRecord {
+            connection: Some(&conn_name),
+            tab_id: Some(tab_id),
+            sql,
+            duration_ms: started.elapsed().as_millis() as u64,
+            outcome: outcome.as_ref()
+                .map(|r| Some(r.affected_rows.unwrap_or(r.row_count as u64)))
+                .map_err(String::as_str),
+        }).await;
+        // Later pages of the same result don't replace the tab's summary.
+        if offset == 0 {
+            let summary = match &outcome {
+                Ok(r) => ResultSummary::from_result(r),
+                Err(e) => ResultSummary::from_error(e, started.elapsed()),
+            };
+            self.update_snapshot(|snapshot| {
+                if let Some(tab) = snapshot.tab_mut(tab_id) {
+                    tab.last_result = Some(summary);
+                }
+            }).await;
+        }
+
+        result
+    }
+
+    /// Run a query that only reads on the tab's connection and write every
+    /// row to `writer`, with no row limit. Checked, audited and recorded in
+    /// the history like [`execute_query`](Self::execute_query). Returns the
+    /// number of rows.
+    pub async fn stream_query(
+        &self,
+        tab_id: u32,
+        sql: &str,
+        params: &HashMap<String, ParamValue>,
+        writer: &mut (dyn RowWriter + Send),
+    ) -> Result<u64> {
+        self.stream_rows(tab_id, sql, params, None, writer).await
+    }
+
+    /// Run `sql` on the tab's connection again and read its first `limit`
+    /// rows losslessly, to export results the grid has loaded: grid cells
+    /// are display text, with binary shortened and NULL spelled out. Checked,
+    /// audited and recorded like [`stream_query`](Self::stream_query).
+    pub async fn reread_rows(
+        &self,
+        tab_id: u32,
+        sql: &str,
+        params: &HashMap<String, ParamValue>,
+        limit: usize,
+    ) -> Result<ResultRows> {
+        let mut rows = ResultRows::default();
+        self.stream_rows(tab_id, sql, params, Some(limit), &mut rows).await?;
+        Ok(rows)
+    }
+
+    async fn stream_rows(
+        &self,
+        tab_id: u32,
+        sql: &str,
+        params: &HashMap<String, ParamValue>,
+        limit: Option<usize>,
+        writer: &mut (dyn RowWriter + Send),
+    ) -> Result<u64> {
+        let sql = sql.trim_end().trim_end_matches(';');
+        let (conn_name, open) = self.require_tab_connection(tab_id).await?;
+        let conn = open.conn.clone();
+
+        let parameterized = ParameterizedSql::new(sql, conn.dialect());
+        let analyzer = SqlAnalyzer::new(conn.dialect(), &parameterized.sql);
+        // The first statement alone doesn't say: a later one, or a CTE that
+        // deletes, would run along with it.
+        if !analyzer.is_read_only() {
+            return Err("Only statements that read can be streamed".into());
+        }
+        let safety = open.safety_check(&conn_name, &analyzer);
+        let audit = start_audit(&open, &conn_name, sql, &parameterized, params, analyzer.most_dangerous_type());
+
+        let started = Instant::now();
+        let result = self.run_in_tab(tab_id, |token| async move {
+            let run = async {
+                authorize_unconfirmed(&safety)?;
+                let values = parameterized.values(params)?;
+                // Column names come from the statement, so an empty result still gets a header.
+                let (columns, column_types) = conn.describe_columns(&parameterized.sql).await?;
+                writer.begin(&columns, &column_types)?;
+                let mut rows = 0;
+                let mut stream = conn.fetch_rows_with(&parameterized.sql, &values);
+                while limit.is_none_or(|limit| rows < limit as u64) {
+                    let Some(row) = stream.try_next().await? else { break };
+                    writer.write_row(&row.decode_lossless()?)?;
+                    rows += 1;
+                }
+                drop(stream);
+                writer.finish()?;
+                Ok(rows)
+            };
+            tokio::select! {
+                res = run => res,
+                _ = token.cancelled() => Err(AppError::QueryCancelled),
+            }
+        }).await;
+
+        let outcome = result.as_ref().map(|n| Some(*n)).map_err(ToString::to_string);
+        let outcome = outcome.as_ref().copied().map_err(String::as_str);
+        if let Some(audit) = audit {
+            self.record_audit(&audit.finish(outcome));
+        }
+        self.record_history(&HistoryRecord {
+            connection: Some(&conn_name),
+            tab_id: Some(tab_id),
+            sql,
+            duration_ms: started.elapsed().as_millis() as u64,
+            outcome,
+        }).await;
+
+        result
+    }
+
+    /// Run an UPDATE/DELETE in a rolled-back transaction to count the rows it
+    /// would touch. Cancellable like a normal query.
+    ///
+    /// The statement still takes locks and fires triggers, so the policy's
+    /// refusals apply and it is audited like a statement that ran. It is run
+    /// to show in the confirmation, so it doesn't wait for one.
+    pub async fn dry_run_query(
+        &self,
+        tab_id: u32,
+        sql: &str,
+        sample_limit: usize,
+        params: &HashMap<String, ParamValue>,
+    ) -> Result<DryRunResult> {
+        let sql = sql.trim_end().trim_end_matches(';');
+        let (conn_name, open) = self.require_tab_connection(tab_id).await?;
+        let conn = &open.conn;
+        let parameterized = ParameterizedSql::new(sql, conn.dialect());
+        let analyzer = SqlAnalyzer::new(conn.dialect(), &parameterized.sql);
+        let safety = open.safety_check(&conn_name, &analyzer);
+        let audited = format!("-- dry run, rolled back\n{sql}");
+        let audit = start_audit(&open, &conn_name, &audited, &parameterized, params, analyzer.most_dangerous_type());
+
+        let result = self.run_in_tab(tab_id, |token| async move {
+            let run = async {
+                if safety.is_blocked() {
+                    return Err(AppError::PolicyViolation(safety.violations.join("\n")));
+                }
+                let values = parameterized.values(params)?;
+                conn.dry_run(&parameterized.sql, &values, sample_limit).await
+            };
+            // Dropping the transaction on cancel rolls it back.
+            tokio::select! {
+                res = run => res,
+                _ = token.cancelled() => Err(AppError::QueryCancelled),
+            }
+        }).await;
+
+        if let Some(audit) = audit {
+            let outcome = result.as_ref().map(|r| Some(r.rows_affected)).map_err(ToString::to_string);
+            self.record_audit(&audit.finish(outcome.as_ref().copied().map_err(String::as_str)));
+        }
+        result
+    }
+
+    /// What running `sql` on the tab's connection would need: confirmation,
+    /// typed confirmation, or refusal under the environment policy.
+    pub async fn check_query_safety(&self, tab_id: u32, sql: &str) -> QuerySafetyCheck {
+        let Some(conn_name) = self.tab_connection_name(tab_id).await else {
+            return QuerySafetyCheck::check(sql, "unknown", false);
+        };
+        if let Some(open) = self.connections.lock().await.get(&conn_name) {
+            let parameterized = ParameterizedSql::new(sql, open.conn.dialect());
+            return open.safety_check(&conn_name, &SqlAnalyzer::new(open.conn.dialect(), &parameterized.sql));
+        }
+        // Not open yet: the saved connection decides, as it will once open.
+        let saved = self.config.lock().await.connections.iter().find(|c| c.name() == conn_name).cloned();
+        let Some(saved) = saved else {
+            return QuerySafetyCheck::check(sql, &conn_name, false);
+        };
+        let dialect = match saved {
+            SavedConnection::Postgres { .. } => SqlDialect::Postgres,
+            SavedConnection::Sqlite { .. } => SqlDialect::Sqlite,
+        };
+        let (policy, database) = self.policy_and_database(&saved);
+        let parameterized = ParameterizedSql::new(sql, dialect);
+        let analyzer = SqlAnalyzer::new(dialect, &parameterized.sql);
+        QuerySafetyCheck::from_analyzer(&analyzer, &conn_name, saved.is_dangerous())
+            .with_policy(&analyzer, &policy, &database)
+    }
+
+    /// What a write of statements of `types` on the tab's connection would
+    /// need, for writes without SQL text such as inline edits.
+    pub async fn check_write_safety(&self, tab_id: u32, types: &[QueryType]) -> Result<QuerySafetyCheck> {
+        let (conn_name, open) = self.require_tab_connection(tab_id).await?;
+        Ok(open.write_check(&conn_name, types))
+    }
+
+    /// Set one cell of a row identified by its primary key. Returns the rows
+    /// affected, which must be exactly one.
+    pub async fn update_cell(&self, tab_id: u32, cell: CellUpdate<'_>, confirmation: Option<&str>) -> Result<u64> {
+        let CellUpdate { schema, table, column, new_value, pk_pairs } = cell;
+        if pk_pairs.is_empty() {
+            return Err("No primary key columns provided".into());
+        }
+        let (conn_name, open) = self.require_tab_connection(tab_id).await?;
+        authorize_write(&open.write_check(&conn_name, &[QueryType::Update]), confirmation)?;
+
+        // Audited as the equivalent literal statement; the real one binds parameters.
+        let audit = open.is_dangerous.then(|| {
+            let value = new_value.map_or_else(|| "NULL".to_string(), quote_literal);
+            let predicate = pk_pairs.iter()
+                .map(|(name, value)| format!("{} = {}", quote_ident(name), quote_literal(value)))
+                .collect::<Vec<_>>()
+                .join(" AND ");
+            let statement = format!(
+                "UPDATE {} SET {} = {} WHERE {}",
+                qualified_name(schema, table), quote_ident(column), value, predicate
+            );
+            AuditEntry::start(&conn_name, &open.tags, &statement, QueryType::Update)
+        });
+
+        let result = open.conn.update_cell(schema, table, column, new_value, pk_pairs).await;
+        if let Some(audit) = audit {
+            let outcome = result.as_ref().map(|n| Some(*n)).map_err(ToString::to_string);
+            self.record_audit(&audit.finish(outcome.as_ref().copied().map_err(String::as_str)));
+        }
+        let affected = result?;
+
+        if affected == 0 {
+            return Err(AppError::QueryFailed(
+                "No rows were updated — the row may have been modified or deleted".to_string(),
+            ));
+        }
+        if affected > 1 {
+            return Err(AppError::QueryFailed(format!(
+                "Expected 1 row affected, got {affected} — this should not happen with a primary key WHERE clause"
+            )));
+        }
+        Ok(affected)
+    }
+
+    // ── Bulk writes ──────────────────────────────────────────────────────────
+
+    /// Load a CSV/TSV file into a table on the tab's connection, as the tab's
+    /// current query.
+    pub async fn import_csv(
+        &self,
+        tab_id: u32,
+        path: &Path,
+        csv_options: &CsvOptions,
+        options: &ImportOptions,
+        confirmation: Option<&str>,
+        on_progress: impl FnMut(&ImportProgress) + Send,
+    ) -> Result<ImportSummary> {
+        let (open, audit) = self.import_target(tab_id, path, options, confirmation).await?;
+        let result = self.run_in_tab(tab_id, |token| async move {
+            import::csv::import_csv(&open.conn, path, csv_options, options, &token, on_progress).await
+        }).await;
+        self.finish_audit(audit, result.as_ref().map(|summary| summary.rows_imported));
+        result
+    }
+
+    /// Load a JSON array or NDJSON file into a table on the tab's connection,
+    /// as the tab's current query.
+    pub async fn import_json(
+        &self,
+        tab_id: u32,
+        path: &Path,
+        json_options: &JsonOptions,
+        options: &ImportOptions,
+        confirmation: Option<&str>,
+        on_progress: impl FnMut(&ImportProgress) + Send,
+    ) -> Result<ImportSummary> {
+        let (open, audit) = self.import_target(tab_id, path, options, confirmation).await?;
+        let result = self.run_in_tab(tab_id, |token| async move {
+            import::json::import_json(&open.conn, path, json_options, options, &token, on_progress).await
+        }).await;
+        self.finish_audit(audit, result.as_ref().map(|summary| summary.rows_imported));
+        result
+    }
+
+    /// The tab's connection, once its policy allows the import, and the
+    /// import's audit entry. Dry runs write nothing, so any policy allows
+    /// them and they aren't audited.
+    async fn import_target(
+        &self,
+        tab_id: u32,
+        path: &Path,
+        options: &ImportOptions,
+        confirmation: Option<&str>,
+    ) -> Result<(Arc<OpenConnection>, Option<PendingAudit>)> {
+        let (conn_name, open) = self.require_tab_connection(tab_id).await?;
+        if options.dry_run.is_some() {
+            return Ok((open, None));
+        }
+        let target = match &options.schema {
+            Some(schema) => qualified_name(schema, &options.table),
+            None => quote_ident(&options.table),
+        };
+        let types = bulk_write_types(options.create_table);
+        let statement = format!("-- import {} into {target}", path.display());
+        let audit = start_bulk_audit(&open, &conn_name, &statement, &types);
+        let audit = self.authorize_bulk_write(&open.write_check(&conn_name, &types), confirmation, audit)?;
+        Ok((open, audit))
+    }
+
+    /// Copy a table or query result between open connections, named as
+    /// `(source, destination)`, as the tab's current query. A source query
+    /// must only read; the destination's policy applies to the rows written.
+    pub async fn transfer_table(
+        &self,
+        tab_id: u32,
+        (source_connection, dest_connection): (&str, &str),
+        source: &TransferSource,
+        options: &TransferOptions,
+        confirmation: Option<&str>,
+        on_progress: impl FnMut(&TransferProgress) + Send,
+    ) -> Result<TransferSummary> {
+        let source_open = self.connection(source_connection).await?;
+        let dest_open = self.connection(dest_connection).await?;
+        if let TransferSource::Query { sql } = source {
+            if !SqlAnalyzer::new(source_open.conn.dialect(), sql).is_read_only() {
+                return Err("The source of a transfer must be a query that only reads".into());
+            }
+        }
+        let types = bulk_write_types(options.create_if_missing);
+        let target = match &options.dest_schema {
+            Some(schema) => qualified_name(schema, &options.dest_table),
+            None => quote_ident(&options.dest_table),
+        };
+        let statement = match source {
+            TransferSource::Table { schema, table } => format!(
+                "-- transfer {} from '{source_connection}' into {target}", qualified_name(schema, table)
+            ),
+            TransferSource::Query { sql } => format!("-- transfer from '{source_connection}' into {target}:\n{sql}"),
+        };
+        let audit = start_bulk_audit(&dest_open, dest_connection, &statement, &types);
+        let audit = self.authorize_bulk_write(&dest_open.write_check(dest_connection, &types), confirmation, audit)?;
+
+        let result = self.run_in_tab(tab_id, |token| async move {
+            transfer::transfer_table(&source_open.conn, &dest_open.conn, source, options, &token, on_progress).await
+        }).await;
+        self.finish_audit(audit, result.as_ref().map(|summary| summary.rows_copied));
+        result
+    }
+
+    /// [`authorize_write`] for a bulk write, auditing a refusal.
+    fn authorize_bulk_write(
+        &self,
+        safety: &QuerySafetyCheck,
+        confirmation: Option<&str>,
+        audit: Option<PendingAudit>,
+    ) -> Result<Option<PendingAudit>> {
+        match authorize_write(safety, confirmation) {
+            Ok(()) => Ok(audit),
+            Err(e) => {
+                self.finish_audit(audit, Err(&e));
+                Err(e)
+            }
+        }
+    }
+
+    /// Record a bulk operation's audit entry with the rows it wrote or read.
+    fn finish_audit(&self, audit: Option<PendingAudit>, rows: std::result::Result<u64, &AppError>) {
+        if let Some(audit) = audit {
+            let outcome = rows.map(Some).map_err(ToString::to_string);
+            self.record_audit(&audit.finish(outcome.as_ref().copied().map_err(String::as_str)));
+        }
+    }
+
+    // ── Exports ──────────────────────────────────────────────────────────────
+
+    /// Run a query that only reads on the tab's connection again and stream
+    /// every row to `path`, without the results grid's row limit. Checked,
+    /// audited and recorded in the history like
+    /// [`stream_query`](Self::stream_query).
+    pub async fn export_query_to_file(
+        &self,
+        tab_id: u32,
+        sql: &str,
+        path: &Path,
+        format: ExportFormat,
+        options: &ExportOptions,
+        on_progress: impl FnMut(&ExportProgress) + Send,
+    ) -> Result<ExportSummary> {
+        let sql = sql.trim_end().trim_end_matches(';');
+        let (conn_name, open) = self.require_tab_connection(tab_id).await?;
+        let conn = open.conn.clone();
+        let analyzer = SqlAnalyzer::new(conn.dialect(), sql);
+        if !analyzer.is_read_only() {
+            return Err("Only queries that read can be exported to a file".into());
+        }
+        let safety = open.safety_check(&conn_name, &analyzer);
+        let audit = open.is_dangerous
+            .then(|| AuditEntry::start(&conn_name, &open.tags, sql, analyzer.most_dangerous_type()));
+
+        let started = Instant::now();
+        let result = self.run_in_tab(tab_id, |token| async move {
+            authorize_unconfirmed(&safety)?;
+            export::export_query(&conn, sql, path, format, options, &token, on_progress).await
+        }).await;
+
+        let outcome = result.as_ref().map(|summary| Some(summary.rows_written)).map_err(ToString::to_string);
+        let outcome = outcome.as_ref().copied().map_err(String::as_str);
+        if let Some(audit) = audit {
+            self.record_audit(&audit.finish(outcome));
+        }
+        self.record_history(&HistoryRecord {
+            connection: Some(&conn_name),
+            tab_id: Some(tab_id),
+            sql,
+            duration_ms: started.elapsed().as_millis() as u64,
+            outcome,
+        }).await;
+
+        result
+    }
+
+    // ── Project ──────────────────────────────────────────────────────────────
+
+    /// Open the project at or above `root` and remember it for next time.
+    pub async fn open_project(&self, root: &Path) -> Result<Project> {
+        let project = Project::discover(root)
+            .or_else(|| Project::open(root))
+            .ok_or_else(|| AppError::InvalidInput(format!("{} is not a directory", root.display())))?;
+        *self.project.lock().await = Some(project.clone());
+        let project_root = project.root.clone();
+        self.update_snapshot(|snapshot| snapshot.project_root = Some(project_root)).await;
+        Ok(project)
+    }
+
+    // ── Records ──────────────────────────────────────────────────────────────
+
+    /// Append to the audit log. A failed write is reported, not fatal: the
+    /// statement has already run.
+    pub fn record_audit(&self, entry: &AuditEntry) {
+        if let Some(audit) = &self.audit {
+            if let Err(e) = audit.append(entry) {
+                eprintln!("Warning: Failed to write audit log {}: {}", audit.path().display(), e);
+            }
+        }
+    }
+
+    /// Add a finished statement to the query history, reporting failures.
+    pub async fn record_history(&self, record: &HistoryRecord<'_>) {
+        if let Some(history) = &self.history {
+            if let Err(e) = history.record(record).await {
+                eprintln!("Warning: Failed to write query history {}: {}", history.path().display(), e);
+            }
+        }
+    }
+
+    /// The query history, with any pre-database history from the config file
+    /// moved into it first.
+    pub async fn history(&self) -> Result<&HistoryStore> {
+        let history = self.history.as_ref().ok_or(AppError::ConfigDirNotFound)?;
+        let mut config = self.config.lock().await;
+        if !config.query_history.is_empty() {
+            history.import_legacy(&config.query_history).await?;
+            config.query_history.clear();
+            config.save()?;
+        }
+        Ok(history)
+    }
+
+    /// Change the snapshot of open tabs and queue it to be saved.
+    pub async fn update_snapshot(&self, update: impl FnOnce(&mut WorkspaceSnapshot)) {
+        let mut snapshot = self.snapshot.lock().await;
+        update(&mut snapshot);
+        if let Some(saver) = &self.snapshot_saver {
+            saver.update(snapshot.clone());
+        }
+    }
+
+    /// Write any pending snapshot changes, e.g. before the host exits.
+    pub fn flush_snapshot(&self) {
+        if let Some(saver) = &self.snapshot_saver {
+            saver.flush();
+        }
+    }
+}
+
+/// The project saved in the snapshot if it still exists. Otherwise the one
+/// around the working directory, which is only meaningful for hosts started
+/// from a shell: an app launched from the Dock runs in `/`.
+fn initial_project(snapshot: &WorkspaceSnapshot) -> Option<Project> {
+    snapshot.project_root.as_deref()
+        .and_then(Project::open)
+        .or_else(|| std::env::current_dir().ok().and_then(|cwd| Project::discover(&cwd)))
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Self::load()
+    }
+}
+
+/// How [`Workspace::run_query`] may run a statement.
+#[derive(Clone, Copy)]
+enum Access<'a> {
+    /// Through the policy gate, with what the user typed if it asked.
+    Checked(Option<&'a str>),
+    /// Only reads, in a rolled-back read-only transaction.
+    ReadOnly,
+}
+
+/// Statements a bulk write runs: INSERTs, and CREATE TABLE when it may
+/// create its table.
+fn bulk_write_types(may_create: bool) -> Vec<QueryType> {
+    let mut types = vec![QueryType::Insert];
+    if may_create {
+        types.push(QueryType::Create);
+    }
+    types
+}
+
+/// The policy gate every write passes through: refuses what the connection's
+/// environment policy forbids, and requires `confirmation` to be the text the
+/// policy asks the user to type.
+fn authorize_write(safety: &QuerySafetyCheck, confirmation: Option<&str>) -> Result<()> {
+    if safety.is_blocked() {
+        return Err(AppError::PolicyViolation(safety.violations.join("\n")));
+    }
+    match &safety.typed_confirmation {
+        Some(expected) if confirmation != Some(expected.as_str()) => Err(AppError::PolicyViolation(format!(
+            "Type '{expected}' to confirm this statement on '{}'",
+            safety.connection_name
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// [`authorize_write`] for statements run without a chance to confirm
+/// them: anything that would ask for confirmation is refused.
+fn authorize_unconfirmed(safety: &QuerySafetyCheck) -> Result<()> {
+    if safety.requires_confirmation {
+        return Err(AppError::PolicyViolation(format!(
+            "This statement needs confirmation on '{}' and can't run here",
+            safety.connection_name
+        )));
+    }
+    authorize_write(safety, None)
+}
+
+/// Audit entry for a statement about to run, when the connection is
+/// dangerous. Parameter values are appended as a comment.
+fn start_audit(
+    open: &OpenConnection,
+    conn_name: &str,
+    sql: &str,
+    parameterized: &ParameterizedSql,
+    params: &HashMap<String, ParamValue>,
+    query_type: QueryType,
+) -> Option<PendingAudit> {
+    open.is_dangerous.then(|| {
+        let statement = match parameterized.describe(params) {
+            described if described.is_empty() => sql.to_string(),
+            described => format!("{sql}\n-- {described}"),
+        };
+        AuditEntry::start(conn_name, &open.tags, &statement, query_type)
+    })
+}
+
+/// Audit entry for a bulk write described by `statement`, when the
+/// connection is marked dangerous.
+fn start_bulk_audit(
+    open: &OpenConnection,
+    conn_name: &str,
+    statement: &str,
+    types: &[QueryType],
+) -> Option<PendingAudit> {
+    open.is_dangerous.then(|| AuditEntry::start(conn_name, &open.tags, statement, most_dangerous(types)))
+}
+
+/// Check that a saved connection can be reached, through its tunnel if it
+/// has one. Nothing is kept open.
+pub async fn test_connection(saved: &SavedConnection) -> Result<()> {
+    let (effective_host, effective_port, _tunnel) = build_tunnel(saved).await?;
+    let conn_config = saved_to_connection_config_with_host(saved, effective_host, effective_port);
+    // _tunnel dropped here — temporary tunnel torn down after test
+    DatabaseConnection::test_connection(&conn_config).await
+}
+
+/// Establish an SSH tunnel when the saved connection has one configured.
+/// Returns `(effective_host, effective_port, tunnel)`.
+pub async fn build_tunnel(saved: &SavedConnection) -> Result<(String, u16, Option<SshTunnel>)> {
+    match saved {
+        SavedConnection::Postgres { host, port, .. } => {
+            if let Some(ssh) = saved.ssh() {
+                let tunnel = SshTunnel::establish(ssh, host, *port).await?;
+                let local_port = tunnel.local_port;
+                Ok(("127.0.0.1".to_string(), local_port, Some(tunnel)))
+            } else {
+                Ok((host.clone(), *port, None))
+            }
+        }
+        _ => Ok((String::new(), 0, None)),
+    }
+}
+
+pub fn saved_to_connection_config(saved: &SavedConnection) -> ConnectionConfig {
+    match saved {
+        SavedConnection::Sqlite { name, path, .. } => ConnectionConfig::Sqlite {
+            name: name.clone(),
+            path: std::path::PathBuf::from(path),
+            read_only: saved.is_read_only(),
+        },
+        SavedConnection::Postgres { host, port, .. } => {
+            saved_to_connection_config_with_host(saved, host.clone(), *port)
+        }
+    }
+}
+
+/// Connection settings for `saved`, reaching Postgres at `effective_host`
+/// and `effective_port` (a tunnel's local end, when there is one).
+pub fn saved_to_connection_config_with_host(
+    saved: &SavedConnection,
+    effective_host: String,
+    effective_port: u16,
+) -> ConnectionConfig {
+    match saved {
+        SavedConnection::Sqlite { .. } => saved_to_connection_config(saved),
+        SavedConnection::Postgres { name, username, database, .. } => ConnectionConfig::Postgres {
+            name: name.clone(),
+            host: effective_host,
+            port: effective_port,
+            database: database.clone(),
+            username: username.clone(),
+            password: saved.get_password(),
+            read_only: saved.is_read_only(),
+        },
+    }
+}
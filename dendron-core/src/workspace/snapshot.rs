@@ -0,0 +1,249 @@
+//! Open tabs and what they were showing, kept across restarts.
+//!
+//! A snapshot is written to `workspace.json` next to the config file. Edits
+//! arrive far more often than they are worth writing, so saves go through a
+//! [`WorkspaceSaver`], which writes only once changes have been quiet for a
+//! moment. Connections are not part of the snapshot beyond their names; a
+//! restored tab reconnects when it is next used.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::audit::now_timestamp;
+use crate::config::Config;
+use crate::db::postgres::QueryResult;
+use crate::error::Result;
+
+/// How long changes must be quiet before the saver writes them.
+pub const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceSnapshot {
+    /// In display order.
+    #[serde(default)]
+    pub tabs: Vec<TabSnapshot>,
+    #[serde(default)]
+    pub active_tab: Option<u32>,
+    /// Project last opened from the app, reopened at startup.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_root: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TabSnapshot {
+    pub id: u32,
+    pub label: String,
+    #[serde(default)]
+    pub sql: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<CursorPosition>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection_name: Option<String>,
+    /// Path of the saved query the tab was opened from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub saved_query: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_result: Option<ResultSummary>,
+}
+
+/// Editor selection, as UTF-16 offsets into the tab's SQL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CursorPosition {
+    pub anchor: usize,
+    pub head: usize,
+}
+
+/// What a tab's last run produced. Rows are not kept.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResultSummary {
+    /// UTC, `YYYY-MM-DDTHH:MM:SS.mmmZ`.
+    pub ran_at: String,
+    pub columns: Vec<String>,
+    pub row_count: usize,
+    #[serde(default)]
+    pub truncated: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub affected_rows: Option<u64>,
+    pub execution_time_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ResultSummary {
+    pub fn from_result(result: &QueryResult) -> Self {
+        Self {
+            ran_at: now_timestamp(),
+            columns: result.columns.clone(),
+            row_count: result.row_count,
+            truncated: result.truncated,
+            affected_rows: result.affected_rows,
+            execution_time_ms: result.execution_time_ms as u64,
+            error: None,
+        }
+    }
+
+    pub fn from_error(error: &str, duration: Duration) -> Self {
+        Self {
+            ran_at: now_timestamp(),
+            columns: Vec::new(),
+            row_count: 0,
+            truncated: false,
+            affected_rows: None,
+            execution_time_ms: duration.as_millis() as u64,
+            error: Some(error.to_string()),
+        }
+    }
+}
+
+impl WorkspaceSnapshot {
+    pub fn tab(&self, id: u32) -> Option<&TabSnapshot> {
+        self.tabs.iter().find(|t| t.id == id)
+    }
+
+    pub fn tab_mut(&mut self, id: u32) -> Option<&mut TabSnapshot> {
+        self.tabs.iter_mut().find(|t| t.id == id)
+    }
+
+    /// Replace the tabs with the editor's view of them. Last results are
+    /// recorded by the backend, so a tab that comes without one keeps the
+    /// one already held for its id.
+    pub fn apply_update(&mut self, update: WorkspaceSnapshot) {
+        let mut previous = std::mem::take(&mut self.tabs);
+        self.tabs = update.tabs.into_iter()
+            .map(|mut tab| {
+                if tab.last_result.is_none() {
+                    tab.last_result = previous.iter_mut()
+                        .find(|p| p.id == tab.id)
+                        .and_then(|p| p.last_result.take());
+                }
+                tab
+            })
+            .collect();
+        self.active_tab = update.active_tab.filter(|id| self.tab(*id).is_some());
+    }
+}
+
+pub struct WorkspaceStore {
+    path: PathBuf,
+}
+
+impl WorkspaceStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// `workspace.json` in the config directory.
+    pub fn open_default() -> Option<Self> {
+        Config::config_dir().map(|dir| Self::new(dir.join("workspace.json")))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The saved workspace. A missing file is an empty workspace; so is an
+    /// unreadable one, after a warning, rather than keeping the app from
+    /// starting.
+    pub fn load(&self) -> WorkspaceSnapshot {
+        let contents = match std::fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return WorkspaceSnapshot::default(),
+            Err(e) => {
+                eprintln!("Warning: Failed to read workspace {}: {}", self.path.display(), e);
+                return WorkspaceSnapshot::default();
+            }
+        };
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            eprintln!("Warning: Ignoring unreadable workspace {}: {}", self.path.display(), e);
+            WorkspaceSnapshot::default()
+        })
+    }
+
+    /// Write `snapshot` to a temporary file and rename it into place, so a
+    /// crash mid-write leaves the previous workspace intact.
+    pub fn save(&self, snapshot: &WorkspaceSnapshot) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(snapshot)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+enum SaverMessage {
+    Update(WorkspaceSnapshot),
+    Flush(mpsc::Sender<()>),
+}
+
+/// Writes snapshots from a background thread once they stop changing for
+/// the debounce period. Anything still pending is written when the saver
+/// is dropped or flushed.
+pub struct WorkspaceSaver {
+    tx: mpsc::Sender<SaverMessage>,
+}
+
+impl WorkspaceSaver {
+    pub fn spawn(store: WorkspaceStore, debounce: Duration) -> Self {
+        let (tx, rx) = mpsc::channel();
+        thread::Builder::new()
+            .name("workspace-saver".into())
+            .spawn(move || run_saver(store, rx, debounce))
+            .expect("failed to spawn workspace saver thread");
+        Self { tx }
+    }
+
+    /// Queue `snapshot` to be written, replacing any not yet written.
+    pub fn update(&self, snapshot: WorkspaceSnapshot) {
+        let _ = self.tx.send(SaverMessage::Update(snapshot));
+    }
+
+    /// Write anything pending now, and wait until it is on disk.
+    pub fn flush(&self) {
+        let (done_tx, done_rx) = mpsc::channel();
+        if self.tx.send(SaverMessage::Flush(done_tx)).is_ok() {
+            let _ = done_rx.recv();
+        }
+    }
+}
+
+fn run_saver(store: WorkspaceStore, rx: mpsc::Receiver<SaverMessage>, debounce: Duration) {
+    let save = |snapshot: &WorkspaceSnapshot| {
+        if let Err(e) = store.save(snapshot) {
+            eprintln!("Warning: Failed to save workspace {}: {}", store.path().display(), e);
+        }
+    };
+
+    let mut pending: Option<WorkspaceSnapshot> = None;
+    loop {
+        let message = match pending {
+            Some(_) => rx.recv_timeout(debounce),
+            None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match message {
+            Ok(SaverMessage::Update(snapshot)) => pending = Some(snapshot),
+            Ok(SaverMessage::Flush(done)) => {
+                if let Some(snapshot) = pending.take() {
+                    save(&snapshot);
+                }
+                let _ = done.send(());
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                if let Some(snapshot) = pending.take() {
+                    save(&snapshot);
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                if let Some(snapshot) = pending.take() {
+                    save(&snapshot);
+                }
+                return;
+            }
+        }
+    }
+}
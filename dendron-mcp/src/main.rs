@@ -0,0 +1,172 @@
+//! `dendron-mcp`: an MCP server on stdio that lets AI tools browse and query
+//! dendron's saved connections.
+//!
+//! Connections are read-only to MCP clients unless saved with MCP writes
+//! enabled: such statements are refused up front, and the rest run in
+//! read-only transactions on sessions opened read-only, so the database
+//! refuses anything that slips past the check. Policy, audit and history
+//! apply as in the app.
+
+mod protocol;
+mod tools;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use dendron_core::config::{Config, SavedConnection};
+use dendron_core::workspace::Workspace;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::{mpsc, Mutex};
+
+use protocol::{Message, RpcError, METHOD_NOT_FOUND, PARSE_ERROR, PROTOCOL_VERSIONS};
+use tools::ToolError;
+
+struct Server {
+    ws: Workspace,
+    /// Each request runs in a tab of its own, so it can be cancelled alone.
+    next_tab: AtomicU32,
+    /// Request id (as JSON text) → tab running it.
+    in_flight: Mutex<HashMap<String, u32>>,
+}
+
+#[derive(Deserialize)]
+struct InitializeParams {
+    #[serde(rename = "protocolVersion")]
+    protocol_version: String,
+}
+
+#[derive(Deserialize)]
+struct CallParams {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+#[derive(Deserialize)]
+struct CancelledParams {
+    #[serde(rename = "requestId")]
+    request_id: Value,
+}
+
+/// Open every connection read-only unless it allows MCP writes. Only the
+/// in-memory config changes; the server never saves it.
+fn restrict_to_reads(config: &mut Config) {
+    for connection in &mut config.connections {
+        if connection.allows_mcp_writes() {
+            continue;
+        }
+        match connection {
+            SavedConnection::Sqlite { read_only, .. } | SavedConnection::Postgres { read_only, .. } => {
+                *read_only = true;
+            }
+        }
+    }
+}
+
+impl Server {
+    async fn request(&self, id: &Value, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => {
+                let params: InitializeParams = serde_json::from_value(params)
+                    .map_err(|e| RpcError::invalid_params(e.to_string()))?;
+                let version = PROTOCOL_VERSIONS.iter()
+                    .find(|v| **v == params.protocol_version)
+                    .unwrap_or(&PROTOCOL_VERSIONS[0]);
+                Ok(json!({
+                    "protocolVersion": version,
+                    "capabilities": { "tools": {} },
+                    "serverInfo": { "name": "dendron", "version": env!("CARGO_PKG_VERSION") },
+                }))
+            }
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tools::definitions() })),
+            "tools/call" => {
+                let params: CallParams = serde_json::from_value(params)
+                    .map_err(|e| RpcError::invalid_params(e.to_string()))?;
+                let tab_id = self.next_tab.fetch_add(1, Ordering::Relaxed);
+                self.in_flight.lock().await.insert(id.to_string(), tab_id);
+                let result = tools::call(&self.ws, tab_id, &params.name, &params.arguments).await;
+                self.in_flight.lock().await.remove(&id.to_string());
+                self.ws.tabs.lock().await.remove(&tab_id);
+
+                match result {
+                    Ok(value) => Ok(json!({
+                        "content": [{ "type": "text", "text": serde_json::to_string_pretty(&value).unwrap_or_default() }],
+                        "isError": false,
+                    })),
+                    Err(ToolError::Failed(message)) => Ok(json!({
+                        "content": [{ "type": "text", "text": message }],
+                        "isError": true,
+                    })),
+                    Err(ToolError::Rpc(e)) => Err(e),
+                }
+            }
+            _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Method not found: {method}"))),
+        }
+    }
+
+    async fn notification(&self, method: &str, params: Value) {
+        if method == "notifications/cancelled" {
+            let Ok(params) = serde_json::from_value::<CancelledParams>(params) else { return };
+            if let Some(tab_id) = self.in_flight.lock().await.get(&params.request_id.to_string()) {
+                self.ws.cancel_query(*tab_id).await;
+            }
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let ws = Workspace::load_headless();
+    restrict_to_reads(&mut *ws.config.lock().await);
+    let server = Arc::new(Server { ws, next_tab: AtomicU32::new(1), in_flight: Mutex::new(HashMap::new()) });
+
+    // Responses come back in whatever order requests finish; one writer
+    // keeps each on its own line.
+    let (out_tx, mut out_rx) = mpsc::unbounded_channel::<Value>();
+    let writer = tokio::spawn(async move {
+        let mut stdout = tokio::io::stdout();
+        while let Some(message) = out_rx.recv().await {
+            let mut line = message.to_string();
+            line.push('\n');
+            if stdout.write_all(line.as_bytes()).await.is_err() || stdout.flush().await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let message: Message = match serde_json::from_str(&line) {
+            Ok(message) => message,
+            Err(e) => {
+                let _ = out_tx.send(protocol::response(Value::Null, Err(RpcError::new(PARSE_ERROR, e.to_string()))));
+                continue;
+            }
+        };
+        // Without a method it's a response, and the server asks nothing.
+        let Some(method) = message.method else { continue };
+        match message.id {
+            None => server.notification(&method, message.params).await,
+            Some(id) => {
+                let server = server.clone();
+                let out_tx = out_tx.clone();
+                tokio::spawn(async move {
+                    let result = server.request(&id, &method, message.params).await;
+                    let _ = out_tx.send(protocol::response(id, result));
+                });
+            }
+        }
+    }
+
+    // Stdin closed: let running requests answer, then stop.
+    drop(out_tx);
+    let _ = writer.await;
+    Ok(())
+}
@@ -0,0 +1,248 @@
+//! The tools offered to MCP clients.
+//!
+//! Everything is read-only unless the saved connection opts in to MCP
+//! writes. Connection details go out without credentials, hosts or paths.
+
+use std::collections::HashMap;
+
+use dendron_core::config::SavedConnection;
+use dendron_core::db::postgres::DEFAULT_ROW_LIMIT;
+use dendron_core::query::{self, SqlAnalyzer};
+use dendron_core::workspace::{OpenConnection, Workspace};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::protocol::RpcError;
+
+/// Rows returned by `run_query` unless the client asks for more.
+const DEFAULT_MAX_ROWS: usize = 100;
+
+/// Tool descriptions for `tools/list`.
+pub fn definitions() -> Value {
+    let connection = json!({ "type": "string", "description": "Name of a saved connection, from list_connections" });
+    json!([
+        {
+            "name": "list_connections",
+            "description": "List the saved database connections: name, type, tags, and whether writes are allowed.",
+            "inputSchema": { "type": "object", "properties": {} },
+            "annotations": { "readOnlyHint": true },
+        },
+        {
+            "name": "list_schemas",
+            "description": "List the schemas of a connection.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "connection": connection },
+                "required": ["connection"],
+            },
+            "annotations": { "readOnlyHint": true },
+        },
+        {
+            "name": "list_tables",
+            "description": "List the tables and views in a schema.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "connection": connection,
+                    "schema": { "type": "string", "description": "Schema name; defaults to public (Postgres) or main (SQLite)" },
+                },
+                "required": ["connection"],
+            },
+            "annotations": { "readOnlyHint": true },
+        },
+        {
+            "name": "describe_table",
+            "description": "Columns, indexes and foreign keys of a table.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "connection": connection,
+                    "schema": { "type": "string", "description": "Schema name; defaults to public (Postgres) or main (SQLite)" },
+                    "table": { "type": "string" },
+                },
+                "required": ["connection", "table"],
+            },
+            "annotations": { "readOnlyHint": true },
+        },
+        {
+            "name": "run_query",
+            "description": "Run one SQL statement. Only reads are allowed unless the connection has MCP writes enabled.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "connection": connection,
+                    "sql": { "type": "string", "description": "A single SQL statement" },
+                    "max_rows": {
+                        "type": "integer",
+                        "description": format!("Most rows to return (default {DEFAULT_MAX_ROWS}, at most {DEFAULT_ROW_LIMIT})"),
+                        "minimum": 1,
+                    },
+                },
+                "required": ["connection", "sql"],
+            },
+            "annotations": { "destructiveHint": true },
+        },
+    ])
+}
+
+/// What can go wrong calling a tool.
+pub enum ToolError {
+    /// Unknown tool or malformed arguments: a protocol error.
+    Rpc(RpcError),
+    /// The tool ran and failed; reported to the model as a tool result.
+    Failed(String),
+}
+
+impl<E: std::fmt::Display> From<E> for ToolError {
+    fn from(e: E) -> Self {
+        ToolError::Failed(e.to_string())
+    }
+}
+
+type ToolResult = Result<Value, ToolError>;
+
+fn args<T: DeserializeOwned>(arguments: &Value) -> Result<T, ToolError> {
+    let arguments = if arguments.is_null() { json!({}) } else { arguments.clone() };
+    serde_json::from_value(arguments).map_err(|e| ToolError::Rpc(RpcError::invalid_params(e.to_string())))
+}
+
+/// Run tool `name`, using `tab_id` as the tab its statements belong to.
+pub async fn call(ws: &Workspace, tab_id: u32, name: &str, arguments: &Value) -> ToolResult {
+    match name {
+        "list_connections" => list_connections(ws).await,
+        "list_schemas" => list_schemas(ws, tab_id, args(arguments)?).await,
+        "list_tables" => list_tables(ws, tab_id, args(arguments)?).await,
+        "describe_table" => describe_table(ws, tab_id, args(arguments)?).await,
+        "run_query" => run_query(ws, tab_id, args(arguments)?).await,
+        _ => Err(ToolError::Rpc(RpcError::invalid_params(format!("Unknown tool: {name}")))),
+    }
+}
+
+#[derive(Deserialize)]
+struct ConnectionArgs {
+    connection: String,
+}
+
+#[derive(Deserialize)]
+struct TableArgs {
+    connection: String,
+    #[serde(default)]
+    schema: Option<String>,
+    #[serde(default)]
+    table: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct QueryArgs {
+    connection: String,
+    sql: String,
+    #[serde(default)]
+    max_rows: Option<usize>,
+}
+
+async fn open(ws: &Workspace, tab_id: u32, connection: &str) -> Result<std::sync::Arc<OpenConnection>, ToolError> {
+    ws.set_tab_connection(tab_id, Some(connection.to_string())).await;
+    Ok(ws.open_connection(connection).await?)
+}
+
+fn default_schema(open: &OpenConnection) -> &'static str {
+    if open.conn.is_postgres() { "public" } else { "main" }
+}
+
+async fn list_connections(ws: &Workspace) -> ToolResult {
+    let config = ws.config.lock().await;
+    let connections: Vec<Value> = config.connections.iter()
+        .map(|c| json!({
+            "name": c.name(),
+            "type": match c {
+                SavedConnection::Sqlite { .. } => "sqlite",
+                SavedConnection::Postgres { .. } => "postgres",
+            },
+            "tags": c.tags(),
+            "writes_allowed": c.allows_mcp_writes(),
+        }))
+        .collect();
+    Ok(json!({ "connections": connections }))
+}
+
+async fn list_schemas(ws: &Workspace, tab_id: u32, args: ConnectionArgs) -> ToolResult {
+    let open = open(ws, tab_id, &args.connection).await?;
+    Ok(json!({ "schemas": open.conn.get_schema_names().await? }))
+}
+
+async fn list_tables(ws: &Workspace, tab_id: u32, args: TableArgs) -> ToolResult {
+    let open = open(ws, tab_id, &args.connection).await?;
+    let schema = args.schema.as_deref().unwrap_or(default_schema(&open));
+    let tables: Vec<Value> = open.conn.get_tables_lazy(schema).await?
+        .into_iter()
+        .map(|(name, is_view)| json!({ "name": name, "kind": if is_view { "view" } else { "table" } }))
+        .collect();
+    Ok(json!({ "schema": schema, "tables": tables }))
+}
+
+async fn describe_table(ws: &Workspace, tab_id: u32, args: TableArgs) -> ToolResult {
+    let table = args.table.ok_or_else(|| ToolError::Rpc(RpcError::invalid_params("missing field `table`")))?;
+    let open = open(ws, tab_id, &args.connection).await?;
+    let schema = args.schema.as_deref().unwrap_or(default_schema(&open));
+    let structure = open.conn.describe_table(schema, &table).await?;
+    if structure.columns.is_empty() {
+        return Err(ToolError::Failed(format!("Table '{schema}.{table}' not found")));
+    }
+    Ok(serde_json::to_value(structure)?)
+}
+
+async fn run_query(ws: &Workspace, tab_id: u32, args: QueryArgs) -> ToolResult {
+    let writes_allowed = ws.config.lock().await.connections.iter()
+        .find(|c| c.name() == args.connection)
+        .is_some_and(SavedConnection::allows_mcp_writes);
+    let open = open(ws, tab_id, &args.connection).await?;
+
+    let statements = query::split_statements(&args.sql, open.conn.dialect());
+    let [statement] = statements.as_slice() else {
+        return Err(ToolError::Failed("Pass exactly one SQL statement".to_string()));
+    };
+    let sql = statement.text.as_str();
+
+    let analyzer = SqlAnalyzer::new(open.conn.dialect(), sql);
+    let query_type = analyzer.most_dangerous_type();
+    if !writes_allowed && !analyzer.is_read_only() {
+        return Err(ToolError::Failed(format!(
+            "'{}' is read-only for MCP clients; {} statements need MCP writes enabled on the connection",
+            args.connection,
+            format!("{query_type:?}").to_uppercase(),
+        )));
+    }
+    let safety = open.safety_check(&args.connection, &analyzer);
+    if safety.is_blocked() {
+        return Err(ToolError::Failed(safety.violations.join("\n")));
+    }
+    // Nobody is there to type the database name the team policy asks for.
+    if safety.typed_confirmation.is_some() {
+        return Err(ToolError::Failed(format!(
+            "The policy for '{}' requires a person to confirm this statement in Dendron",
+            args.connection
+        )));
+    }
+
+    let max_rows = args.max_rows.unwrap_or(DEFAULT_MAX_ROWS).clamp(1, DEFAULT_ROW_LIMIT);
+    // Without MCP writes, the database itself refuses writes too.
+    let mut result = if writes_allowed {
+        ws.execute_query(tab_id, sql, 0, &HashMap::new(), None).await?
+    } else {
+        ws.execute_read_only(tab_id, sql, &HashMap::new()).await?
+    };
+    if let Some(affected_rows) = result.affected_rows {
+        return Ok(json!({ "affected_rows": affected_rows, "execution_time_ms": result.execution_time_ms }));
+    }
+    let truncated = result.truncated || result.rows.len() > max_rows;
+    result.rows.truncate(max_rows);
+    Ok(json!({
+        "columns": result.columns,
+        "column_types": result.column_types,
+        "rows": result.rows,
+        "row_count": result.rows.len(),
+        "truncated": truncated,
+        "execution_time_ms": result.execution_time_ms,
+    }))
+}
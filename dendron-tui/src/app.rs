@@ -0,0 +1,444 @@
+//! Application state, key handling and the background work behind it.
+//!
+//! The UI runs on the main thread; anything that talks to a database is
+//! spawned on the tokio runtime and reports back as a [`Msg`], so a slow
+//! query never freezes the screen.
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use dendron_core::config::SavedConnection;
+use dendron_core::db::postgres::QueryResult;
+use dendron_core::error::Result;
+use dendron_core::query::{self, QueryType};
+use dendron_core::workspace::Workspace;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::widgets::ListState;
+use ratatui::DefaultTerminal;
+use tokio::runtime::Handle;
+use tui_textarea::TextArea;
+
+use crate::results::ResultsGrid;
+use crate::schema::{SchemaTree, TreeRow};
+use crate::ui;
+
+/// The TUI runs everything in a single tab of its own.
+const TUI_TAB: u32 = 0;
+
+/// How often the screen is redrawn while waiting for input.
+const TICK: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Schema,
+    Editor,
+    Results,
+}
+
+pub enum Modal {
+    /// Pick a saved connection to open.
+    Connections { connections: Vec<SavedConnection>, state: ListState },
+    /// The app's confirmation for a risky statement.
+    Confirm {
+        sql: String,
+        message: String,
+        /// Text that must be typed to go ahead; otherwise y/n.
+        typed: Option<String>,
+        input: String,
+    },
+}
+
+/// Outcome of background work, sent back to the UI thread.
+pub enum Msg {
+    Connected { name: String, schemas: Result<Vec<String>> },
+    Tables { schema: String, tables: Result<Vec<(String, bool)>> },
+    QueryFinished { sql: String, offset: u64, result: Result<QueryResult> },
+}
+
+pub struct Status {
+    pub text: String,
+    pub is_error: bool,
+}
+
+pub struct App {
+    ws: Arc<Workspace>,
+    rt: Handle,
+    tx: Sender<Msg>,
+    rx: Receiver<Msg>,
+    pub connection: Option<String>,
+    /// The connection being opened, if a connect is pending.
+    connecting: Option<String>,
+    pub focus: Focus,
+    pub modal: Option<Modal>,
+    pub schema: SchemaTree,
+    pub editor: TextArea<'static>,
+    pub results: ResultsGrid,
+    pub status: Option<Status>,
+    /// When the running statement started, if one is running.
+    pub running: Option<Instant>,
+    quit: bool,
+}
+
+impl App {
+    pub fn new(ws: Arc<Workspace>, rt: Handle) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let mut editor = TextArea::default();
+        editor.set_placeholder_text("SQL here; F5 or Ctrl-R runs the statement under the cursor");
+        Self {
+            ws,
+            rt,
+            tx,
+            rx,
+            connection: None,
+            connecting: None,
+            focus: Focus::Editor,
+            modal: None,
+            schema: SchemaTree::default(),
+            editor,
+            results: ResultsGrid::default(),
+            status: None,
+            running: None,
+            quit: false,
+        }
+    }
+
+    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| ui::draw(frame, self))?;
+            if event::poll(TICK)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.on_key(key);
+                    }
+                }
+            }
+            while let Ok(msg) = self.rx.try_recv() {
+                self.on_msg(msg);
+            }
+        }
+        Ok(())
+    }
+
+    fn info(&mut self, text: impl Into<String>) {
+        self.status = Some(Status { text: text.into(), is_error: false });
+    }
+
+    fn error(&mut self, text: impl Into<String>) {
+        self.status = Some(Status { text: text.into(), is_error: true });
+    }
+
+    /// Run `task` on the runtime and deliver what it returns.
+    fn spawn<F>(&self, task: F)
+    where
+        F: std::future::Future<Output = Msg> + Send + 'static,
+    {
+        let tx = self.tx.clone();
+        self.rt.spawn(async move {
+            let _ = tx.send(task.await);
+        });
+    }
+
+    // ── Connections ──────────────────────────────────────────────────────────
+
+    pub fn show_connection_picker(&mut self) {
+        let connections = self.rt.block_on(self.ws.config.lock()).connections.clone();
+        let current = self.connection.as_deref()
+            .and_then(|name| connections.iter().position(|c| c.name() == name));
+        let mut state = ListState::default();
+        state.select(current.or((!connections.is_empty()).then_some(0)));
+        self.modal = Some(Modal::Connections { connections, state });
+    }
+
+    /// Connect in the background; the tab switches to `name` only once the
+    /// connect succeeds.
+    pub fn open_connection(&mut self, name: String) {
+        if let Some(pending) = &self.connecting {
+            self.error(format!("Still connecting to {pending}"));
+            return;
+        }
+        self.info(format!("Connecting to {name}…"));
+        self.connecting = Some(name.clone());
+        let ws = self.ws.clone();
+        self.spawn(async move {
+            let schemas = match ws.open_connection(&name).await {
+                Ok(open) => open.conn.get_schema_names().await,
+                Err(e) => Err(e),
+            };
+            Msg::Connected { name, schemas }
+        });
+    }
+
+    fn load_tables(&self, schema: String) {
+        let ws = self.ws.clone();
+        self.spawn(async move {
+            let tables = match ws.require_tab_connection(TUI_TAB).await {
+                Ok((_, open)) => open.conn.get_tables_lazy(&schema).await,
+                Err(e) => Err(e),
+            };
+            Msg::Tables { schema, tables }
+        });
+    }
+
+    // ── Queries ──────────────────────────────────────────────────────────────
+
+    /// The statement under the editor's cursor.
+    fn statement_at_cursor(&self) -> Option<String> {
+        let sql = self.editor.lines().join("\n");
+        let (row, col) = self.editor.cursor();
+        let line_start: usize = self.editor.lines()[..row].iter().map(|l| l.len() + 1).sum();
+        let line = &self.editor.lines()[row];
+        let offset = line_start + line.char_indices().nth(col).map_or(line.len(), |(i, _)| i);
+        let dialect = self.rt.block_on(self.ws.tab_dialect(TUI_TAB));
+        query::statement_at(&sql, dialect, offset).map(|s| s.text)
+    }
+
+    /// Run the statement under the cursor, asking first where the app would.
+    fn run_statement(&mut self) {
+        if self.running.is_some() {
+            return;
+        }
+        if let Some(pending) = &self.connecting {
+            self.error(format!("Still connecting to {pending}"));
+            return;
+        }
+        if self.connection.is_none() {
+            self.error("Open a connection first (Ctrl-O)");
+            return;
+        }
+        let Some(sql) = self.statement_at_cursor() else {
+            self.error("No SQL to run");
+            return;
+        };
+
+        let safety = self.rt.block_on(self.ws.check_query_safety(TUI_TAB, &sql));
+        if safety.is_blocked() {
+            self.error(safety.violations.join("; "));
+            return;
+        }
+        if !safety.requires_confirmation {
+            self.execute(sql, 0, None);
+            return;
+        }
+        let typed = safety.typed_confirmation.clone().or_else(|| {
+            matches!(safety.query_type, QueryType::Drop | QueryType::Truncate)
+                .then(|| safety.connection_name.clone())
+        });
+        self.modal = Some(Modal::Confirm {
+            sql,
+            message: safety.warning_message(),
+            typed,
+            input: String::new(),
+        });
+    }
+
+    /// Run `sql` from row `offset`. `confirmation` is the text typed in the
+    /// confirmation dialog, if it asked for any.
+    fn execute(&mut self, sql: String, offset: u64, confirmation: Option<String>) {
+        self.running = Some(Instant::now());
+        let ws = self.ws.clone();
+        self.spawn(async move {
+            let result = ws.execute_query(TUI_TAB, &sql, offset, &HashMap::new(), confirmation.as_deref()).await;
+            Msg::QueryFinished { sql, offset, result }
+        });
+    }
+
+    /// Fetch the next page when the cursor reaches the last loaded row.
+    fn load_more_if_needed(&mut self) {
+        if self.running.is_none() && self.results.wants_more() {
+            if let Some(sql) = self.results.sql.clone() {
+                self.execute(sql, self.results.rows.len() as u64, None);
+            }
+        }
+    }
+
+    fn cancel(&mut self) {
+        self.rt.block_on(self.ws.cancel_query(TUI_TAB));
+    }
+
+    // ── Messages ─────────────────────────────────────────────────────────────
+
+    fn on_msg(&mut self, msg: Msg) {
+        match msg {
+            Msg::Connected { name, schemas } => {
+                self.connecting = None;
+                match schemas {
+                    Ok(schemas) => {
+                        self.rt.block_on(self.ws.set_tab_connection(TUI_TAB, Some(name.clone())));
+                        self.schema = SchemaTree::new(schemas);
+                        if let Some(index) = self.schema.default_schema() {
+                            self.schema.expand(index);
+                            self.load_tables(self.schema.schemas[index].name.clone());
+                        }
+                        self.results.clear();
+                        self.info(format!("Connected to {name}"));
+                        self.connection = Some(name);
+                    }
+                    Err(e) => self.error(format!("{name}: {e}")),
+                }
+            }
+            Msg::Tables { schema, tables } => match tables {
+                Ok(tables) => self.schema.set_tables(&schema, tables),
+                Err(e) => {
+                    self.schema.set_tables(&schema, Vec::new());
+                    self.error(format!("{schema}: {e}"));
+                }
+            },
+            Msg::QueryFinished { sql, offset, result } => {
+                let elapsed = self.running.take().map(|t| t.elapsed()).unwrap_or_default();
+                match result {
+                    Ok(result) if offset > 0 => {
+                        self.results.append(result.rows, result.truncated);
+                        let more = if self.results.truncated { "+" } else { "" };
+                        self.info(format!("{}{more} rows loaded", self.results.rows.len()));
+                    }
+                    Ok(result) => match result.affected_rows {
+                        Some(n) => {
+                            self.results.clear();
+                            self.info(format!("{n} row{} affected in {} ms", if n == 1 { "" } else { "s" }, elapsed.as_millis()));
+                        }
+                        None => {
+                            let more = if result.truncated { "+" } else { "" };
+                            self.info(format!("{}{more} rows in {} ms", result.row_count, result.execution_time_ms));
+                            self.results.show(sql, result);
+                        }
+                    },
+                    Err(e) => self.error(e.to_string()),
+                }
+            }
+        }
+    }
+
+    // ── Keys ─────────────────────────────────────────────────────────────────
+
+    fn on_key(&mut self, key: KeyEvent) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if self.modal.is_some() {
+            self.on_modal_key(key);
+            return;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Char('c') if ctrl => {
+                self.cancel();
+                self.quit = true;
+            }
+            KeyCode::Char('o') if ctrl => self.show_connection_picker(),
+            KeyCode::Char('r') if ctrl => self.run_statement(),
+            KeyCode::F(5) => self.run_statement(),
+            KeyCode::Esc if self.running.is_some() => self.cancel(),
+            KeyCode::Tab => self.cycle_focus(true),
+            KeyCode::BackTab => self.cycle_focus(false),
+            _ => match self.focus {
+                Focus::Schema => self.on_schema_key(key),
+                Focus::Editor => {
+                    self.editor.input(key);
+                }
+                Focus::Results => self.on_results_key(key),
+            },
+        }
+    }
+
+    fn cycle_focus(&mut self, forward: bool) {
+        let order = [Focus::Schema, Focus::Editor, Focus::Results];
+        let i = order.iter().position(|f| *f == self.focus).unwrap_or(0);
+        let next = if forward { i + 1 } else { i + order.len() - 1 };
+        self.focus = order[next % order.len()];
+    }
+
+    fn on_schema_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.schema.move_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.schema.move_by(1),
+            KeyCode::PageUp => self.schema.move_by(-10),
+            KeyCode::PageDown => self.schema.move_by(10),
+            KeyCode::Left | KeyCode::Char('h') => self.schema.collapse_selected(),
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ') => {
+                match self.schema.selected_row() {
+                    Some(TreeRow::Schema(i)) if self.schema.schemas[i].expanded && key.code != KeyCode::Right => {
+                        self.schema.collapse_selected();
+                    }
+                    Some(TreeRow::Schema(i)) if self.schema.expand(i) => {
+                        self.load_tables(self.schema.schemas[i].name.clone());
+                    }
+                    // A table goes into the editor at the cursor.
+                    Some(TreeRow::Table(s, t)) if key.code == KeyCode::Enter => {
+                        self.editor.insert_str(self.schema.qualified_name(s, t));
+                        self.focus = Focus::Editor;
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_results_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.results.move_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.results.move_by(1),
+            KeyCode::PageUp => self.results.move_by(-20),
+            KeyCode::PageDown => self.results.move_by(20),
+            KeyCode::Home | KeyCode::Char('g') => self.results.move_by(isize::MIN),
+            KeyCode::End | KeyCode::Char('G') => self.results.select_last(),
+            KeyCode::Left | KeyCode::Char('h') => self.results.scroll_columns(-1),
+            KeyCode::Right | KeyCode::Char('l') => self.results.scroll_columns(1),
+            _ => {}
+        }
+        self.load_more_if_needed();
+    }
+
+    fn on_modal_key(&mut self, key: KeyEvent) {
+        let Some(modal) = &mut self.modal else { return };
+        match modal {
+            Modal::Connections { connections, state } => match key.code {
+                KeyCode::Esc => self.modal = None,
+                KeyCode::Up | KeyCode::Char('k') => state.select_previous(),
+                KeyCode::Down | KeyCode::Char('j') => state.select_next(),
+                KeyCode::Enter => {
+                    let name = state.selected()
+                        .and_then(|i| connections.get(i))
+                        .map(|c| c.name().to_string());
+                    self.modal = None;
+                    if let Some(name) = name {
+                        self.open_connection(name);
+                    }
+                }
+                KeyCode::Char('q') | KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.quit = true;
+                }
+                _ => {}
+            },
+            Modal::Confirm { typed, input, .. } => {
+                let confirmed = match (key.code, typed.as_deref()) {
+                    (KeyCode::Esc, _) => Some(false),
+                    (KeyCode::Enter, Some(expected)) => Some(input == expected),
+                    (KeyCode::Backspace, Some(_)) => {
+                        input.pop();
+                        None
+                    }
+                    (KeyCode::Char(c), Some(_)) => {
+                        input.push(c);
+                        None
+                    }
+                    (KeyCode::Char('y' | 'Y'), None) => Some(true),
+                    (KeyCode::Char('n' | 'N') | KeyCode::Enter, None) => Some(false),
+                    _ => None,
+                };
+                match confirmed {
+                    Some(true) => {
+                        if let Some(Modal::Confirm { sql, typed, input, .. }) = self.modal.take() {
+                            self.execute(sql, 0, typed.and(Some(input)));
+                        }
+                    }
+                    Some(false) => {
+                        self.modal = None;
+                        self.info("Cancelled");
+                    }
+                    None => {}
+                }
+            }
+        }
+    }
+}
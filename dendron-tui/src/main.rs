@@ -0,0 +1,55 @@
+//! `dendron-tui`: a terminal frontend on dendron-core, for servers where the
+//! desktop app can't run.
+//!
+//! Uses the saved connections, team config, audit and history of the app,
+//! and asks for the same confirmations before risky statements.
+
+mod app;
+mod results;
+mod schema;
+mod ui;
+
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use dendron_core::workspace::Workspace;
+
+use app::App;
+
+const USAGE: &str = "Usage: dendron-tui [connection]
+
+Opens the saved connection, or a list to pick one from.";
+
+fn main() -> ExitCode {
+    let connection = std::env::args().nth(1);
+    if matches!(connection.as_deref(), Some("-h" | "--help")) {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("dendron-tui: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let ws = Arc::new(Workspace::load_headless());
+    let mut app = App::new(ws, runtime.handle().clone());
+    match connection {
+        Some(name) => app.open_connection(name),
+        None => app.show_connection_picker(),
+    }
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("dendron-tui: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
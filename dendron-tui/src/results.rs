@@ -0,0 +1,95 @@
+//! The results grid: rows of the last statement, paged in as you scroll.
+
+use dendron_core::db::postgres::QueryResult;
+use ratatui::widgets::TableState;
+
+/// Columns are never drawn wider than this.
+pub const MAX_COLUMN_WIDTH: usize = 40;
+
+#[derive(Default)]
+pub struct ResultsGrid {
+    pub columns: Vec<String>,
+    pub column_types: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// Display width of each column, from its header and cells.
+    pub widths: Vec<u16>,
+    /// More rows are available past the ones loaded.
+    pub truncated: bool,
+    /// The SELECT the rows came from, re-run with an offset for more.
+    pub sql: Option<String>,
+    pub state: TableState,
+    /// First column drawn, for scrolling sideways.
+    pub first_column: usize,
+}
+
+/// A cell as drawn: on one line and no wider than a column may be.
+pub fn display_cell(cell: &str) -> String {
+    let one_line = cell.replace('\n', "\\n").replace('\r', "\\r").replace('\t', "\\t");
+    if one_line.chars().count() > MAX_COLUMN_WIDTH {
+        let mut short: String = one_line.chars().take(MAX_COLUMN_WIDTH - 1).collect();
+        short.push('…');
+        short
+    } else {
+        one_line
+    }
+}
+
+impl ResultsGrid {
+    /// Replace the grid with the first page of a SELECT's result.
+    pub fn show(&mut self, sql: String, result: QueryResult) {
+        *self = Self {
+            // Headers show the name and the type.
+            widths: result.columns.iter().zip(&result.column_types)
+                .map(|(name, ty)| (name.chars().count() + 1 + ty.chars().count()).min(MAX_COLUMN_WIDTH) as u16)
+                .collect(),
+            columns: result.columns,
+            column_types: result.column_types,
+            sql: Some(sql),
+            ..Default::default()
+        };
+        self.append(result.rows, result.truncated);
+        if !self.rows.is_empty() {
+            self.state.select(Some(0));
+        }
+    }
+
+    /// Add the next page of rows.
+    pub fn append(&mut self, rows: Vec<Vec<String>>, truncated: bool) {
+        for row in &rows {
+            for (width, cell) in self.widths.iter_mut().zip(row) {
+                *width = (*width).max(display_cell(cell).chars().count() as u16);
+            }
+        }
+        self.rows.extend(rows);
+        self.truncated = truncated;
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Move the selected row by `delta`, clamped to the loaded rows.
+    pub fn move_by(&mut self, delta: isize) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0);
+        self.state.select(Some(current.saturating_add_signed(delta).min(self.rows.len() - 1)));
+    }
+
+    pub fn select_last(&mut self) {
+        if !self.rows.is_empty() {
+            self.state.select(Some(self.rows.len() - 1));
+        }
+    }
+
+    pub fn scroll_columns(&mut self, delta: isize) {
+        let max = self.columns.len().saturating_sub(1);
+        self.first_column = self.first_column.saturating_add_signed(delta).min(max);
+    }
+
+    /// The cursor is on the last loaded row and the result has more.
+    pub fn wants_more(&self) -> bool {
+        self.truncated && self.state.selected().is_some_and(|i| i + 1 >= self.rows.len())
+    }
+}
@@ -0,0 +1,119 @@
+//! The schema tree: schemas, expanded to their tables on demand.
+
+use dendron_core::query;
+
+pub struct SchemaNode {
+    pub name: String,
+    pub expanded: bool,
+    /// `None` until the tables have been fetched; (name, is_view).
+    pub tables: Option<Vec<(String, bool)>>,
+}
+
+/// One line of the tree as drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeRow {
+    Schema(usize),
+    /// Placeholder under an expanded schema whose tables are still loading.
+    Loading(usize),
+    /// (schema index, table index)
+    Table(usize, usize),
+}
+
+#[derive(Default)]
+pub struct SchemaTree {
+    pub schemas: Vec<SchemaNode>,
+    pub selected: usize,
+}
+
+impl SchemaTree {
+    pub fn new(schemas: Vec<String>) -> Self {
+        Self {
+            schemas: schemas.into_iter()
+                .map(|name| SchemaNode { name, expanded: false, tables: None })
+                .collect(),
+            selected: 0,
+        }
+    }
+
+    /// Schema to expand straight after connecting: the only one, or the
+    /// default one.
+    pub fn default_schema(&self) -> Option<usize> {
+        if self.schemas.len() == 1 {
+            return Some(0);
+        }
+        self.schemas.iter().position(|s| s.name == "public")
+    }
+
+    pub fn rows(&self) -> Vec<TreeRow> {
+        let mut rows = Vec::new();
+        for (i, schema) in self.schemas.iter().enumerate() {
+            rows.push(TreeRow::Schema(i));
+            if !schema.expanded {
+                continue;
+            }
+            match &schema.tables {
+                Some(tables) => rows.extend((0..tables.len()).map(|t| TreeRow::Table(i, t))),
+                None => rows.push(TreeRow::Loading(i)),
+            }
+        }
+        rows
+    }
+
+    pub fn selected_row(&self) -> Option<TreeRow> {
+        self.rows().get(self.selected).copied()
+    }
+
+    pub fn move_by(&mut self, delta: isize) {
+        let len = self.rows().len();
+        if len > 0 {
+            self.selected = self.selected.saturating_add_signed(delta).min(len - 1);
+        }
+    }
+
+    /// Expand schema `index`. Returns true when its tables still need fetching.
+    pub fn expand(&mut self, index: usize) -> bool {
+        let schema = &mut self.schemas[index];
+        schema.expanded = true;
+        schema.tables.is_none()
+    }
+
+    /// Collapse the schema under the cursor, or the one holding the table
+    /// under the cursor, and select it.
+    pub fn collapse_selected(&mut self) {
+        let index = match self.selected_row() {
+            Some(TreeRow::Schema(i) | TreeRow::Loading(i) | TreeRow::Table(i, _)) => i,
+            None => return,
+        };
+        self.schemas[index].expanded = false;
+        self.selected = self.rows().iter().position(|r| *r == TreeRow::Schema(index)).unwrap_or(0);
+    }
+
+    pub fn set_tables(&mut self, schema: &str, tables: Vec<(String, bool)>) {
+        if let Some(node) = self.schemas.iter_mut().find(|s| s.name == schema) {
+            node.tables = Some(tables);
+        }
+    }
+
+    /// How the table at (schema, table) is written in SQL.
+    pub fn qualified_name(&self, schema: usize, table: usize) -> String {
+        let node = &self.schemas[schema];
+        let (name, _) = &node.tables.as_ref().expect("table row without tables")[table];
+        // SQLite's only schema needs no prefix.
+        if node.name == "main" && self.schemas.len() == 1 {
+            sql_name(name)
+        } else {
+            format!("{}.{}", sql_name(&node.name), sql_name(name))
+        }
+    }
+}
+
+/// Quote an identifier only when it would not survive unquoted.
+fn sql_name(name: &str) -> String {
+    let plain = name.chars().next().is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if plain {
+        name.to_string()
+    } else {
+        query::quote_ident(name)
+    }
+}
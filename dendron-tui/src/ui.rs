@@ -0,0 +1,185 @@
+//! Drawing the screen from the [`App`] state.
+
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, Cell, Clear, List, ListItem, Paragraph, Row, Table, Wrap};
+use ratatui::Frame;
+
+use crate::app::{App, Focus, Modal};
+use crate::results::display_cell;
+use crate::schema::TreeRow;
+
+const SIDEBAR_WIDTH: u16 = 32;
+
+fn pane(title: impl Into<Line<'static>>, focused: bool) -> Block<'static> {
+    let block = Block::bordered().title(title.into());
+    if focused {
+        block.border_type(BorderType::Thick).border_style(Style::new().fg(Color::Cyan))
+    } else {
+        block.border_style(Style::new().fg(Color::DarkGray))
+    }
+}
+
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [main, status] = Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+    let [sidebar, body] = Layout::horizontal([Constraint::Length(SIDEBAR_WIDTH), Constraint::Fill(1)]).areas(main);
+    let [editor, results] = Layout::vertical([Constraint::Percentage(35), Constraint::Fill(1)]).areas(body);
+
+    draw_schema(frame, app, sidebar);
+    app.editor.set_block(pane(" Query ", app.focus == Focus::Editor));
+    frame.render_widget(&app.editor, editor);
+    draw_results(frame, app, results);
+    draw_status(frame, app, status);
+
+    match &mut app.modal {
+        Some(Modal::Connections { connections, state }) => {
+            let items: Vec<ListItem> = connections.iter()
+                .map(|c| {
+                    let mut spans = vec![Span::raw(c.name().to_string())];
+                    if !c.tags().is_empty() {
+                        spans.push(Span::raw(format!("  [{}]", c.tags().join(", "))).dark_gray());
+                    }
+                    if c.is_dangerous() {
+                        spans.push(Span::raw("  dangerous").red());
+                    }
+                    ListItem::new(Line::from(spans))
+                })
+                .collect();
+            let area = centered(frame.area(), 60, (items.len() as u16 + 2).clamp(3, 20));
+            let title = if items.is_empty() { " No saved connections " } else { " Open connection " };
+            let list = List::new(items)
+                .block(pane(title, true))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+            frame.render_widget(Clear, area);
+            frame.render_stateful_widget(list, area, state);
+        }
+        Some(Modal::Confirm { message, typed, input, .. }) => {
+            let mut text: Vec<Line> = message.lines().map(|l| Line::raw(l.to_string())).collect();
+            text.push(Line::raw(""));
+            match typed {
+                Some(expected) => {
+                    text.push(Line::raw(format!("Type '{expected}' and press Enter to continue:")));
+                    text.push(Line::raw(format!("> {input}")).bold());
+                }
+                None => text.push(Line::raw("Continue? [y/N]").bold()),
+            }
+            let area = centered(frame.area(), 64, text.len() as u16 + 2);
+            frame.render_widget(Clear, area);
+            frame.render_widget(
+                Paragraph::new(text)
+                    .wrap(Wrap { trim: false })
+                    .block(pane(" Confirm ", true).border_style(Style::new().fg(Color::Red))),
+                area,
+            );
+        }
+        None => {}
+    }
+}
+
+fn draw_schema(frame: &mut Frame, app: &App, area: Rect) {
+    let title = match &app.connection {
+        Some(name) => format!(" {name} "),
+        None => " No connection ".to_string(),
+    };
+    let rows = app.schema.rows();
+    let items: Vec<ListItem> = rows.iter()
+        .map(|row| match *row {
+            TreeRow::Schema(i) => {
+                let schema = &app.schema.schemas[i];
+                let marker = if schema.expanded { "▾" } else { "▸" };
+                ListItem::new(format!("{marker} {}", schema.name)).bold()
+            }
+            TreeRow::Loading(_) => ListItem::new("    loading…").dark_gray(),
+            TreeRow::Table(s, t) => {
+                let (name, is_view) = &app.schema.schemas[s].tables.as_ref().expect("table row without tables")[t];
+                let item = ListItem::new(format!("    {name}"));
+                if *is_view { item.italic() } else { item }
+            }
+        })
+        .collect();
+
+    let focused = app.focus == Focus::Schema;
+    let mut state = ratatui::widgets::ListState::default();
+    if focused && !rows.is_empty() {
+        state.select(Some(app.schema.selected));
+    }
+    let list = List::new(items)
+        .block(pane(title, focused))
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_results(frame: &mut Frame, app: &mut App, area: Rect) {
+    let grid = &mut app.results;
+    let focused = app.focus == Focus::Results;
+    let title = if grid.columns.is_empty() {
+        " Results ".to_string()
+    } else {
+        let more = if grid.truncated { "+" } else { "" };
+        format!(" Results: {}{more} rows ", grid.rows.len())
+    };
+    let block = pane(title, focused);
+
+    if grid.columns.is_empty() {
+        frame.render_widget(block, area);
+        return;
+    }
+
+    let first = grid.first_column;
+    let header = Row::new(
+        grid.columns[first..].iter().zip(&grid.column_types[first..])
+            .map(|(name, ty)| Cell::from(Line::from(vec![Span::raw(name.clone()).bold(), Span::raw(format!(" {ty}")).dark_gray()])))
+    );
+    let widths = grid.widths[first..].iter().map(|w| Constraint::Length(*w));
+
+    // Only the rows that fit are built; the table is told where they start.
+    let height = area.height.saturating_sub(3) as usize;
+    let selected = grid.state.selected().unwrap_or(0);
+    let offset = grid.state.offset().min(selected).max((selected + 1).saturating_sub(height));
+    *grid.state.offset_mut() = offset;
+    let rows = grid.rows.iter().skip(offset).take(height).map(|row| {
+        Row::new(row[first..].iter().map(|cell| {
+            if cell == "NULL" {
+                Cell::from(cell.as_str()).dark_gray()
+            } else {
+                Cell::from(display_cell(cell))
+            }
+        }))
+    });
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(block)
+        .column_spacing(2)
+        .row_highlight_style(if focused { Style::new().add_modifier(Modifier::REVERSED) } else { Style::new() });
+    let mut state = grid.state.clone().with_offset(0).with_selected(Some(selected - offset));
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+fn draw_status(frame: &mut Frame, app: &App, area: Rect) {
+    let hints = match app.focus {
+        Focus::Schema => "Enter insert/expand  ←/→ collapse/expand",
+        Focus::Editor => "F5/Ctrl-R run",
+        Focus::Results => "↑↓ rows  ←→ columns  g/G first/last",
+    };
+    let hints = format!("{hints}  Tab focus  Ctrl-O connect  Ctrl-Q quit ");
+
+    let left = match (&app.running, &app.status) {
+        (Some(started), _) => Line::raw(format!(" Running… {:.1}s  (Esc cancels)", started.elapsed().as_secs_f32())).yellow(),
+        (None, Some(status)) if status.is_error => Line::raw(format!(" {}", status.text.replace('\n', " "))).red(),
+        (None, Some(status)) => Line::raw(format!(" {}", status.text)),
+        (None, None) => Line::raw(""),
+    };
+    let [left_area, right_area] = Layout::horizontal([Constraint::Fill(1), Constraint::Length(hints.chars().count() as u16)])
+        .areas(area);
+    frame.render_widget(Paragraph::new(left), left_area);
+    frame.render_widget(Paragraph::new(hints).dark_gray(), right_area);
+}
+
+/// A `width` × `height` box in the middle of `area`.
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let [row] = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center).areas(area);
+    let [cell] = Layout::horizontal([Constraint::Length(width)]).flex(Flex::Center).areas(row);
+    cell
+}
@@ -0,0 +1,32 @@
+//! Tauri commands for browsing and exporting the audit log
+
+use tauri::State;
+
+use dendron_core::audit::{AuditEntry, AuditExportFormat, AuditFilter};
+use dendron_core::workspace::Workspace;
+
+/// Matching audit entries, newest first.
+#[tauri::command]
+pub async fn get_audit_log(
+    filter: AuditFilter,
+    state: State<'_, Workspace>,
+) -> Result<Vec<AuditEntry>, String> {
+    let audit = state.audit.as_ref()
+        .ok_or_else(|| "Could not determine config directory".to_string())?;
+    let mut entries = audit.read(&filter).map_err(|e| e.to_string())?;
+    entries.reverse();
+    Ok(entries)
+}
+
+/// Write matching entries to `path`; returns how many were written.
+#[tauri::command]
+pub async fn export_audit_log(
+    filter: AuditFilter,
+    format: AuditExportFormat,
+    path: String,
+    state: State<'_, Workspace>,
+) -> Result<usize, String> {
+    let audit = state.audit.as_ref()
+        .ok_or_else(|| "Could not determine config directory".to_string())?;
+    audit.export(&filter, format, std::path::Path::new(&path)).map_err(|e| e.to_string())
+}
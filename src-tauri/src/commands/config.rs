@@ -4,7 +4,8 @@ use tauri::State;
 use serde::{Deserialize, Serialize};
 
 use dendron_core::config::Settings;
-use crate::state::AppState;
+use dendron_core::format::{self, SqlFormatOptions};
+use dendron_core::workspace::Workspace;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SettingsPayload {
@@ -12,6 +13,9 @@ pub struct SettingsPayload {
     pub editor_height: f32,
     pub show_tree: bool,
     pub theme_name: Option<String>,
+    /// Omitted by older callers; saving without it keeps the stored options.
+    #[serde(default)]
+    pub formatter: Option<SqlFormatOptions>,
 }
 
 impl From<&Settings> for SettingsPayload {
@@ -21,12 +25,13 @@ impl From<&Settings> for SettingsPayload {
             editor_height: s.editor_height,
             show_tree: s.show_tree,
             theme_name: s.theme_name.clone(),
+            formatter: Some(s.formatter.clone()),
         }
     }
 }
 
 #[tauri::command]
-pub async fn get_settings(state: State<'_, AppState>) -> Result<SettingsPayload, String> {
+pub async fn get_settings(state: State<'_, Workspace>) -> Result<SettingsPayload, String> {
     let config = state.config.lock().await;
     Ok(SettingsPayload::from(&config.settings))
 }
@@ -34,7 +39,7 @@ pub async fn get_settings(state: State<'_, AppState>) -> Result<SettingsPayload,
 #[tauri::command]
 pub async fn save_settings(
     settings: SettingsPayload,
-    state: State<'_, AppState>,
+    state: State<'_, Workspace>,
 ) -> Result<(), String> {
     let mut config = state.config.lock().await;
     config.settings = Settings {
@@ -42,6 +47,28 @@ pub async fn save_settings(
         editor_height: settings.editor_height,
         show_tree: settings.show_tree,
         theme_name: settings.theme_name,
+        formatter: settings.formatter.unwrap_or_else(|| config.settings.formatter.clone()),
     };
     config.save().map_err(|e| e.to_string())
 }
+
+/// Format `sql` with the saved formatter options. With `range` (JavaScript
+/// string indices, i.e. UTF-16 code units) only that part is formatted; the
+/// whole text is returned either way.
+#[tauri::command]
+pub async fn format_sql(
+    sql: String,
+    range: Option<(usize, usize)>,
+    state: State<'_, Workspace>,
+) -> Result<String, String> {
+    let options = state.config.lock().await.settings.formatter.clone();
+    match range {
+        None => Ok(format::format_sql(&sql, &options)),
+        Some((start, end)) => {
+            let to_byte = |offset| format::utf16_to_byte_offset(&sql, offset)
+                .ok_or_else(|| format!("Offset {offset} is out of range"));
+            let range = to_byte(start)?..to_byte(end)?;
+            format::format_sql_range(&sql, range, &options).map_err(|e| e.to_string())
+        }
+    }
+}
@@ -1,13 +1,10 @@
 //! Tauri commands for connection management
 
-use std::sync::Arc;
 use tauri::State;
 use serde::{Deserialize, Serialize};
 
 use dendron_core::config::{SavedConnection, SshAuth, SshConfig};
-use dendron_core::db::connection::{ConnectionConfig, DatabaseConnection};
-use dendron_core::db::ssh::SshTunnel;
-use crate::state::{AppState, OpenConnection, TabContext};
+use dendron_core::workspace::{self, Workspace};
 
 /// Serializable connection info for the frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +22,10 @@ pub struct ConnectionInfo {
     pub database: Option<String>,
     #[serde(default)]
     pub is_dangerous: bool,
+    #[serde(default)]
+    pub read_only: bool,
+    #[serde(default)]
+    pub mcp_writes: bool,
     // SSH tunnel fields (Postgres only)
     #[serde(default)]
     pub ssh_enabled: bool,
@@ -38,7 +39,7 @@ impl From<&SavedConnection> for ConnectionInfo {
     fn from(conn: &SavedConnection) -> Self {
         let is_dangerous = conn.is_dangerous();
         match conn {
-            SavedConnection::Sqlite { name, path, tags } => ConnectionInfo {
+            SavedConnection::Sqlite { name, path, tags, read_only, mcp_writes } => ConnectionInfo {
                 name: name.clone(),
                 conn_type: "sqlite".to_string(),
                 tags: tags.clone(),
@@ -48,13 +49,15 @@ impl From<&SavedConnection> for ConnectionInfo {
                 username: None,
                 database: None,
                 is_dangerous,
+                read_only: *read_only,
+                mcp_writes: *mcp_writes,
                 ssh_enabled: false,
                 ssh_host: None,
                 ssh_port: None,
                 ssh_username: None,
                 ssh_key_path: None,
             },
-            SavedConnection::Postgres { name, host, port, username, database, tags, .. } => {
+            SavedConnection::Postgres { name, host, port, username, database, tags, read_only, mcp_writes, .. } => {
                 let (ssh_enabled, ssh_host, ssh_port, ssh_username, ssh_key_path) =
                     match conn.ssh() {
                         Some(s) => {
@@ -77,6 +80,8 @@ impl From<&SavedConnection> for ConnectionInfo {
                     username: Some(username.clone()),
                     database: Some(database.clone()),
                     is_dangerous,
+                    read_only: *read_only,
+                    mcp_writes: *mcp_writes,
                     ssh_enabled,
                     ssh_host,
                     ssh_port,
@@ -91,7 +96,7 @@ impl From<&SavedConnection> for ConnectionInfo {
 // ── Saved connection CRUD (unchanged) ─────────────────────────────────────────
 
 #[tauri::command]
-pub async fn list_connections(state: State<'_, AppState>) -> Result<Vec<ConnectionInfo>, String> {
+pub async fn list_connections(state: State<'_, Workspace>) -> Result<Vec<ConnectionInfo>, String> {
     let config = state.config.lock().await;
     Ok(config.connections.iter().map(ConnectionInfo::from).collect())
 }
@@ -101,7 +106,7 @@ pub async fn save_connection(
     conn: ConnectionInfo,
     password: Option<String>,
     ssh_passphrase: Option<String>,
-    state: State<'_, AppState>,
+    state: State<'_, Workspace>,
 ) -> Result<(), String> {
     let mut config = state.config.lock().await;
     let mut saved = build_saved_connection(&conn, password.clone(), ssh_passphrase.clone())?;
@@ -128,11 +133,19 @@ pub async fn save_connection(
     }
 
     config.add_connection(saved);
-    config.save().map_err(|e| e.to_string())
+    config.save().map_err(|e| e.to_string())?;
+    drop(config);
+
+    // An open pool still uses the old settings, `read_only` included.
+    if state.connection(&conn.name).await.is_ok() {
+        state.close_connection(&conn.name).await;
+        state.open_connection(&conn.name).await.map_err(|e| e.to_string())?;
+    }
+    Ok(())
 }
 
 #[tauri::command]
-pub async fn delete_connection(name: String, state: State<'_, AppState>) -> Result<(), String> {
+pub async fn delete_connection(name: String, state: State<'_, Workspace>) -> Result<(), String> {
     let mut config = state.config.lock().await;
     config.remove_connection(&name);
     config.save().map_err(|e| e.to_string())
@@ -145,10 +158,7 @@ pub async fn test_connection(
     ssh_passphrase: Option<String>,
 ) -> Result<(), String> {
     let saved = build_saved_connection(&conn, password, ssh_passphrase)?;
-    let (effective_host, effective_port, _tunnel) = build_tunnel(&saved).await?;
-    let conn_config = saved_to_connection_config_with_host(&saved, effective_host, effective_port)?;
-    // _tunnel dropped here — temporary tunnel torn down after test
-    DatabaseConnection::test_connection(&conn_config).await.map_err(|e| e.to_string())
+    workspace::test_connection(&saved).await.map_err(|e| e.to_string())
 }
 
 // ── App-level connection lifecycle ────────────────────────────────────────────
@@ -156,54 +166,23 @@ pub async fn test_connection(
 /// Open a named connection (establish pool + tunnel) and store it app-wide.
 /// Idempotent: if already open, returns immediately without re-connecting.
 #[tauri::command]
-pub async fn open_connection(name: String, state: State<'_, AppState>) -> Result<(), String> {
-    // Check if already open — avoid holding the lock across await points.
-    {
-        let conns = state.connections.lock().await;
-        if conns.contains_key(&name) {
-            return Ok(());
-        }
-    }
-
-    let saved = {
-        let config = state.config.lock().await;
-        config.connections.iter()
-            .find(|c| c.name() == name)
-            .ok_or_else(|| format!("Connection '{}' not found", name))?
-            .clone()
-    };
-
-    let is_dangerous = saved.is_dangerous();
-    let (effective_host, effective_port, tunnel) = build_tunnel(&saved).await?;
-    let conn_config = saved_to_connection_config_with_host(&saved, effective_host, effective_port)?;
-
-    let db_conn = DatabaseConnection::connect(&conn_config).await
-        .map_err(|e| e.to_string())?;
-
-    let open = Arc::new(OpenConnection {
-        conn: Arc::new(db_conn),
-        is_dangerous,
-        _ssh_tunnel: tunnel,
-    });
-
-    state.connections.lock().await.insert(name, open);
-    Ok(())
+pub async fn open_connection(name: String, state: State<'_, Workspace>) -> Result<(), String> {
+    state.open_connection(&name).await.map(|_| ()).map_err(|e| e.to_string())
 }
 
 /// Close a named connection. Drops the pool and SSH tunnel.
 /// Tabs that pointed to this connection keep their `connection_name` string
-/// but will get "no active connection" errors until reconnected.
+/// and open it again the next time they run a query.
 #[tauri::command]
-pub async fn close_connection(name: String, state: State<'_, AppState>) -> Result<(), String> {
-    state.connections.lock().await.remove(&name);
+pub async fn close_connection(name: String, state: State<'_, Workspace>) -> Result<(), String> {
+    state.close_connection(&name).await;
     Ok(())
 }
 
 /// List names of all currently open (live) connections.
 #[tauri::command]
-pub async fn list_open_connections(state: State<'_, AppState>) -> Result<Vec<String>, String> {
-    let conns = state.connections.lock().await;
-    Ok(conns.keys().cloned().collect())
+pub async fn list_open_connections(state: State<'_, Workspace>) -> Result<Vec<String>, String> {
+    Ok(state.open_connection_names().await)
 }
 
 /// Point a tab at an open connection (or clear it).
@@ -212,35 +191,14 @@ pub async fn list_open_connections(state: State<'_, AppState>) -> Result<Vec<Str
 pub async fn set_tab_connection(
     tab_id: u32,
     connection_name: Option<String>,
-    state: State<'_, AppState>,
+    state: State<'_, Workspace>,
 ) -> Result<(), String> {
-    let mut tabs = state.tabs.lock().await;
-    let ctx = tabs.entry(tab_id).or_insert_with(TabContext::new);
-    ctx.connection_name = connection_name;
+    state.set_tab_connection(tab_id, connection_name).await;
     Ok(())
 }
 
 // ── Helpers ────────────────────────────────────────────────────────────────────
 
-/// Establish an SSH tunnel when the saved connection has one configured.
-/// Returns `(effective_host, effective_port, tunnel)`.
-async fn build_tunnel(saved: &SavedConnection) -> Result<(String, u16, Option<SshTunnel>), String> {
-    match saved {
-        SavedConnection::Postgres { host, port, .. } => {
-            if let Some(ssh) = saved.ssh() {
-                let tunnel = SshTunnel::establish(ssh, host, *port)
-                    .await
-                    .map_err(|e| e.to_string())?;
-                let local_port = tunnel.local_port;
-                Ok(("127.0.0.1".to_string(), local_port, Some(tunnel)))
-            } else {
-                Ok((host.clone(), *port, None))
-            }
-        }
-        _ => Ok((String::new(), 0, None)),
-    }
-}
-
 fn build_saved_connection(
     info: &ConnectionInfo,
     password: Option<String>,
@@ -253,6 +211,8 @@ fn build_saved_connection(
             name: info.name.clone(),
             path: info.path.clone().unwrap_or_default(),
             tags: info.tags.clone(),
+            read_only: info.read_only,
+            mcp_writes: info.mcp_writes,
         }),
         "postgres" => {
             let encrypted_pw = if let Some(pw) = password.filter(|p| !p.is_empty()) {
@@ -300,47 +260,10 @@ fn build_saved_connection(
                 database: info.database.clone().unwrap_or_default(),
                 tags: info.tags.clone(),
                 ssh: ssh_config,
+                read_only: info.read_only,
+                mcp_writes: info.mcp_writes,
             })
         }
         t => Err(format!("Unknown connection type: {}", t)),
     }
 }
-
-pub fn saved_to_connection_config(saved: &SavedConnection) -> Result<ConnectionConfig, String> {
-    match saved {
-        SavedConnection::Sqlite { name, path, .. } => Ok(ConnectionConfig::Sqlite {
-            name: name.clone(),
-            path: std::path::PathBuf::from(path),
-        }),
-        SavedConnection::Postgres { name, host, port, username, database, .. } => {
-            Ok(ConnectionConfig::Postgres {
-                name: name.clone(),
-                host: host.clone(),
-                port: *port,
-                database: database.clone(),
-                username: username.clone(),
-                password: saved.get_password(),
-            })
-        }
-    }
-}
-
-fn saved_to_connection_config_with_host(
-    saved: &SavedConnection,
-    effective_host: String,
-    effective_port: u16,
-) -> Result<ConnectionConfig, String> {
-    match saved {
-        SavedConnection::Sqlite { .. } => saved_to_connection_config(saved),
-        SavedConnection::Postgres { name, username, database, .. } => {
-            Ok(ConnectionConfig::Postgres {
-                name: name.clone(),
-                host: effective_host,
-                port: effective_port,
-                database: database.clone(),
-                username: username.clone(),
-                password: saved.get_password(),
-            })
-        }
-    }
-}
@@ -1,6 +1,21 @@
 //! Tauri commands for exporting query results
 
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, State};
+
 use dendron_core::db::postgres::QueryResult;
+use dendron_core::db::statements::{StatementGenerator, StatementKind};
+use dendron_core::export::{self, ExportFormat, ExportOptions, ExportProgress, ExportSummary, ResultRows};
+use dendron_core::params::ParamValue;
+use dendron_core::workspace::Workspace;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ExportProgressEvent {
+    pub tab_id: u32,
+    #[serde(flatten)]
+    pub progress: ExportProgress,
+}
 
 #[tauri::command]
 pub fn export_csv(results: QueryResult) -> Result<String, String> {
@@ -23,58 +38,128 @@ pub fn export_csv(results: QueryResult) -> Result<String, String> {
     String::from_utf8(data).map_err(|e| e.to_string())
 }
 
+/// JSON array of row objects, typed by `column_types`, from the grid's
+/// display text: `"NULL"` is written as null.
 #[tauri::command]
 pub fn export_json(results: QueryResult) -> Result<String, String> {
-    if results.rows.is_empty() {
-        return Err("No results to export".to_string());
-    }
+    let rows = ResultRows {
+        rows: results.rows.into_iter()
+            .map(|row| row.into_iter().map(|v| if v == "NULL" { None } else { Some(v) }).collect())
+            .collect(),
+        columns: results.columns,
+        column_types: results.column_types,
+    };
+    export::export_results(&rows, ExportFormat::Json, &ExportOptions::default())
+        .map_err(|e| e.to_string())
+}
 
-    let records: Vec<serde_json::Map<String, serde_json::Value>> = results.rows.iter().map(|row| {
-        let mut map = serde_json::Map::new();
-        for (col, val) in results.columns.iter().zip(row.iter()) {
-            let json_val = if val == "NULL" {
-                serde_json::Value::Null
-            } else if let Ok(n) = val.parse::<i64>() {
-                serde_json::Value::Number(n.into())
-            } else if let Ok(n) = val.parse::<f64>() {
-                serde_json::json!(n)
-            } else if val == "true" || val == "false" {
-                serde_json::Value::Bool(val == "true")
-            } else {
-                serde_json::Value::String(val.clone())
-            };
-            map.insert(col.clone(), json_val);
-        }
-        map
-    }).collect();
-
-    serde_json::to_string_pretty(&records).map_err(|e| e.to_string())
+/// Render the first `row_count` rows of `sql` as Markdown, HTML, SQL or any
+/// other text format, e.g. for the clipboard, spelled for the tab's
+/// connection. The rows are read again rather than taken from the grid, so
+/// binary values and NULLs come out whole.
+#[tauri::command]
+pub async fn export_results(
+    tab_id: u32,
+    sql: String,
+    params: Option<HashMap<String, ParamValue>>,
+    row_count: usize,
+    format: ExportFormat,
+    options: ExportOptions,
+    state: State<'_, Workspace>,
+) -> Result<String, String> {
+    let (rows, options) = reread(&state, tab_id, &sql, params, row_count, options).await?;
+    export::export_results(&rows, format, &options).map_err(|e| e.to_string())
 }
 
+/// [`export_results`] written to `path`, in any format including XLSX.
 #[tauri::command]
-pub fn get_row_as_insert(
-    table: String,
-    row: Vec<String>,
-    columns: Vec<String>,
+#[allow(clippy::too_many_arguments)]
+pub async fn save_results(
+    tab_id: u32,
+    sql: String,
+    params: Option<HashMap<String, ParamValue>>,
+    row_count: usize,
+    format: ExportFormat,
+    options: ExportOptions,
+    path: String,
+    state: State<'_, Workspace>,
+) -> Result<(), String> {
+    let (rows, options) = reread(&state, tab_id, &sql, params, row_count, options).await?;
+    export::save_results(&rows, format, &options, &PathBuf::from(path)).map_err(|e| e.to_string())
+}
+
+/// The rows to export, and `options` with `postgres` set from the tab's
+/// connection.
+async fn reread(
+    state: &Workspace,
+    tab_id: u32,
+    sql: &str,
+    params: Option<HashMap<String, ParamValue>>,
+    row_count: usize,
+    options: ExportOptions,
+) -> Result<(ResultRows, ExportOptions), String> {
+    let rows = state.reread_rows(tab_id, sql, &params.unwrap_or_default(), row_count)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (_, open) = state.require_tab_connection(tab_id).await.map_err(|e| e.to_string())?;
+    Ok((rows, ExportOptions { postgres: open.conn.is_postgres(), ..options }))
+}
+
+/// Rows of a result to turn into statements. Cells are display text, with
+/// `"NULL"` meaning NULL; rows of a table with a primary key are read again
+/// in full before statements are written.
+#[derive(Debug, serde::Deserialize)]
+pub struct RowStatementRequest {
+    pub kind: StatementKind,
+    pub schema: Option<String>,
+    pub table: String,
+    pub columns: Vec<String>,
+    pub column_types: Vec<String>,
+    #[serde(default)]
+    pub pk_columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// INSERT / UPSERT / UPDATE / DELETE statements for rows of a result, with
+/// literals typed by `column_types` and spelled for the tab's connection.
+#[tauri::command]
+pub async fn generate_row_statements(
+    tab_id: u32,
+    request: RowStatementRequest,
+    state: State<'_, Workspace>,
 ) -> Result<String, String> {
-    if row.len() != columns.len() {
-        return Err("Row and column count mismatch".to_string());
-    }
+    let (_, open) = state.require_tab_connection(tab_id).await.map_err(|e| e.to_string())?;
+    let postgres = open.conn.is_postgres();
+
+    let grid_rows: Vec<Vec<Option<String>>> = request.rows.into_iter()
+        .map(|row| row.into_iter().map(|v| if v == "NULL" { None } else { Some(v) }).collect())
+        .collect();
 
-    let col_list = columns.join(", ");
-    let val_list: Vec<String> = row.iter().map(|v| {
-        if v == "NULL" {
-            "NULL".to_string()
-        } else {
-            // Escape single quotes
-            format!("'{}'", v.replace('\'', "''"))
-        }
-    }).collect();
-
-    Ok(format!("INSERT INTO {} ({}) VALUES ({});", table, col_list, val_list.join(", ")))
+    let generator = StatementGenerator::new(request.schema.as_deref(), &request.table, request.columns, &request.column_types, postgres)
+        .with_primary_key(request.pk_columns);
+    let rows = generator.lossless_rows(&open.conn, grid_rows).await.map_err(|e| e.to_string())?;
+    generator.generate(request.kind, &rows).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn save_file(path: String, content: String) -> Result<(), String> {
     std::fs::write(&path, content).map_err(|e| e.to_string())
 }
+
+/// Re-run a SELECT on the tab's connection and stream every row to `path`,
+/// without the results grid's row limit. Cancellable via `cancel_query`;
+/// progress is emitted as `export_progress` events.
+#[tauri::command]
+pub async fn export_query_to_file(
+    tab_id: u32,
+    sql: String,
+    path: String,
+    format: ExportFormat,
+    options: ExportOptions,
+    app: AppHandle,
+    state: State<'_, Workspace>,
+) -> Result<ExportSummary, String> {
+    state.export_query_to_file(tab_id, &sql, &PathBuf::from(path), format, &options, |progress| {
+        let _ = app.emit("export_progress", ExportProgressEvent { tab_id, progress: progress.clone() });
+    }).await.map_err(|e| e.to_string())
+}
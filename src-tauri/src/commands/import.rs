@@ -0,0 +1,63 @@
+//! Tauri commands for importing files into tables
+
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, State};
+
+use dendron_core::import::csv::{self, CsvOptions, CsvPreview};
+use dendron_core::import::json::{self, JsonOptions, JsonPreview};
+use dendron_core::import::{ImportOptions, ImportProgress, ImportSummary};
+use dendron_core::workspace::Workspace;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ImportProgressEvent {
+    pub tab_id: u32,
+    #[serde(flatten)]
+    pub progress: ImportProgress,
+}
+
+/// Header, inferred column types and sample rows, for the import dialog.
+#[tauri::command]
+pub fn preview_csv_import(path: String, options: CsvOptions) -> Result<CsvPreview, String> {
+    csv::preview_csv(&PathBuf::from(path), &options).map_err(|e| e.to_string())
+}
+
+/// Discovered columns and inferred types of a JSON/NDJSON file.
+#[tauri::command]
+pub fn preview_json_import(path: String, options: JsonOptions) -> Result<JsonPreview, String> {
+    json::preview_json(&PathBuf::from(path), &options).map_err(|e| e.to_string())
+}
+
+/// Load a CSV/TSV file into a table on the tab's connection.
+/// Cancellable via `cancel_query`; progress is emitted as `import_progress` events.
+/// `confirmation` is the database name, when the environment policy asks for it.
+#[tauri::command]
+pub async fn import_csv(
+    tab_id: u32,
+    path: String,
+    csv_options: CsvOptions,
+    options: ImportOptions,
+    confirmation: Option<String>,
+    app: AppHandle,
+    state: State<'_, Workspace>,
+) -> Result<ImportSummary, String> {
+    state.import_csv(tab_id, &PathBuf::from(path), &csv_options, &options, confirmation.as_deref(), |progress| {
+        let _ = app.emit("import_progress", ImportProgressEvent { tab_id, progress: progress.clone() });
+    }).await.map_err(|e| e.to_string())
+}
+
+/// Load a JSON array or NDJSON file into a table on the tab's connection.
+/// Same cancellation, progress events and confirmation as `import_csv`.
+#[tauri::command]
+pub async fn import_json(
+    tab_id: u32,
+    path: String,
+    json_options: JsonOptions,
+    options: ImportOptions,
+    confirmation: Option<String>,
+    app: AppHandle,
+    state: State<'_, Workspace>,
+) -> Result<ImportSummary, String> {
+    state.import_json(tab_id, &PathBuf::from(path), &json_options, &options, confirmation.as_deref(), |progress| {
+        let _ = app.emit("import_progress", ImportProgressEvent { tab_id, progress: progress.clone() });
+    }).await.map_err(|e| e.to_string())
+}
@@ -3,3 +3,8 @@ pub mod queries;
 pub mod schema;
 pub mod export;
 pub mod config;
+pub mod transfer;
+pub mod import;
+pub mod audit;
+pub mod saved_queries;
+pub mod workspace;
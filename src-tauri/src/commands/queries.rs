@@ -1,10 +1,20 @@
 //! Tauri commands for query execution
 
-use tauri::State;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use dendron_core::db::postgres::{QueryResult, DEFAULT_ROW_LIMIT};
-use dendron_core::query::{QuerySafetyCheck, QueryType, analyze_query, has_top_level_order_by, extract_source_table};
-use crate::state::AppState;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use dendron_core::db::dry_run::{DryRunResult, DEFAULT_DRY_RUN_SAMPLE};
+use dendron_core::db::postgres::QueryResult;
+use dendron_core::params::{ParamKind, ParamValue, ParameterizedSql};
+use dendron_core::history::{HistoryEntry, HistoryFilter};
+use dendron_core::format::{byte_to_utf16_offset, utf16_to_byte_offset};
+use dendron_core::query::{
+    self, wildcard_tables, LintFinding, QuerySafetyCheck, QueryType, SqlAnalyzer, SqlDialect, SqlLinter,
+    StatementSpan,
+};
+use dendron_core::workspace::{CellUpdate, Workspace};
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct EditableInfoResponse {
@@ -21,60 +31,98 @@ pub struct PkColumn {
     pub value: String,
 }
 
+/// Query ids handed out by `execute_query`, unique for the app's lifetime.
+static NEXT_QUERY_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Something a query started by `execute_query` reports, emitted as a
+/// `query_event` event.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct QueryEvent {
+    pub query_id: u64,
+    pub tab_id: u32,
+    #[serde(flatten)]
+    pub kind: QueryEventKind,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum QueryEventKind {
+    Started,
+    /// Rows a SELECT has read so far.
+    Progress { rows_fetched: usize },
+    /// The first rows read, to show while the rest of the page loads. Not
+    /// sent when the whole page arrives at once.
+    FirstBatch {
+        columns: Vec<String>,
+        column_types: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    Completed { result: QueryResult },
+    Failed { error: String },
+}
+
+/// Start running `sql` on the tab's connection and return its query id at
+/// once. How it goes arrives as `query_event` events; `cancel_query` on the
+/// tab stops it. `confirmation` is the text typed in the confirmation dialog.
 #[tauri::command]
-pub async fn execute_query(
+pub fn execute_query(
     tab_id: u32,
     sql: String,
     offset: Option<u64>,
-    state: State<'_, AppState>,
-) -> Result<QueryResult, String> {
-    let offset = offset.unwrap_or(0);
-    // Strip trailing semicolons so the SQL can be safely embedded as a subquery.
-    let sql = sql.trim_end().trim_end_matches(';').to_string();
-    let is_select = analyze_query(&sql) == QueryType::Select;
-    let has_order_by = if is_select { has_top_level_order_by(&sql) } else { true };
-    let effective_sql = if is_select {
-        format!("SELECT * FROM ({sql}) q LIMIT {} OFFSET {offset}", DEFAULT_ROW_LIMIT + 1)
-    } else {
-        sql
-    };
+    params: Option<HashMap<String, ParamValue>>,
+    confirmation: Option<String>,
+    app: AppHandle,
+) -> u64 {
+    let query_id = NEXT_QUERY_ID.fetch_add(1, Ordering::Relaxed);
+    tauri::async_runtime::spawn(async move {
+        let emit = |kind| {
+            let _ = app.emit("query_event", QueryEvent { query_id, tab_id, kind });
+        };
+        emit(QueryEventKind::Started);
 
-    // Resolve connection + register query — drop all locks before any await.
-    let (conn, token, query_id) = {
-        let mut tabs = state.tabs.lock().await;
-        let ctx = tabs.entry(tab_id).or_default();
-        let conn_name = ctx.connection_name.clone()
-            .ok_or_else(|| "No active connection for this tab".to_string())?;
-        let conns = state.connections.lock().await;
-        let open = conns.get(&conn_name)
-            .ok_or_else(|| format!("Connection '{}' is not open", conn_name))?;
-        let conn = open.conn.clone();
-        let (token, query_id) = ctx.start_query();
-        (conn, token, query_id)
-    };
-
-    let result = tokio::select! {
-        res = conn.execute_query(&effective_sql, has_order_by, is_select) => res.map_err(|e| e.to_string()),
-        _ = token.cancelled() => Err("Query was cancelled".to_string()),
-    };
+        let state = app.state::<Workspace>();
+        let mut first_batch_sent = false;
+        let params = params.unwrap_or_default();
+        let offset = offset.unwrap_or(0);
+        let result = state.execute_query_with(tab_id, &sql, offset, &params, confirmation.as_deref(), |progress| {
+            if !first_batch_sent {
+                first_batch_sent = true;
+                emit(QueryEventKind::FirstBatch {
+                    columns: progress.columns.to_vec(),
+                    column_types: progress.column_types.to_vec(),
+                    rows: progress.rows.to_vec(),
+                });
+            }
+            emit(QueryEventKind::Progress { rows_fetched: progress.rows.len() });
+        }).await;
 
-    // Clear the token only if our generation is still current.
-    {
-        let mut tabs = state.tabs.lock().await;
-        if let Some(ctx) = tabs.get_mut(&tab_id) {
-            ctx.finish_query(query_id);
-        }
-    }
+        emit(match result {
+            Ok(result) => QueryEventKind::Completed { result },
+            Err(e) => QueryEventKind::Failed { error: e.to_string() },
+        });
+    });
+    query_id
+}
 
-    result
+/// Run an UPDATE/DELETE in a rolled-back transaction to count the rows it
+/// would touch. Cancellable like a normal query.
+#[tauri::command]
+pub async fn dry_run_query(
+    tab_id: u32,
+    sql: String,
+    sample_limit: Option<usize>,
+    params: Option<HashMap<String, ParamValue>>,
+    state: State<'_, Workspace>,
+) -> Result<DryRunResult, String> {
+    let sample_limit = sample_limit.unwrap_or(DEFAULT_DRY_RUN_SAMPLE);
+    state.dry_run_query(tab_id, &sql, sample_limit, &params.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn cancel_query(tab_id: u32, state: State<'_, AppState>) -> Result<(), String> {
-    let mut tabs = state.tabs.lock().await;
-    if let Some(ctx) = tabs.get_mut(&tab_id) {
-        ctx.cancel_current_query();
-    }
+pub async fn cancel_query(tab_id: u32, state: State<'_, Workspace>) -> Result<(), String> {
+    state.cancel_query(tab_id).await;
     Ok(())
 }
 
@@ -82,48 +130,196 @@ pub async fn cancel_query(tab_id: u32, state: State<'_, AppState>) -> Result<(),
 pub async fn check_query_safety(
     sql: String,
     tab_id: u32,
-    state: State<'_, AppState>,
+    state: State<'_, Workspace>,
 ) -> Result<QuerySafetyCheck, String> {
-    // Grab connection_name from the tab (drop lock before next await).
-    let conn_name = {
-        let tabs = state.tabs.lock().await;
-        match tabs.get(&tab_id) {
-            None => return Ok(QuerySafetyCheck::check(&sql, "unknown", false)),
-            Some(ctx) => ctx.connection_name.clone().unwrap_or_default(),
+    Ok(state.check_query_safety(tab_id, &sql).await)
+}
+
+/// What a write without SQL text, such as committing inline edits, needs on
+/// the tab's connection.
+#[tauri::command]
+pub async fn check_write_safety(
+    tab_id: u32,
+    query_type: QueryType,
+    state: State<'_, Workspace>,
+) -> Result<QuerySafetyCheck, String> {
+    state.check_write_safety(tab_id, &[query_type]).await.map_err(|e| e.to_string())
+}
+
+/// A statement located the way the editor counts: UTF-16 offsets.
+#[derive(Debug, serde::Serialize)]
+pub struct EditorStatement {
+    pub text: String,
+    /// Region owned by the statement, through its `;`.
+    pub from: usize,
+    pub to: usize,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+impl EditorStatement {
+    fn new(sql: &str, span: StatementSpan) -> Self {
+        Self {
+            from: byte_to_utf16_offset(sql, span.region.start),
+            to: byte_to_utf16_offset(sql, span.region.end),
+            start_line: span.start_line,
+            end_line: span.end_line,
+            text: span.text,
         }
-    };
+    }
+}
 
-    let is_dangerous = {
-        let conns = state.connections.lock().await;
-        conns.get(&conn_name).map(|c| c.is_dangerous).unwrap_or(false)
-    };
+#[tauri::command]
+pub async fn split_statements(
+    tab_id: u32,
+    sql: String,
+    state: State<'_, Workspace>,
+) -> Result<Vec<EditorStatement>, String> {
+    let dialect = state.tab_dialect(tab_id).await;
+    Ok(query::split_statements(&sql, dialect)
+        .into_iter()
+        .map(|span| EditorStatement::new(&sql, span))
+        .collect())
+}
+
+/// `offset` is a UTF-16 index into `sql`.
+#[tauri::command]
+pub async fn statement_at(
+    tab_id: u32,
+    sql: String,
+    offset: usize,
+    state: State<'_, Workspace>,
+) -> Result<Option<EditorStatement>, String> {
+    let offset = utf16_to_byte_offset(&sql, offset)
+        .ok_or_else(|| format!("Invalid offset {offset}"))?;
+    let dialect = state.tab_dialect(tab_id).await;
+    Ok(query::statement_at(&sql, dialect, offset).map(|span| EditorStatement::new(&sql, span)))
+}
+
+/// A lint finding with its statement located in UTF-16 offsets.
+#[derive(Debug, serde::Serialize)]
+pub struct EditorLintFinding {
+    #[serde(flatten)]
+    pub finding: LintFinding,
+    pub from: usize,
+    pub to: usize,
+}
+
+#[tauri::command]
+pub async fn lint_query(
+    tab_id: u32,
+    sql: String,
+    state: State<'_, Workspace>,
+) -> Result<Vec<EditorLintFinding>, String> {
+    let conn = state.tab_connection(tab_id).await.map(|open| open.conn.clone());
+    let dialect = conn.as_ref().map_or(SqlDialect::Generic, |c| c.dialect());
+
+    // Widths for the SELECT * check; tables that can't be described are skipped.
+    let mut linter = SqlLinter::new(dialect);
+    if let Some(conn) = &conn {
+        for table in wildcard_tables(&sql, dialect) {
+            let schema = table.0.clone().unwrap_or_else(|| {
+                if conn.is_postgres() { "public".to_string() } else { "main".to_string() }
+            });
+            if let Ok(structure) = conn.describe_table(&schema, &table.1).await {
+                linter = linter.with_table_width(table, structure.columns.len());
+            }
+        }
+    }
 
-    Ok(QuerySafetyCheck::check(&sql, &conn_name, is_dangerous))
+    Ok(linter.lint(&sql)
+        .into_iter()
+        .map(|finding| EditorLintFinding {
+            from: byte_to_utf16_offset(&sql, finding.range.start),
+            to: byte_to_utf16_offset(&sql, finding.range.end),
+            finding,
+        })
+        .collect())
 }
 
+/// A parameter the SQL needs, located the way the editor counts.
+#[derive(Debug, serde::Serialize)]
+pub struct EditorQueryParam {
+    pub name: String,
+    pub kind: ParamKind,
+    /// UTF-16 `[from, to)` of each occurrence.
+    pub ranges: Vec<(usize, usize)>,
+    /// Value last used with the saved query, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<ParamValue>,
+}
+
+/// Parameters in `sql`, prefilled from `saved_query` (a personal query's
+/// path or a project query's file) when given.
 #[tauri::command]
-pub async fn get_query_history(state: State<'_, AppState>) -> Result<Vec<String>, String> {
-    let config = state.config.lock().await;
-    Ok(config.query_history.clone())
+pub async fn get_query_params(
+    tab_id: u32,
+    sql: String,
+    saved_query: Option<String>,
+    state: State<'_, Workspace>,
+) -> Result<Vec<EditorQueryParam>, String> {
+    let dialect = state.tab_dialect(tab_id).await;
+    let remembered = match &saved_query {
+        Some(key) => state.config.lock().await.remembered_params(key).cloned().unwrap_or_default(),
+        None => HashMap::new(),
+    };
+    Ok(ParameterizedSql::new(&sql, dialect).params
+        .into_iter()
+        .map(|p| EditorQueryParam {
+            ranges: p.ranges.iter()
+                .map(|r| (byte_to_utf16_offset(&sql, r.start), byte_to_utf16_offset(&sql, r.end)))
+                .collect(),
+            value: remembered.get(&p.name).cloned(),
+            name: p.name,
+            kind: p.kind,
+        })
+        .collect())
 }
 
+/// Store the values last used with a saved query, keyed as in `get_query_params`.
 #[tauri::command]
-pub async fn add_to_history(query: String, state: State<'_, AppState>) -> Result<(), String> {
+pub async fn remember_query_params(
+    saved_query: String,
+    params: HashMap<String, ParamValue>,
+    state: State<'_, Workspace>,
+) -> Result<(), String> {
     let mut config = state.config.lock().await;
-    // Keep last 100 queries, deduplicated
-    config.query_history.retain(|q| q != &query);
-    config.query_history.insert(0, query);
-    config.query_history.truncate(100);
+    config.remember_params(&saved_query, params);
     config.save().map_err(|e| e.to_string())
 }
 
+/// Matching history entries, newest first.
+#[tauri::command]
+pub async fn get_query_history(
+    filter: HistoryFilter,
+    state: State<'_, Workspace>,
+) -> Result<Vec<HistoryEntry>, String> {
+    let history = state.history().await.map_err(|e| e.to_string())?;
+    history.search(&filter).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_history_entry(id: i64, state: State<'_, Workspace>) -> Result<(), String> {
+    let history = state.history().await.map_err(|e| e.to_string())?;
+    history.delete(id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn clear_query_history(state: State<'_, Workspace>) -> Result<(), String> {
+    let history = state.history().await.map_err(|e| e.to_string())?;
+    history.clear().await.map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_editable_info(
     tab_id: u32,
     sql: String,
-    state: State<'_, AppState>,
+    state: State<'_, Workspace>,
 ) -> Result<EditableInfoResponse, String> {
-    let info = extract_source_table(&sql);
+    let (_, open) = state.require_tab_connection(tab_id).await.map_err(|e| e.to_string())?;
+    let conn = &open.conn;
+
+    let info = SqlAnalyzer::new(conn.dialect(), &sql).editable_info();
     if !info.editable {
         return Ok(EditableInfoResponse {
             editable: false,
@@ -134,19 +330,6 @@ pub async fn get_editable_info(
         });
     }
 
-    // Resolve connection from tab
-    let conn = {
-        let tabs = state.tabs.lock().await;
-        let ctx = tabs.get(&tab_id)
-            .ok_or_else(|| "Tab not found".to_string())?;
-        let conn_name = ctx.connection_name.clone()
-            .ok_or_else(|| "No active connection for this tab".to_string())?;
-        let conns = state.connections.lock().await;
-        let open = conns.get(&conn_name)
-            .ok_or_else(|| format!("Connection '{}' is not open", conn_name))?;
-        open.conn.clone()
-    };
-
     // Default schema based on connection type
     let schema = info.schema.unwrap_or_else(|| {
         if conn.is_postgres() { "public".to_string() } else { "main".to_string() }
@@ -188,42 +371,14 @@ pub async fn update_cell(
     column: String,
     new_value: Option<String>,
     pk_columns: Vec<PkColumn>,
-    state: State<'_, AppState>,
+    confirmation: Option<String>,
+    state: State<'_, Workspace>,
 ) -> Result<u64, String> {
-    if pk_columns.is_empty() {
-        return Err("No primary key columns provided".to_string());
-    }
-
-    let conn = {
-        let tabs = state.tabs.lock().await;
-        let ctx = tabs.get(&tab_id)
-            .ok_or_else(|| "Tab not found".to_string())?;
-        let conn_name = ctx.connection_name.clone()
-            .ok_or_else(|| "No active connection for this tab".to_string())?;
-        let conns = state.connections.lock().await;
-        let open = conns.get(&conn_name)
-            .ok_or_else(|| format!("Connection '{}' is not open", conn_name))?;
-        open.conn.clone()
-    };
-
     let pk_pairs: Vec<(String, String)> = pk_columns.into_iter()
         .map(|pk| (pk.name, pk.value))
         .collect();
-
-    let affected = conn.update_cell(
-        &schema,
-        &table,
-        &column,
-        new_value.as_deref(),
-        &pk_pairs,
-    ).await.map_err(|e| e.to_string())?;
-
-    if affected == 0 {
-        return Err("No rows were updated — the row may have been modified or deleted".to_string());
-    }
-    if affected > 1 {
-        return Err(format!("Expected 1 row affected, got {affected} — this should not happen with a primary key WHERE clause"));
-    }
-
-    Ok(affected)
+    let cell = CellUpdate { schema: &schema, table: &table, column: &column, new_value: new_value.as_deref(), pk_pairs: &pk_pairs };
+    state.update_cell(tab_id, cell, confirmation.as_deref())
+        .await
+        .map_err(|e| e.to_string())
 }
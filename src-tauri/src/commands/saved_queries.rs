@@ -0,0 +1,97 @@
+//! Tauri commands for saved queries, personal and project-shared
+
+use tauri::State;
+use serde::Serialize;
+
+use dendron_core::config::SavedQuery;
+use dendron_core::workspace::Workspace;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SavedQuerySource {
+    /// Stored in config.toml; editable from the app.
+    Personal,
+    /// A `.sql` file in the project's `.dendron/queries/`; edited in git.
+    Project,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedQueryInfo {
+    #[serde(flatten)]
+    pub query: SavedQuery,
+    /// `folder/name`, the key for update and delete.
+    pub path: String,
+    pub source: SavedQuerySource,
+    /// File on disk, for project queries.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+/// Personal queries then project queries, each sorted by path. Project files
+/// are re-read on every call so changes pulled from git show up.
+#[tauri::command]
+pub async fn list_saved_queries(state: State<'_, Workspace>) -> Result<Vec<SavedQueryInfo>, String> {
+    let (mut personal, project_query_params) = {
+        let config = state.config.lock().await;
+        let personal: Vec<SavedQueryInfo> = config.saved_queries.iter()
+            .map(|q| SavedQueryInfo {
+                path: q.path(),
+                query: q.clone(),
+                source: SavedQuerySource::Personal,
+                file: None,
+            })
+            .collect();
+        (personal, config.project_query_params.clone())
+    };
+    personal.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let Some(project) = state.project.lock().await.clone() else {
+        return Ok(personal);
+    };
+    // Reading the queries directory walks the filesystem; keep it off the
+    // async runtime.
+    let project = tauri::async_runtime::spawn_blocking(move || {
+        project.queries().into_iter()
+            .map(|mut q| {
+                let file = q.file.display().to_string();
+                if let Some(params) = project_query_params.get(&file) {
+                    q.query.params = params.clone();
+                }
+                SavedQueryInfo {
+                    path: q.query.path(),
+                    query: q.query,
+                    source: SavedQuerySource::Project,
+                    file: Some(file),
+                }
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    personal.extend(project);
+    Ok(personal)
+}
+
+/// Create or update a personal saved query. Pass `previous_path` when
+/// renaming or moving so the old entry is replaced, and `overwrite` to
+/// replace another query already at the new path.
+#[tauri::command]
+pub async fn save_query(
+    query: SavedQuery,
+    previous_path: Option<String>,
+    overwrite: Option<bool>,
+    state: State<'_, Workspace>,
+) -> Result<String, String> {
+    let mut config = state.config.lock().await;
+    let path = config.save_query(query, previous_path.as_deref(), overwrite.unwrap_or(false))
+        .map_err(|e| e.to_string())?;
+    config.save().map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+#[tauri::command]
+pub async fn delete_saved_query(path: String, state: State<'_, Workspace>) -> Result<(), String> {
+    let mut config = state.config.lock().await;
+    config.remove_saved_query(&path);
+    config.save().map_err(|e| e.to_string())
+}
@@ -3,7 +3,7 @@
 use tauri::State;
 
 use dendron_core::db::schema::{ColumnInfo, TableStructure};
-use crate::state::AppState;
+use dendron_core::workspace::Workspace;
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct TableRow {
@@ -14,14 +14,9 @@ pub struct TableRow {
 #[tauri::command]
 pub async fn get_schema_names(
     connection_name: String,
-    state: State<'_, AppState>,
+    state: State<'_, Workspace>,
 ) -> Result<Vec<String>, String> {
-    let conn = {
-        let conns = state.connections.lock().await;
-        conns.get(&connection_name)
-            .ok_or_else(|| format!("Connection '{}' is not open", connection_name))?
-            .conn.clone()
-    };
+    let conn = state.connection(&connection_name).await.map_err(|e| e.to_string())?.conn.clone();
     conn.get_schema_names().await.map_err(|e| e.to_string())
 }
 
@@ -29,14 +24,9 @@ pub async fn get_schema_names(
 pub async fn get_tables(
     connection_name: String,
     schema: String,
-    state: State<'_, AppState>,
+    state: State<'_, Workspace>,
 ) -> Result<Vec<TableRow>, String> {
-    let conn = {
-        let conns = state.connections.lock().await;
-        conns.get(&connection_name)
-            .ok_or_else(|| format!("Connection '{}' is not open", connection_name))?
-            .conn.clone()
-    };
+    let conn = state.connection(&connection_name).await.map_err(|e| e.to_string())?.conn.clone();
     let tables = conn.get_tables_lazy(&schema).await.map_err(|e| e.to_string())?;
     Ok(tables.into_iter().map(|(name, is_view)| TableRow { name, is_view }).collect())
 }
@@ -46,14 +36,9 @@ pub async fn get_columns(
     connection_name: String,
     schema: String,
     table: String,
-    state: State<'_, AppState>,
+    state: State<'_, Workspace>,
 ) -> Result<Vec<ColumnInfo>, String> {
-    let conn = {
-        let conns = state.connections.lock().await;
-        conns.get(&connection_name)
-            .ok_or_else(|| format!("Connection '{}' is not open", connection_name))?
-            .conn.clone()
-    };
+    let conn = state.connection(&connection_name).await.map_err(|e| e.to_string())?.conn.clone();
     conn.get_columns_lazy(&schema, &table).await.map_err(|e| e.to_string())
 }
 
@@ -62,14 +47,9 @@ pub async fn describe_table(
     connection_name: String,
     schema: String,
     table: String,
-    state: State<'_, AppState>,
+    state: State<'_, Workspace>,
 ) -> Result<TableStructure, String> {
-    let conn = {
-        let conns = state.connections.lock().await;
-        conns.get(&connection_name)
-            .ok_or_else(|| format!("Connection '{}' is not open", connection_name))?
-            .conn.clone()
-    };
+    let conn = state.connection(&connection_name).await.map_err(|e| e.to_string())?.conn.clone();
     conn.describe_table(&schema, &table).await.map_err(|e| e.to_string())
 }
 
@@ -77,14 +57,11 @@ pub async fn describe_table(
 pub async fn get_completions(
     prefix: String,
     connection_name: String,
-    state: State<'_, AppState>,
+    state: State<'_, Workspace>,
 ) -> Result<Vec<String>, String> {
     use dendron_core::schema_ops::SchemaOperations;
 
-    let conn = {
-        let conns = state.connections.lock().await;
-        conns.get(&connection_name).map(|c| c.conn.clone())
-    };
+    let conn = state.connection(&connection_name).await.ok().map(|open| open.conn.clone());
 
     let mut ops = SchemaOperations::new();
 
@@ -0,0 +1,35 @@
+//! Tauri commands for copying data between connections
+
+use tauri::{AppHandle, Emitter, State};
+
+use dendron_core::db::transfer::{TransferOptions, TransferProgress, TransferSource, TransferSummary};
+use dendron_core::workspace::Workspace;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TransferProgressEvent {
+    pub tab_id: u32,
+    #[serde(flatten)]
+    pub progress: TransferProgress,
+}
+
+/// Copy a table or SELECT result from one open connection into another.
+/// Runs under the tab's cancel token, so `cancel_query` aborts it; progress is
+/// emitted as `transfer_progress` events. `confirmation` is the destination's
+/// database name, when its environment policy asks for it.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn transfer_table(
+    tab_id: u32,
+    source_connection: String,
+    dest_connection: String,
+    source: TransferSource,
+    options: TransferOptions,
+    confirmation: Option<String>,
+    app: AppHandle,
+    state: State<'_, Workspace>,
+) -> Result<TransferSummary, String> {
+    let connections = (source_connection.as_str(), dest_connection.as_str());
+    state.transfer_table(tab_id, connections, &source, &options, confirmation.as_deref(), |progress| {
+        let _ = app.emit("transfer_progress", TransferProgressEvent { tab_id, progress: progress.clone() });
+    }).await.map_err(|e| e.to_string())
+}
@@ -0,0 +1,46 @@
+//! Tauri commands for the persisted workspace (open tabs and project)
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+use tauri::State;
+
+use dendron_core::workspace::{Workspace, WorkspaceSnapshot};
+
+/// The workspace restored at startup, updated with anything since.
+#[tauri::command]
+pub async fn get_workspace(state: State<'_, Workspace>) -> Result<WorkspaceSnapshot, String> {
+    Ok(state.snapshot.lock().await.clone())
+}
+
+/// Record the editor's tabs. Saved to disk once edits pause.
+#[tauri::command]
+pub async fn save_workspace(workspace: WorkspaceSnapshot, state: State<'_, Workspace>) -> Result<(), String> {
+    state.update_snapshot(|snapshot| snapshot.apply_update(workspace)).await;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectInfo {
+    pub name: String,
+    pub root: String,
+}
+
+/// The project whose shared queries are listed, if any.
+#[tauri::command]
+pub async fn get_project(state: State<'_, Workspace>) -> Result<Option<ProjectInfo>, String> {
+    Ok(state.project.lock().await.as_ref().map(|p| ProjectInfo {
+        name: p.name.clone(),
+        root: p.root.display().to_string(),
+    }))
+}
+
+/// Switch to the project at or above `path`; it is reopened at next launch.
+#[tauri::command]
+pub async fn open_project(path: String, state: State<'_, Workspace>) -> Result<ProjectInfo, String> {
+    let project = state.open_project(&PathBuf::from(path)).await.map_err(|e| e.to_string())?;
+    Ok(ProjectInfo {
+        name: project.name,
+        root: project.root.display().to_string(),
+    })
+}
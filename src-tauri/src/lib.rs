@@ -1,15 +1,15 @@
 pub mod commands;
-pub mod state;
 
-use commands::{connections::*, queries::*, schema::*, export::*, config::*};
-use state::AppState;
+use commands::{connections::*, queries::*, schema::*, export::*, config::*, transfer::*, import::*, audit::*, saved_queries::*, workspace::*};
+use dendron_core::workspace::Workspace;
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
-        .manage(AppState::new())
+        .manage(Workspace::load())
         .invoke_handler(tauri::generate_handler![
             // connections (saved config CRUD)
             list_connections,
@@ -24,9 +24,17 @@ pub fn run() {
             // queries
             execute_query,
             cancel_query,
+            dry_run_query,
             check_query_safety,
+            check_write_safety,
+            split_statements,
+            statement_at,
+            lint_query,
+            get_query_params,
+            remember_query_params,
             get_query_history,
-            add_to_history,
+            delete_history_entry,
+            clear_query_history,
             get_editable_info,
             update_cell,
             // schema
@@ -38,12 +46,40 @@ pub fn run() {
             // export
             export_csv,
             export_json,
-            get_row_as_insert,
+            export_results,
+            save_results,
+            generate_row_statements,
             save_file,
+            export_query_to_file,
+            // transfer
+            transfer_table,
+            // import
+            preview_csv_import,
+            import_csv,
+            preview_json_import,
+            import_json,
+            // saved queries
+            list_saved_queries,
+            save_query,
+            delete_saved_query,
+            // audit
+            get_audit_log,
+            export_audit_log,
+            // workspace
+            get_workspace,
+            save_workspace,
+            get_project,
+            open_project,
             // config
             get_settings,
             save_settings,
+            format_sql,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                app.state::<Workspace>().flush_snapshot();
+            }
+        });
 }
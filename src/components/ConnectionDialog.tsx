@@ -22,6 +22,8 @@ interface FormState {
   username: string;
   password: string;
   path: string;
+  readOnly: boolean;
+  mcpWrites: boolean;
   // SSH tunnel
   useSsh: boolean;
   sshHost: string;
@@ -41,6 +43,8 @@ const DEFAULTS: FormState = {
   username: "postgres",
   password: "",
   path: "",
+  readOnly: false,
+  mcpWrites: false,
   useSsh: false,
   sshHost: "",
   sshPort: "22",
@@ -66,6 +70,8 @@ export function ConnectionDialog({ editing }: { editing?: ConnectionInfo }) {
       username: editing.username ?? "postgres",
       password: "",
       path: editing.path ?? "",
+      readOnly: editing.read_only ?? false,
+      mcpWrites: editing.mcp_writes ?? false,
       useSsh: editing.ssh_enabled ?? false,
       sshHost: editing.ssh_host ?? "",
       sshPort: String(editing.ssh_port ?? 22),
@@ -112,6 +118,8 @@ export function ConnectionDialog({ editing }: { editing?: ConnectionInfo }) {
         port: parseInt(form.port, 10) || 5432,
         database: form.database,
         username: form.username,
+        read_only: form.readOnly,
+        mcp_writes: form.mcpWrites,
         ssh_enabled: form.useSsh,
         ssh_host: form.useSsh ? form.sshHost : undefined,
         ssh_port: form.useSsh ? (parseInt(form.sshPort, 10) || 22) : undefined,
@@ -124,6 +132,8 @@ export function ConnectionDialog({ editing }: { editing?: ConnectionInfo }) {
       type: "sqlite",
       tags,
       path: form.path,
+      read_only: form.readOnly,
+      mcp_writes: form.mcpWrites,
     };
   }
 
@@ -307,6 +317,82 @@ export function ConnectionDialog({ editing }: { editing?: ConnectionInfo }) {
             </div>
           </div>
 
+          {/* Read-only */}
+          <div className="flex items-center justify-between">
+            <div className="flex flex-col">
+              <span style={{ fontSize: "11px", fontWeight: 500, color: "var(--text-muted)", textTransform: "uppercase", letterSpacing: "0.06em" }}>
+                Read-only
+              </span>
+              <span style={{ fontSize: "11px", color: "var(--text-muted)" }}>
+                The database rejects every write from this connection.
+              </span>
+            </div>
+            <button
+              onClick={() => update("readOnly", !form.readOnly)}
+              style={{
+                width: "32px",
+                height: "18px",
+                borderRadius: "9px",
+                background: form.readOnly ? "var(--accent)" : "var(--border-strong)",
+                position: "relative",
+                transition: "background 0.15s",
+                flexShrink: 0,
+              }}
+              aria-label="Toggle read-only"
+            >
+              <span
+                style={{
+                  position: "absolute",
+                  top: "2px",
+                  left: form.readOnly ? "16px" : "2px",
+                  width: "14px",
+                  height: "14px",
+                  borderRadius: "50%",
+                  background: "white",
+                  transition: "left 0.15s",
+                }}
+              />
+            </button>
+          </div>
+
+          {/* MCP writes */}
+          <div className="flex items-center justify-between">
+            <div className="flex flex-col">
+              <span style={{ fontSize: "11px", fontWeight: 500, color: "var(--text-muted)", textTransform: "uppercase", letterSpacing: "0.06em" }}>
+                MCP writes
+              </span>
+              <span style={{ fontSize: "11px", color: "var(--text-muted)" }}>
+                AI tools connected over MCP may change data, not just read it.
+              </span>
+            </div>
+            <button
+              onClick={() => update("mcpWrites", !form.mcpWrites)}
+              style={{
+                width: "32px",
+                height: "18px",
+                borderRadius: "9px",
+                background: form.mcpWrites ? "var(--accent)" : "var(--border-strong)",
+                position: "relative",
+                transition: "background 0.15s",
+                flexShrink: 0,
+              }}
+              aria-label="Toggle MCP writes"
+            >
+              <span
+                style={{
+                  position: "absolute",
+                  top: "2px",
+                  left: form.mcpWrites ? "16px" : "2px",
+                  width: "14px",
+                  height: "14px",
+                  borderRadius: "50%",
+                  background: "white",
+                  transition: "left 0.15s",
+                }}
+              />
+            </button>
+          </div>
+
           {dbType === "postgres" ? (
             <>
               <div className="flex gap-2">
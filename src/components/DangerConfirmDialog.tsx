@@ -1,17 +1,21 @@
 import { useState, useEffect, useRef } from "react";
 import { useHotkey } from "@tanstack/react-hotkeys";
 import { Button } from "@/components/ui/Button";
+import type { LintFinding } from "@/lib/types";
 
 export interface DangerConfirmRequest {
   /** The warning message explaining what will happen. */
   message: string;
   /** For the most destructive operations (DROP, TRUNCATE), require typing the connection name. */
   requireTypedConfirmation?: string;
+  /** Lint findings to list under the message. */
+  findings?: LintFinding[];
 }
 
 interface DangerConfirmDialogProps {
   request: DangerConfirmRequest;
-  onConfirm: () => void;
+  /** Gets the text typed, which the backend checks against the policy. */
+  onConfirm: (typed: string) => void;
   onCancel: () => void;
 }
 
@@ -104,6 +108,26 @@ export function DangerConfirmDialog({ request, onConfirm, onCancel }: DangerConf
             {request.message}
           </div>
 
+          {/* Lint findings */}
+          {request.findings && request.findings.length > 0 && (
+            <ul className="flex flex-col gap-1" style={{ fontSize: "12px", lineHeight: 1.4 }}>
+              {request.findings.map((f, i) => (
+                <li key={i} className="flex gap-2">
+                  <span
+                    style={{
+                      color: f.severity === "error" ? "#f87171" : f.severity === "warning" ? "#fbbf24" : "var(--text-muted)",
+                      fontFamily: "var(--font-mono)",
+                      flexShrink: 0,
+                    }}
+                  >
+                    L{f.start_line}
+                  </span>
+                  <span style={{ color: "var(--text-secondary)" }}>{f.message}</span>
+                </li>
+              ))}
+            </ul>
+          )}
+
           {/* Typed confirmation for DROP/TRUNCATE */}
           {needsTyping && (
             <div className="flex flex-col gap-1.5">
@@ -122,7 +146,7 @@ export function DangerConfirmDialog({ request, onConfirm, onCancel }: DangerConf
                 value={typed}
                 onChange={(e) => setTyped(e.target.value)}
                 onKeyDown={(e) => {
-                  if (e.key === "Enter" && typedCorrectly) onConfirm();
+                  if (e.key === "Enter" && typedCorrectly) onConfirm(typed);
                 }}
                 className="rounded"
                 style={{
@@ -152,7 +176,7 @@ export function DangerConfirmDialog({ request, onConfirm, onCancel }: DangerConf
           <Button
             variant="danger"
             size="sm"
-            onClick={onConfirm}
+            onClick={() => onConfirm(typed)}
             disabled={!typedCorrectly}
           >
             Execute
@@ -0,0 +1,185 @@
+import { useState } from "react";
+import { useHotkey } from "@tanstack/react-hotkeys";
+import { Button } from "@/components/ui/Button";
+import type { ParamValue, QueryParam } from "@/lib/types";
+
+type ParamType = ParamValue["type"];
+
+interface ParamsDialogProps {
+  params: QueryParam[];
+  /** Values used earlier this session, by parameter name; a param's own `value` wins. */
+  previous: Record<string, ParamValue>;
+  onSubmit: (values: Record<string, ParamValue>) => void;
+  onCancel: () => void;
+}
+
+interface Draft {
+  type: ParamType;
+  text: string;
+}
+
+function toDraft(value: ParamValue | undefined): Draft {
+  if (!value) return { type: "text", text: "" };
+  if (value.type === "null") return { type: "null", text: "" };
+  return { type: value.type, text: String(value.value) };
+}
+
+/** The typed value for a draft, or an error message. */
+function parseDraft(draft: Draft): ParamValue | string {
+  switch (draft.type) {
+    case "null":
+      return { type: "null" };
+    case "text":
+      return { type: "text", value: draft.text };
+    case "bool": {
+      const t = draft.text.trim().toLowerCase();
+      if (["true", "t", "1", "yes"].includes(t)) return { type: "bool", value: true };
+      if (["false", "f", "0", "no"].includes(t)) return { type: "bool", value: false };
+      return "Expected true or false";
+    }
+    case "int": {
+      const t = draft.text.trim();
+      return /^[-+]?\d+$/.test(t) && Number.isSafeInteger(Number(t))
+        ? { type: "int", value: Number(t) }
+        : "Expected a whole number";
+    }
+    case "float": {
+      const n = Number(draft.text.trim());
+      return draft.text.trim() !== "" && Number.isFinite(n) ? { type: "float", value: n } : "Expected a number";
+    }
+  }
+}
+
+export function ParamsDialog({ params, previous, onSubmit, onCancel }: ParamsDialogProps) {
+  const [drafts, setDrafts] = useState<Record<string, Draft>>(() =>
+    Object.fromEntries(params.map((p) => [p.name, toDraft(p.value ?? previous[p.name])])),
+  );
+
+  useHotkey("Escape", () => onCancel());
+
+  const parsed = params.map((p) => [p.name, parseDraft(drafts[p.name])] as const);
+  const valid = parsed.every(([, v]) => typeof v !== "string");
+
+  const submit = () => {
+    if (!valid) return;
+    onSubmit(Object.fromEntries(parsed) as Record<string, ParamValue>);
+  };
+
+  const setDraft = (name: string, update: Partial<Draft>) =>
+    setDrafts((prev) => ({ ...prev, [name]: { ...prev[name], ...update } }));
+
+  return (
+    <>
+      {/* Backdrop */}
+      <div
+        className="fixed inset-0 z-50"
+        style={{ background: "rgba(0,0,0,0.7)" }}
+        onClick={onCancel}
+      />
+
+      {/* Dialog */}
+      <div
+        className="fixed z-50 flex flex-col overflow-hidden"
+        style={{
+          top: "50%",
+          left: "50%",
+          transform: "translate(-50%, -50%)",
+          width: "460px",
+          maxHeight: "80vh",
+          background: "var(--bg-elevated)",
+          border: "1px solid var(--border-strong)",
+          borderRadius: "8px",
+          boxShadow: "0 24px 48px rgba(0,0,0,0.6)",
+        }}
+        onClick={(e) => e.stopPropagation()}
+      >
+        <div className="flex flex-col gap-3 p-4" style={{ overflowY: "auto" }}>
+          <span style={{ fontSize: "14px", fontWeight: 600, color: "var(--text-primary)" }}>
+            Query Parameters
+          </span>
+
+          {params.map((p, i) => {
+            const draft = drafts[p.name];
+            const error = parsed[i][1];
+            return (
+              <div key={p.name} className="flex flex-col gap-1">
+                <div className="flex items-center gap-2">
+                  <span
+                    style={{
+                      width: "110px",
+                      flexShrink: 0,
+                      fontFamily: "var(--font-mono)",
+                      fontSize: "12px",
+                      color: "var(--accent)",
+                      overflow: "hidden",
+                      textOverflow: "ellipsis",
+                    }}
+                    title={p.name}
+                  >
+                    {p.name}
+                  </span>
+                  <select
+                    value={draft.type}
+                    onChange={(e) => setDraft(p.name, { type: e.target.value as ParamType })}
+                    className="rounded"
+                    style={{
+                      height: "28px",
+                      background: "var(--bg-overlay)",
+                      border: "1px solid var(--border-strong)",
+                      fontSize: "12px",
+                      color: "var(--text-primary)",
+                    }}
+                  >
+                    <option value="text">text</option>
+                    <option value="int">int</option>
+                    <option value="float">float</option>
+                    <option value="bool">bool</option>
+                    <option value="null">null</option>
+                  </select>
+                  <input
+                    autoFocus={i === 0}
+                    type="text"
+                    value={draft.text}
+                    disabled={draft.type === "null"}
+                    onChange={(e) => setDraft(p.name, { text: e.target.value })}
+                    onKeyDown={(e) => {
+                      if (e.key === "Enter") submit();
+                    }}
+                    className="flex-1 rounded"
+                    style={{
+                      height: "28px",
+                      padding: "0 8px",
+                      background: "var(--bg-overlay)",
+                      border: "1px solid var(--border-strong)",
+                      fontSize: "12px",
+                      fontFamily: "var(--font-mono)",
+                      color: "var(--text-primary)",
+                    }}
+                    autoComplete="off"
+                    spellCheck={false}
+                  />
+                </div>
+                {typeof error === "string" && draft.text !== "" && (
+                  <span style={{ fontSize: "11px", color: "var(--error)", marginLeft: "118px" }}>{error}</span>
+                )}
+              </div>
+            );
+          })}
+        </div>
+
+        {/* Footer */}
+        <div
+          className="flex items-center justify-end gap-2 px-4 border-t"
+          style={{ height: "48px", borderColor: "var(--border)" }}
+        >
+          <Button variant="ghost" size="sm" onClick={onCancel}>
+            Cancel
+          </Button>
+          <Button variant="accent" size="sm" onClick={submit} disabled={!valid}>
+            Run
+          </Button>
+        </div>
+      </div>
+    </>
+  );
+}
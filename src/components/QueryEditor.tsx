@@ -7,15 +7,15 @@ import {
 } from "react";
 import { useQuery } from "@tanstack/react-query";
 import { EditorView, keymap, Decoration, type DecorationSet } from "@codemirror/view";
-import { EditorState, Compartment, StateField } from "@codemirror/state";
+import { EditorState, Compartment, StateField, StateEffect } from "@codemirror/state";
 import { Prec } from "@codemirror/state";
 import { basicSetup } from "codemirror";
 import { sql, StandardSQL } from "@codemirror/lang-sql";
 import { HighlightStyle, syntaxHighlighting } from "@codemirror/language";
 import { tags } from "@lezer/highlight";
 import { api } from "@/lib/tauri";
-import { ENV_META } from "@/lib/types";
-import { splitStatements, statementAtOffset, type SqlStatement } from "@/lib/sql-utils";
+import { ENV_META, type CursorPosition, type SqlStatement } from "@/lib/types";
+import { statementAtOffset } from "@/lib/sql-utils";
 import { Button } from "@/components/ui/Button";
 import { Spinner } from "@/components/ui/Spinner";
 
@@ -24,9 +24,9 @@ export interface QueryEditorHandle {
   /** Returns the trimmed SQL of the current selection, or null if nothing is selected. */
   getSelectedText: () => string | null;
   /** Returns the statement under the cursor (when there are multiple statements). */
-  getStatementAtCursor: () => SqlStatement | null;
+  getStatementAtCursor: () => Promise<SqlStatement | null>;
   /** Returns all statements parsed from the editor content. */
-  getAllStatements: () => SqlStatement[];
+  getAllStatements: () => Promise<SqlStatement[]>;
 }
 
 interface QueryEditorProps {
@@ -41,6 +41,9 @@ interface QueryEditorProps {
   connectionEnv?: import("@/lib/types").ConnectionEnvironment;
   openConnections: string[];
   onConnectionChange: (name: string) => void;
+  /** Selection to restore when the editor mounts. */
+  initialCursor?: CursorPosition | null;
+  onCursorChange?: (cursor: CursorPosition) => void;
 }
 
 // ── Active-statement highlighting ────────────────────────────
@@ -48,9 +51,32 @@ const activeStatementLine = Decoration.line({
   class: "cm-active-statement-line",
 });
 
+/** Replaces the cached statement split after the backend answers. */
+const setStatements = StateEffect.define<SqlStatement[]>();
+
+/** Last backend split of the document, mapped through edits made since. */
+const statementsField = StateField.define<SqlStatement[]>({
+  create() {
+    return [];
+  },
+  update(statements, tr) {
+    for (const effect of tr.effects) {
+      if (effect.is(setStatements)) return effect.value;
+    }
+    if (!tr.docChanged) return statements;
+    return statements.map((stmt) => ({
+      ...stmt,
+      from: tr.changes.mapPos(stmt.from, -1),
+      to: tr.changes.mapPos(stmt.to, 1),
+    }));
+  },
+});
+
+/** How long the document must be idle before it is re-split. */
+const SPLIT_DEBOUNCE_MS = 150;
+
 function computeActiveStatementDecorations(state: EditorState): DecorationSet {
-  const doc = state.doc.toString();
-  const statements = splitStatements(doc);
+  const statements = state.field(statementsField);
 
   // Only highlight when there are 2+ statements
   if (statements.length <= 1) return Decoration.none;
@@ -164,19 +190,47 @@ const dendronHighlight = syntaxHighlighting(
 
 // ── Component ────────────────────────────────────────────────
 export const QueryEditor = forwardRef<QueryEditorHandle, QueryEditorProps>(
-  ({ tabId, defaultValue, onValueChange, onRun, onRunAll, onCancel, isRunning, connectionName, connectionEnv, openConnections, onConnectionChange }, ref) => {
+  ({ tabId, defaultValue, onValueChange, onRun, onRunAll, onCancel, isRunning, connectionName, connectionEnv, openConnections, onConnectionChange, initialCursor, onCursorChange }, ref) => {
     const containerRef = useRef<HTMLDivElement>(null);
     const viewRef = useRef<EditorView | null>(null);
     const sqlCompartment = useRef(new Compartment());
+    const splitTimer = useRef<ReturnType<typeof setTimeout>>(undefined);
     const [showHistory, setShowHistory] = useState(false);
+    const [historySearch, setHistorySearch] = useState("");
+    const [historyThisConnection, setHistoryThisConnection] = useState(false);
     const [showConnectionDropdown, setShowConnectionDropdown] = useState(false);
 
+    const historyConnection = historyThisConnection ? connectionName : null;
     const historyQuery = useQuery({
-      queryKey: ["query-history"],
-      queryFn: api.queries.getHistory,
+      queryKey: ["query-history", historySearch, historyConnection],
+      queryFn: () =>
+        api.queries.getHistory({
+          text: historySearch.trim() || undefined,
+          connection: historyConnection ?? undefined,
+          limit: 100,
+        }),
       enabled: showHistory,
     });
 
+    /** Re-split the document in the backend once typing pauses. */
+    function scheduleSplit() {
+      clearTimeout(splitTimer.current);
+      splitTimer.current = setTimeout(async () => {
+        const view = viewRef.current;
+        if (!view) return;
+        const doc = view.state.doc.toString();
+        try {
+          const statements = await api.queries.splitStatements(tabId, doc);
+          // Drop stale answers; the field keeps mapping the old split meanwhile.
+          if (viewRef.current === view && view.state.doc.toString() === doc) {
+            view.dispatch({ effects: setStatements.of(statements) });
+          }
+        } catch {
+          // Keep the previous split
+        }
+      }, SPLIT_DEBOUNCE_MS);
+    }
+
     useImperativeHandle(ref, () => ({
       setValue(value: string) {
         const view = viewRef.current;
@@ -196,20 +250,18 @@ export const QueryEditor = forwardRef<QueryEditorHandle, QueryEditorProps>(
         return text || null;
       },
 
-      getStatementAtCursor() {
+      async getStatementAtCursor() {
         const view = viewRef.current;
         if (!view) return null;
         const doc = view.state.doc.toString();
-        const statements = splitStatements(doc);
-        if (statements.length === 0) return null;
         const cursor = view.state.selection.main.head;
-        return statementAtOffset(statements, cursor);
+        return api.queries.statementAt(tabId, doc, cursor);
       },
 
-      getAllStatements() {
+      async getAllStatements() {
         const view = viewRef.current;
         if (!view) return [];
-        return splitStatements(view.state.doc.toString());
+        return api.queries.splitStatements(tabId, view.state.doc.toString());
       },
     }));
 
@@ -217,14 +269,19 @@ export const QueryEditor = forwardRef<QueryEditorHandle, QueryEditorProps>(
     useEffect(() => {
       if (!containerRef.current) return;
 
+      const clamp = (pos: number) => Math.min(Math.max(pos, 0), defaultValue.length);
       const view = new EditorView({
         state: EditorState.create({
           doc: defaultValue,
+          selection: initialCursor
+            ? { anchor: clamp(initialCursor.anchor), head: clamp(initialCursor.head) }
+            : undefined,
           extensions: [
             basicSetup,
             sqlCompartment.current.of(sql({ dialect: StandardSQL })),
             dendronTheme,
             dendronHighlight,
+            statementsField,
             activeStatementField,
             Prec.highest(
               keymap.of([
@@ -249,6 +306,11 @@ export const QueryEditor = forwardRef<QueryEditorHandle, QueryEditorProps>(
             EditorView.updateListener.of((update) => {
               if (update.docChanged) {
                 onValueChange(update.state.doc.toString());
+                scheduleSplit();
+              }
+              if (update.selectionSet) {
+                const { anchor, head } = update.state.selection.main;
+                onCursorChange?.({ anchor, head });
               }
             }),
           ],
@@ -257,8 +319,13 @@ export const QueryEditor = forwardRef<QueryEditorHandle, QueryEditorProps>(
       });
 
       viewRef.current = view;
+      if (initialCursor) {
+        view.dispatch({ effects: EditorView.scrollIntoView(view.state.selection.main.head, { y: "center" }) });
+      }
+      scheduleSplit();
 
       return () => {
+        clearTimeout(splitTimer.current);
         view.destroy();
         viewRef.current = null;
       };
@@ -343,6 +410,12 @@ export const QueryEditor = forwardRef<QueryEditorHandle, QueryEditorProps>(
       };
     }, [connectionName, tabId]);
 
+    // The connection's dialect decides how the script splits
+    useEffect(() => {
+      scheduleSplit();
+      // eslint-disable-next-line react-hooks/exhaustive-deps
+    }, [connectionName]);
+
     function insertHistoryQuery(query: string) {
       viewRef.current?.dispatch({
         changes: { from: 0, to: viewRef.current.state.doc.length, insert: query },
@@ -459,6 +532,34 @@ export const QueryEditor = forwardRef<QueryEditorHandle, QueryEditorProps>(
                   overflowY: "auto",
                 }}
               >
+                <div
+                  className="flex items-center gap-2 px-2 py-1.5 border-b"
+                  style={{ borderColor: "var(--border-subtle)" }}
+                  onClick={(e) => e.stopPropagation()}
+                >
+                  <input
+                    autoFocus
+                    value={historySearch}
+                    onChange={(e) => setHistorySearch(e.target.value)}
+                    placeholder="Search history..."
+                    className="flex-1 h-6 px-2 rounded text-xs"
+                    style={{
+                      background: "var(--bg-surface)",
+                      border: "1px solid var(--border)",
+                      color: "var(--text-primary)",
+                    }}
+                  />
+                  {connectionName && (
+                    <label className="flex items-center gap-1" style={{ color: "var(--text-muted)", fontSize: "11px" }}>
+                      <input
+                        type="checkbox"
+                        checked={historyThisConnection}
+                        onChange={(e) => setHistoryThisConnection(e.target.checked)}
+                      />
+                      This connection
+                    </label>
+                  )}
+                </div>
                 {historyQuery.isLoading && (
                   <div className="flex items-center gap-2 p-3" style={{ color: "var(--text-muted)", fontSize: "12px" }}>
                     <Spinner size="xs" />
@@ -471,12 +572,17 @@ export const QueryEditor = forwardRef<QueryEditorHandle, QueryEditorProps>(
                   </div>
                 )}
                 {(historyQuery.data ?? [])
-                  .slice()
-                  .reverse()
-                  .map((query, i) => (
+                  .map((entry) => (
                     <button
-                      key={i}
-                      onClick={() => insertHistoryQuery(query)}
+                      key={entry.id}
+                      onClick={() => insertHistoryQuery(entry.sql)}
+                      title={[
+                        entry.timestamp,
+                        entry.connection,
+                        entry.success
+                          ? entry.row_count != null ? `${entry.row_count} rows, ${entry.duration_ms} ms` : null
+                          : entry.error,
+                      ].filter(Boolean).join(" · ")}
                       className="w-full text-left px-3 py-2 border-b transition-colors"
                       style={{
                         fontFamily: "var(--font-mono)",
@@ -496,7 +602,8 @@ export const QueryEditor = forwardRef<QueryEditorHandle, QueryEditorProps>(
                         (e.currentTarget as HTMLButtonElement).style.color = "var(--text-secondary)";
                       }}
                     >
-                      {query.replace(/\s+/g, " ").trim()}
+                      {!entry.success && <span style={{ color: "var(--error)", marginRight: "6px" }}>&#10007;</span>}
+                      {entry.sql.replace(/\s+/g, " ").trim()}
                     </button>
                   ))}
               </div>
@@ -1,4 +1,4 @@
-import { useRef, useMemo, useState, useEffect, type ReactNode } from "react";
+import { useRef, useMemo, useState, useEffect, type ReactNode, type MouseEvent as ReactMouseEvent } from "react";
 import { useHotkey } from "@tanstack/react-hotkeys";
 import {
   useReactTable,
@@ -8,7 +8,7 @@ import {
   type ColumnSizingState,
 } from "@tanstack/react-table";
 import { useVirtualizer } from "@tanstack/react-virtual";
-import type { QueryResult, EditableInfo, PkColumn, StatementResult } from "@/lib/types";
+import type { QueryResult, EditableInfo, PkColumn, StatementResult, StatementKind, ResultSummary } from "@/lib/types";
 import { Badge } from "@/components/ui/Badge";
 import { Spinner } from "@/components/ui/Spinner";
 import { Button } from "@/components/ui/Button";
@@ -16,6 +16,7 @@ import { formatMs } from "@/lib/utils";
 import { save } from "@tauri-apps/plugin-dialog";
 import { api } from "@/lib/tauri";
 import { useContextMenu, type ContextMenuItem } from "@/components/ui/ContextMenu";
+import { useWorkspace } from "@/lib/WorkspaceContext";
 
 interface ResultsTableProps {
   result: QueryResult | null;
@@ -30,12 +31,21 @@ interface ResultsTableProps {
   activeResultIndex?: number;
   /** Callback to switch active result sub-tab. */
   onActiveResultChange?: (index: number) => void;
+  /** What the tab's last run returned before a restart; shown in place of the empty state. */
+  lastResult?: ResultSummary | null;
+}
+
+function describeSummary(summary: ResultSummary): string {
+  if (summary.error) return "failed";
+  const n = summary.affected_rows ?? summary.row_count;
+  const noun = summary.affected_rows != null ? "affected" : "returned";
+  return `${n.toLocaleString()}${summary.truncated ? "+" : ""} row${n !== 1 ? "s" : ""} ${noun} in ${formatMs(summary.execution_time_ms)}`;
 }
 
 const ROW_HEIGHT = 28;
 const HEADER_HEIGHT = 28;
 
-export function ResultsTable({ result, error, isRunning, onLoadMore, editableInfo, tabId, results, activeResultIndex, onActiveResultChange }: ResultsTableProps) {
+export function ResultsTable({ result, error, isRunning, onLoadMore, editableInfo, tabId, results, activeResultIndex, onActiveResultChange, lastResult }: ResultsTableProps) {
   // ── Multi-result mode ─────────────────────────────────────
   if (results && results.length > 1) {
     const idx = activeResultIndex ?? 0;
@@ -120,7 +130,8 @@ export function ResultsTable({ result, error, isRunning, onLoadMore, editableInf
   }
 
   // ── Single-result mode (unchanged) ────────────────────────
-  if (isRunning) {
+  // A running SELECT's first rows show as soon as they arrive.
+  if (isRunning && !result) {
     return (
       <div className="flex flex-col h-full overflow-hidden" style={{ background: "var(--bg-surface)" }}>
         <Toolbar />
@@ -165,6 +176,11 @@ export function ResultsTable({ result, error, isRunning, onLoadMore, editableInf
           <span style={{ fontSize: "12px", color: "var(--text-muted)" }}>
             Run a query to see results
           </span>
+          {lastResult && (
+            <span style={{ fontSize: "11px", color: "var(--text-muted)" }}>
+              Last run {new Date(lastResult.ran_at).toLocaleString()}: {describeSummary(lastResult)}
+            </span>
+          )}
         </div>
       </div>
     );
@@ -192,7 +208,7 @@ export function ResultsTable({ result, error, isRunning, onLoadMore, editableInf
     );
   }
 
-  return <DataTable result={result} onLoadMore={onLoadMore} editableInfo={editableInfo} tabId={tabId} />;
+  return <DataTable result={result} isRunning={isRunning} onLoadMore={onLoadMore} editableInfo={editableInfo} tabId={tabId} />;
 }
 
 function Toolbar({
@@ -265,8 +281,7 @@ function Toolbar({
           }}
         >
           {result.row_count.toLocaleString()} row{result.row_count !== 1 ? "s" : ""}
-          {" · "}
-          {formatMs(result.execution_time_ms)}
+          {isRunning ? " so far" : <>{" · "}{formatMs(result.execution_time_ms)}</>}
         </span>
       )}
 
@@ -426,19 +441,24 @@ interface EditingCell {
 
 function DataTable({
   result,
+  isRunning,
   onLoadMore,
   editableInfo,
   tabId,
 }: {
   result: QueryResult;
+  isRunning?: boolean;
   onLoadMore?: () => Promise<void>;
   editableInfo?: EditableInfo | null;
   tabId: number;
 }) {
   const parentRef = useRef<HTMLDivElement>(null);
   const [selectedCell, setSelectedCell] = useState<SelectedCell | null>(null);
-  const [selectedRow, setSelectedRow] = useState<number | null>(null);
+  // Rows picked in the gutter, in order; the anchor is the last plain or Cmd/Ctrl click.
+  const [selectedRows, setSelectedRows] = useState<number[]>([]);
+  const [anchorRow, setAnchorRow] = useState<number | null>(null);
   const { showContextMenu, contextMenuElement } = useContextMenu();
+  const { confirmWrite } = useWorkspace();
 
   // Editing state
   const [editingCell, setEditingCell] = useState<EditingCell | null>(null);
@@ -460,6 +480,54 @@ function DataTable({
     (pk) => result.columns.includes(pk)
   );
 
+  const copyRowStatements = async (kind: StatementKind, rowIdxs: number[]) => {
+    const sql = await api.export.rowStatements(tabId, {
+      kind,
+      schema: editableInfo?.schema,
+      table: editableInfo?.table ?? "table",
+      columns: result.columns,
+      column_types: result.column_types,
+      // Without the key in the result, rows can't be read again in full.
+      pk_columns: canEdit ? editableInfo!.pk_columns : [],
+      rows: rowIdxs.map((i) => result.rows[i]),
+    });
+    navigator.clipboard.writeText(sql);
+  };
+
+  // Shift-click extends the selection from the anchor, Cmd/Ctrl-click toggles a row.
+  function selectRow(rowIdx: number, e: ReactMouseEvent) {
+    if (e.shiftKey && anchorRow !== null) {
+      const [from, to] = anchorRow < rowIdx ? [anchorRow, rowIdx] : [rowIdx, anchorRow];
+      setSelectedRows(Array.from({ length: to - from + 1 }, (_, i) => from + i));
+    } else if (e.metaKey || e.ctrlKey) {
+      setSelectedRows((rows) =>
+        rows.includes(rowIdx) ? rows.filter((r) => r !== rowIdx) : [...rows, rowIdx].sort((a, b) => a - b)
+      );
+      setAnchorRow(rowIdx);
+    } else {
+      setSelectedRows([rowIdx]);
+      setAnchorRow(rowIdx);
+    }
+    setSelectedCell(null);
+  }
+
+  // A row inside the selection acts for the whole selection.
+  const rowsFor = (rowIdx: number) => (selectedRows.includes(rowIdx) ? selectedRows : [rowIdx]);
+
+  const statementLabel = (rowIdxs: number[], kind: string) =>
+    rowIdxs.length > 1 ? `Copy ${rowIdxs.length} rows as ${kind}` : `Copy as ${kind}`;
+
+  const keyedStatementItems = (rowIdx: number) => {
+    const rows = rowsFor(rowIdx);
+    return canEdit
+      ? [
+          { label: statementLabel(rows, "UPSERT"), onClick: () => copyRowStatements("upsert", rows) },
+          { label: statementLabel(rows, "UPDATE"), onClick: () => copyRowStatements("update", rows) },
+          { label: statementLabel(rows, "DELETE"), onClick: () => copyRowStatements("delete", rows) },
+        ]
+      : [];
+  };
+
   // Clear all edits when result changes
   useEffect(() => {
     setPendingEdits({});
@@ -508,6 +576,13 @@ function DataTable({
   async function commitAllEdits() {
     if (!editableInfo?.editable || isCommitting || pendingEditCount === 0) return;
 
+    const confirmed = await confirmWrite(
+      tabId,
+      "Update",
+      `Commit ${pendingEditCount} edit${pendingEditCount !== 1 ? "s" : ""} to ${editableInfo.table}?`,
+    );
+    if (!confirmed) return;
+
     setIsCommitting(true);
     setEditError(null);
 
@@ -541,6 +616,7 @@ function DataTable({
           result.columns[colIdx],
           serverValue,
           pkValues,
+          confirmed.confirmation,
         );
         // Move from pending to committed
         setCommittedEdits((prev) => ({ ...prev, [editKey]: newDisplayValue }));
@@ -634,25 +710,26 @@ function DataTable({
   const tableWidth = table.getCenterTotalSize();
 
   useEffect(() => {
-    const idx = selectedCell?.rowIdx ?? selectedRow;
+    const idx = selectedCell?.rowIdx ?? anchorRow;
     if (idx !== null && idx !== undefined) {
       rowVirtualizer.scrollToIndex(idx, { align: "auto" });
     }
-  }, [selectedCell, selectedRow, rowVirtualizer]);
+  }, [selectedCell, anchorRow, rowVirtualizer]);
 
   useHotkey("Mod+C", () => {
     if (window.getSelection()?.toString()) return;
     if (selectedCell) {
       navigator.clipboard.writeText(selectedCell.value === "NULL" ? "" : selectedCell.value);
-    } else if (selectedRow !== null) {
-      navigator.clipboard.writeText(result.rows[selectedRow].join("\t"));
+    } else if (selectedRows.length > 0) {
+      navigator.clipboard.writeText(selectedRows.map((i) => result.rows[i].join("\t")).join("\n"));
     }
-  }, { enabled: selectedCell !== null || selectedRow !== null });
+  }, { enabled: selectedCell !== null || selectedRows.length > 0 });
 
   return (
     <div className="flex flex-col h-full overflow-hidden" style={{ background: "var(--bg-surface)" }}>
       <Toolbar
         result={result}
+        isRunning={isRunning}
         onLoadMore={onLoadMore}
         pendingEditCount={pendingEditCount}
         onCommit={commitAllEdits}
@@ -782,7 +859,7 @@ function DataTable({
               >
                 {row.getVisibleCells().map((cell) => {
                   const isGutter = cell.column.id === "__row__";
-                  const isRowSelected = selectedRow === virtualRow.index;
+                  const isRowSelected = selectedRows.includes(virtualRow.index);
 
                   if (isGutter) {
                     return (
@@ -801,10 +878,7 @@ function DataTable({
                             ? "rgba(96,165,250,0.18)"
                             : "rgba(0,0,0,0.12)",
                         }}
-                        onClick={() => {
-                          setSelectedRow(virtualRow.index);
-                          setSelectedCell(null);
-                        }}
+                        onClick={(e) => selectRow(virtualRow.index, e)}
                         onContextMenu={(e) => {
                           const rowData = result.rows[virtualRow.index];
                           showContextMenu(e, [
@@ -818,13 +892,11 @@ function DataTable({
                               },
                             },
                             {
-                              label: "Copy as INSERT",
+                              label: statementLabel(rowsFor(virtualRow.index), "INSERT"),
                               separator: true,
-                              onClick: async () => {
-                                const insert = await api.export.rowAsInsert("table", rowData, result.columns);
-                                navigator.clipboard.writeText(insert);
-                              },
+                              onClick: () => copyRowStatements("insert", rowsFor(virtualRow.index)),
                             },
+                            ...keyedStatementItems(virtualRow.index),
                           ]);
                         }}
                       >
@@ -924,7 +996,7 @@ function DataTable({
                           type: result.column_types[colIdx] ?? "",
                           value: displayValue,
                         });
-                        setSelectedRow(null);
+                        setSelectedRows([]);
                       }}
                       onDoubleClick={() => startEditing(virtualRow.index, colIdx)}
                       onContextMenu={(e) => {
@@ -943,11 +1015,9 @@ function DataTable({
                           {
                             label: "Copy row as INSERT",
                             separator: true,
-                            onClick: async () => {
-                              const insert = await api.export.rowAsInsert("table", rowData, result.columns);
-                              navigator.clipboard.writeText(insert);
-                            },
+                            onClick: () => copyRowStatements("insert", [virtualRow.index]),
                           },
+                          ...keyedStatementItems(virtualRow.index),
                         ];
                         if (canEdit) {
                           items.push(
@@ -14,6 +14,11 @@ export interface WorkspaceContextValue {
   runAllQueries: () => Promise<void>;
   loadMoreQuery: () => Promise<void>;
   cancelActiveQuery: () => void;
+  /**
+   * Ask for the database name before a write without SQL text (e.g. "Update" for inline edits)
+   * when the policy wants it. Resolves to null to stop, or the confirmation to send.
+   */
+  confirmWrite: (tabId: number, queryType: string, message: string) => Promise<{ confirmation?: string } | null>;
   insertSql: (sql: string) => void;
   /** Open a new tab pointed at connectionName with sql pre-filled, optionally auto-running it. */
   openSqlInNewTab: (connectionName: string, sql: string, autoRun?: boolean, label?: string) => Promise<void>;
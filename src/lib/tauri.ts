@@ -1,18 +1,88 @@
 // Typed wrappers around Tauri invoke
 
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import type {
   ConnectionInfo,
   QueryResult,
+  QueryEvent,
   QuerySafetyCheck,
+  SqlStatement,
+  LintFinding,
+  DryRunResult,
+  HistoryEntry,
+  HistoryFilter,
+  SavedQuery,
+  SavedQueryInfo,
+  QueryParam,
+  ParamValue,
   ColumnInfo,
   TableRow,
   TableStructure,
   Settings,
   EditableInfo,
   PkColumn,
+  TransferSource,
+  TransferOptions,
+  TransferSummary,
+  CsvOptions,
+  CsvPreview,
+  ImportOptions,
+  ImportSummary,
+  JsonOptions,
+  JsonPreview,
+  ExportFormat,
+  ExportOptions,
+  ExportSummary,
+  RowStatementRequest,
+  AuditEntry,
+  AuditFilter,
+  AuditExportFormat,
+  WorkspaceSnapshot,
+  ProjectInfo,
 } from "./types";
 
+/**
+ * Start a query and resolve with its result. The backend answers with a
+ * query id at once and reports through `query_event` events; `onEvent` sees
+ * each of this query's events as it arrives.
+ */
+async function runQuery(
+  tabId: number,
+  sql: string,
+  offset: number | undefined,
+  params: Record<string, ParamValue> | undefined,
+  confirmation: string | undefined,
+  onEvent: ((event: QueryEvent) => void) | undefined,
+): Promise<QueryResult> {
+  let resolve!: (result: QueryResult) => void;
+  let reject!: (error: string) => void;
+  const done = new Promise<QueryResult>((res, rej) => {
+    resolve = res;
+    reject = rej;
+  });
+  const handle = (event: QueryEvent) => {
+    onEvent?.(event);
+    if (event.kind === "completed") resolve(event.result);
+    else if (event.kind === "failed") reject(event.error);
+  };
+
+  // Events can arrive before the id does; hold them until it's known.
+  let queryId: number | null = null;
+  const early: QueryEvent[] = [];
+  const unlisten = await listen<QueryEvent>("query_event", ({ payload }) => {
+    if (queryId === null) early.push(payload);
+    else if (payload.query_id === queryId) handle(payload);
+  });
+  try {
+    queryId = await invoke<number>("execute_query", { tabId, sql, offset, params, confirmation });
+    early.filter((event) => event.query_id === queryId).forEach(handle);
+    return await done;
+  } finally {
+    unlisten();
+  }
+}
+
 export const api = {
   connections: {
     list: () => invoke<ConnectionInfo[]>("list_connections"),
@@ -40,17 +110,55 @@ export const api = {
   },
 
   queries: {
-    execute: (tabId: number, sql: string, offset?: number) =>
-      invoke<QueryResult>("execute_query", { tabId, sql, offset }),
+    /**
+     * `params` binds placeholder names (":id", "$1", "${var}", ...) to typed values.
+     * `confirmation` is the text typed in the confirmation dialog, checked by the backend.
+     * `onEvent` gets the query's `query_event`s: progress and the first rows before it completes.
+     */
+    execute: (
+      tabId: number,
+      sql: string,
+      offset?: number,
+      params?: Record<string, ParamValue>,
+      confirmation?: string,
+      onEvent?: (event: QueryEvent) => void,
+    ) => runQuery(tabId, sql, offset, params, confirmation, onEvent),
 
     cancel: (tabId: number) => invoke<void>("cancel_query", { tabId }),
 
+    dryRun: (tabId: number, sql: string, sampleLimit?: number, params?: Record<string, ParamValue>) =>
+      invoke<DryRunResult>("dry_run_query", { tabId, sql, sampleLimit, params }),
+
     checkSafety: (sql: string, tabId: number) =>
       invoke<QuerySafetyCheck>("check_query_safety", { sql, tabId }),
 
-    getHistory: () => invoke<string[]>("get_query_history"),
+    /** Safety check for a write without SQL text, like committing inline edits. */
+    checkWriteSafety: (tabId: number, queryType: string) =>
+      invoke<QuerySafetyCheck>("check_write_safety", { tabId, queryType }),
+
+    splitStatements: (tabId: number, sql: string) =>
+      invoke<SqlStatement[]>("split_statements", { tabId, sql }),
+
+    statementAt: (tabId: number, sql: string, offset: number) =>
+      invoke<SqlStatement | null>("statement_at", { tabId, sql, offset }),
+
+    lint: (tabId: number, sql: string) =>
+      invoke<LintFinding[]>("lint_query", { tabId, sql }),
+
+    /** Placeholders in `sql`, prefilled from the saved query's remembered values when given. */
+    getParams: (tabId: number, sql: string, savedQuery?: string | null) =>
+      invoke<QueryParam[]>("get_query_params", { tabId, sql, savedQuery: savedQuery ?? null }),
+
+    rememberParams: (savedQuery: string, params: Record<string, ParamValue>) =>
+      invoke<void>("remember_query_params", { savedQuery, params }),
 
-    addHistory: (query: string) => invoke<void>("add_to_history", { query }),
+    /** Statements run from the app, newest first. Recorded by the backend. */
+    getHistory: (filter: HistoryFilter = {}) =>
+      invoke<HistoryEntry[]>("get_query_history", { filter }),
+
+    deleteHistory: (id: number) => invoke<void>("delete_history_entry", { id }),
+
+    clearHistory: () => invoke<void>("clear_query_history"),
 
     getEditableInfo: (tabId: number, sql: string) =>
       invoke<EditableInfo>("get_editable_info", { tabId, sql }),
@@ -62,7 +170,8 @@ export const api = {
       column: string,
       newValue: string | null,
       pkColumns: PkColumn[],
-    ) => invoke<number>("update_cell", { tabId, schema, table, column, newValue, pkColumns }),
+      confirmation?: string,
+    ) => invoke<number>("update_cell", { tabId, schema, table, column, newValue, pkColumns, confirmation }),
   },
 
   schema: {
@@ -87,11 +196,93 @@ export const api = {
 
     json: (results: QueryResult) => invoke<string>("export_json", { results }),
 
-    rowAsInsert: (table: string, row: string[], columns: string[]) =>
-      invoke<string>("get_row_as_insert", { table, row, columns }),
+    /**
+     * The first `rowCount` rows of `sql` as text (Markdown, HTML, SQL, ...). The rows are
+     * read again, so binary values and NULLs aren't taken from the grid's display text.
+     */
+    results: (tabId: number, sql: string, params: Record<string, ParamValue> | undefined, rowCount: number, format: ExportFormat, options: ExportOptions) =>
+      invoke<string>("export_results", { tabId, sql, params, rowCount, format, options }),
+
+    saveResults: (tabId: number, sql: string, params: Record<string, ParamValue> | undefined, rowCount: number, format: ExportFormat, options: ExportOptions, path: string) =>
+      invoke<void>("save_results", { tabId, sql, params, rowCount, format, options, path }),
+
+    /** INSERT / UPSERT / UPDATE / DELETE statements for result rows, in the tab connection's dialect. */
+    rowStatements: (tabId: number, request: RowStatementRequest) =>
+      invoke<string>("generate_row_statements", { tabId, request }),
 
     saveFile: (path: string, content: string) =>
       invoke<void>("save_file", { path, content }),
+
+    /** Re-run a SELECT and stream all rows to `path`; progress arrives as `export_progress` events. */
+    queryToFile: (tabId: number, sql: string, path: string, format: ExportFormat, options: ExportOptions) =>
+      invoke<ExportSummary>("export_query_to_file", { tabId, sql, path, format, options }),
+  },
+
+  transfer: {
+    /** Copy rows between open connections; progress arrives as `transfer_progress` events. */
+    table: (
+      tabId: number,
+      sourceConnection: string,
+      destConnection: string,
+      source: TransferSource,
+      options: TransferOptions,
+      confirmation?: string,
+    ) =>
+      invoke<TransferSummary>("transfer_table", { tabId, sourceConnection, destConnection, source, options, confirmation }),
+  },
+
+  import: {
+    previewCsv: (path: string, options: CsvOptions) =>
+      invoke<CsvPreview>("preview_csv_import", { path, options }),
+
+    /** Load a CSV/TSV file; progress arrives as `import_progress` events. */
+    csv: (tabId: number, path: string, csvOptions: CsvOptions, options: ImportOptions, confirmation?: string) =>
+      invoke<ImportSummary>("import_csv", { tabId, path, csvOptions, options, confirmation }),
+
+    previewJson: (path: string, options: JsonOptions) =>
+      invoke<JsonPreview>("preview_json_import", { path, options }),
+
+    /** Load a JSON array or NDJSON file; set `options.dry_run` to preview without writing. */
+    json: (tabId: number, path: string, jsonOptions: JsonOptions, options: ImportOptions, confirmation?: string) =>
+      invoke<ImportSummary>("import_json", { tabId, path, jsonOptions, options, confirmation }),
+  },
+
+  savedQueries: {
+    /** Personal queries, then project queries from .dendron/queries/. */
+    list: () => invoke<SavedQueryInfo[]>("list_saved_queries"),
+
+    /**
+     * Create or update a personal query; pass `previousPath` when renaming or moving. Fails if
+     * another query is already at the new path unless `overwrite` is set. Returns the new path.
+     */
+    save: (query: SavedQuery, previousPath?: string, overwrite?: boolean) =>
+      invoke<string>("save_query", { query, previousPath, overwrite }),
+
+    delete: (path: string) => invoke<void>("delete_saved_query", { path }),
+  },
+
+  audit: {
+    /** Audit entries for dangerous connections, newest first. */
+    list: (filter: AuditFilter = {}) =>
+      invoke<AuditEntry[]>("get_audit_log", { filter }),
+
+    /** Write matching entries to `path`; returns the number written. */
+    export: (filter: AuditFilter, format: AuditExportFormat, path: string) =>
+      invoke<number>("export_audit_log", { filter, format, path }),
+  },
+
+  workspace: {
+    /** Tabs restored from the last session, with the last result of each. */
+    get: () => invoke<WorkspaceSnapshot>("get_workspace"),
+
+    /** Record the open tabs; the backend keeps last results and writes to disk once edits pause. */
+    save: (workspace: WorkspaceSnapshot) => invoke<void>("save_workspace", { workspace }),
+
+    /** The project whose .dendron/queries/ are listed, if any. */
+    project: () => invoke<ProjectInfo | null>("get_project"),
+
+    /** Use the project at or above `path` for shared queries, now and at next launch. */
+    openProject: (path: string) => invoke<ProjectInfo>("open_project", { path }),
   },
 
   config: {
@@ -99,5 +290,9 @@ export const api = {
 
     saveSettings: (settings: Settings) =>
       invoke<void>("save_settings", { settings }),
+
+    /** Format with the saved options; `range` is [start, end) string indices. Returns the whole text. */
+    formatSql: (sql: string, range?: [number, number]) =>
+      invoke<string>("format_sql", { sql, range: range ?? null }),
   },
 };
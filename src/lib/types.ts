@@ -11,6 +11,16 @@ export interface QueryResult {
   affected_rows?: number;
 }
 
+/** What a running query reports, as `query_event` events. */
+export type QueryEvent = { query_id: number; tab_id: number } & (
+  | { kind: "started" }
+  | { kind: "progress"; rows_fetched: number }
+  /** The first rows, sent while the rest of the page loads. */
+  | { kind: "first_batch"; columns: string[]; column_types: string[]; rows: string[][] }
+  | { kind: "completed"; result: QueryResult }
+  | { kind: "failed"; error: string }
+);
+
 export interface ColumnInfo {
   name: string;
   data_type: string;
@@ -85,6 +95,10 @@ export interface ConnectionInfo {
   username?: string;
   database?: string;
   is_dangerous: boolean;
+  /** Sessions are opened read-only; the database rejects writes. */
+  read_only?: boolean;
+  /** MCP clients may run statements that change data or schema. */
+  mcp_writes?: boolean;
   // SSH tunnel (Postgres only)
   ssh_enabled?: boolean;
   ssh_host?: string;
@@ -98,6 +112,61 @@ export interface Settings {
   editor_height: number;
   show_tree: boolean;
   theme_name?: string | null;
+  formatter?: SqlFormatOptions;
+}
+
+export interface SqlFormatOptions {
+  indent_width: number;
+  uppercase_keywords: boolean;
+  /** Blank lines between statements. */
+  lines_between_statements: number;
+  max_line_width: number;
+}
+
+/** A statement of the editor script, as split by the backend (UTF-16 offsets). */
+export interface SqlStatement {
+  /** Trimmed SQL text of the statement, without its `;`. */
+  text: string;
+  /** Start of the region the statement owns (inclusive). */
+  from: number;
+  /** End of the region, through its `;` (exclusive). */
+  to: number;
+  start_line: number;
+  end_line: number;
+}
+
+export type LintSeverity = "info" | "warning" | "error";
+
+export type LintRule =
+  | "update_without_where"
+  | "delete_without_where"
+  | "tautological_where"
+  | "select_star_wide_table"
+  | "drop_without_if_exists"
+  | "join_without_condition"
+  | "table_rewrite"
+  | "non_concurrent_index";
+
+export interface LintFinding {
+  rule: LintRule;
+  severity: LintSeverity;
+  message: string;
+  /** Index of the offending statement in the script. */
+  statement: number;
+  start_line: number;
+  end_line: number;
+  /** Statement position in the editor (UTF-16 offsets). */
+  from: number;
+  to: number;
+}
+
+/** Outcome of running an UPDATE/DELETE in a rolled-back transaction. */
+export interface DryRunResult {
+  query_type: string;
+  rows_affected: number;
+  /** First affected rows (Postgres only). */
+  sample?: QueryResult;
+  execution_time_ms: number;
 }
 
 export interface QuerySafetyCheck {
@@ -106,6 +175,12 @@ export interface QuerySafetyCheck {
   connection_name: string;
   requires_confirmation: boolean;
   warning_message?: string;
+  /** Environment policy rules the SQL breaks; the backend refuses to run it. */
+  violations: string[];
+  /** Text to type before running, from the environment policy. */
+  typed_confirmation?: string;
+  /** Most rows an UPDATE/DELETE may touch under the environment policy. */
+  max_affected_rows?: number;
 }
 
 export interface EditableInfo {
@@ -116,11 +191,251 @@ export interface EditableInfo {
   reason?: string;
 }
 
+export type StatementKind = "insert" | "upsert" | "update" | "delete";
+
+export interface RowStatementRequest {
+  kind: StatementKind;
+  schema?: string;
+  table: string;
+  columns: string[];
+  column_types: string[];
+  /** Required for upsert, update and delete. */
+  pk_columns?: string[];
+  /** Display cells; "NULL" means NULL. */
+  rows: string[][];
+}
+
 export interface PkColumn {
   name: string;
   value: string;
 }
 
+export type TransferSource =
+  | { type: "table"; schema: string; table: string }
+  | { type: "query"; sql: string };
+
+export interface TransferOptions {
+  dest_schema?: string | null;
+  dest_table: string;
+  batch_size?: number;
+  create_if_missing?: boolean;
+}
+
+export interface TransferProgress {
+  tab_id: number;
+  rows_copied: number;
+  total_rows: number | null;
+}
+
+export interface TransferSummary {
+  rows_copied: number;
+  created_table: boolean;
+  execution_time_ms: number;
+}
+
+export type ColumnKind =
+  | "small_int" | "integer" | "big_int" | "real" | "double" | "numeric" | "boolean"
+  | "text" | "bytes" | "date" | "time" | "timestamp" | "timestamp_tz" | "uuid" | "json";
+
+export interface CsvOptions {
+  delimiter?: string;
+  has_header?: boolean;
+  quote?: string;
+  encoding?: string | null;
+  null_marker?: string;
+  sample_size?: number;
+}
+
+export interface ImportOptions {
+  schema?: string | null;
+  table: string;
+  create_table?: boolean;
+  /** Source column → table column; empty maps by name. */
+  column_map?: Record<string, string>;
+  batch_size?: number;
+  max_errors?: number;
+  /** Validate and return up to this many would-be rows without writing. */
+  dry_run?: number | null;
+}
+
+export interface RowError {
+  line: number;
+  message: string;
+}
+
+export interface CsvPreview {
+  columns: string[];
+  kinds: ColumnKind[];
+  rows: (string | null)[][];
+  errors: RowError[];
+}
+
+export interface JsonOptions {
+  format?: "auto" | "array" | "ndjson";
+  sample_size?: number;
+}
+
+export interface JsonPreview {
+  columns: string[];
+  kinds: ColumnKind[];
+}
+
+export interface ImportProgress {
+  tab_id: number;
+  rows_imported: number;
+  rows_rejected: number;
+}
+
+export interface ImportSummary {
+  rows_imported: number;
+  created_table: boolean;
+  /** `array`: the target is a Postgres array column, fed JSON arrays or `{a,b}` literals. */
+  columns: { source: string; target: string; kind: ColumnKind; array: boolean }[];
+  errors: RowError[];
+  /** Dry run only: normalized would-be rows. */
+  preview?: (string | null)[][];
+  execution_time_ms: number;
+}
+
+export type ExportFormat =
+  | "csv"
+  | "json"
+  | "ndjson"
+  | "markdown"
+  | "html"
+  | "xlsx"
+  | "sql"
+  | "parquet"
+  | "arrow";
+
+export type ParquetCompression = "none" | "snappy" | "gzip" | "zstd" | "lz4";
+
+export interface ExportOptions {
+  /** Target table of a SQL script. */
+  table?: string;
+  /** Rows per INSERT statement in a SQL script. */
+  insert_batch_size?: number;
+  /** Postgres literals in SQL scripts; set from the tab's connection. */
+  postgres?: boolean;
+  /** Parquet codec; defaults to snappy. */
+  compression?: ParquetCompression;
+  /** Rows per Parquet row group / Arrow record batch. */
+  row_group_size?: number;
+}
+
+export interface ExportProgress {
+  tab_id: number;
+  rows_written: number;
+  bytes_written: number;
+}
+
+export interface ExportSummary {
+  rows_written: number;
+  bytes_written: number;
+  execution_time_ms: number;
+}
+
+export interface SavedQuery {
+  name: string;
+  query: string;
+  connection?: string;
+  /** Slash-separated, e.g. "reports/monthly". */
+  folder?: string;
+  tags?: string[];
+  description?: string;
+  /** Values last used for its parameters, by parameter name. */
+  params?: Record<string, ParamValue>;
+}
+
+/** "personal" queries live in config.toml; "project" ones are .sql files under .dendron/queries/. */
+export type SavedQuerySource = "personal" | "project";
+
+export interface SavedQueryInfo extends SavedQuery {
+  /** "folder/name"; the key for update and delete. */
+  path: string;
+  source: SavedQuerySource;
+  /** File on disk, for project queries. */
+  file?: string;
+}
+
+/** `:name` / `$name`, `$1` / `?1`, bare `?`, or `${var}`. */
+export type ParamKind = "named" | "positional" | "anonymous" | "template";
+
+/** A typed value bound to a parameter. Text and null are sent as text, so typed columns may need a cast in the SQL. */
+export type ParamValue =
+  | { type: "null" }
+  | { type: "bool"; value: boolean }
+  | { type: "int"; value: number }
+  | { type: "float"; value: number }
+  | { type: "text"; value: string };
+
+export interface QueryParam {
+  /** As written, e.g. ":id", "$1", "${env}"; bare "?" become "?1", "?2", ... */
+  name: string;
+  kind: ParamKind;
+  /** [from, to) string indices of each occurrence. */
+  ranges: [number, number][];
+  /** Value last used with the saved query, if any. */
+  value?: ParamValue;
+}
+
+/** A statement run from the app, as recorded in the query history. */
+export interface HistoryEntry {
+  id: number;
+  /** UTC, e.g. "2024-05-01T12:00:00.000Z". */
+  timestamp: string;
+  connection: string | null;
+  tab_id: number | null;
+  sql: string;
+  duration_ms: number;
+  /** Rows returned by a SELECT, or rows affected by anything else. */
+  row_count: number | null;
+  success: boolean;
+  error: string | null;
+}
+
+/** Every set field must match. Dates compare as text, so "2024-05-01" works. */
+export interface HistoryFilter {
+  /** Words the statement must contain; the last may be a prefix. */
+  text?: string;
+  connection?: string;
+  since?: string;
+  until?: string;
+  errors_only?: boolean;
+  limit?: number;
+  offset?: number;
+}
+
+/** A statement run on a dangerous connection, as recorded in the audit log. */
+export interface AuditEntry {
+  /** UTC, e.g. "2024-05-01T12:00:00.000Z". */
+  timestamp: string;
+  connection: string;
+  tags: string[];
+  os_user: string;
+  statement: string;
+  query_type: string;
+  affected_rows?: number;
+  duration_ms: number;
+  success: boolean;
+  error?: string;
+}
+
+/** Every set field must match. Dates compare as text, so "2024-05-01" works. */
+export interface AuditFilter {
+  connection?: string;
+  query_type?: string;
+  since?: string;
+  until?: string;
+  /** Case-insensitive substring of the statement. */
+  text?: string;
+  errors_only?: boolean;
+  /** Keep only the newest matches. */
+  limit?: number;
+}
+
+export type AuditExportFormat = "ndjson" | "csv";
+
 /** One statement's result within a multi-statement batch. */
 export interface StatementResult {
   /** 1-based index within the batch. */
@@ -148,4 +463,51 @@ export interface Tab {
   results: StatementResult[] | null;
   /** Which sub-tab is active (0-based index into results). */
   activeResultIndex: number;
+  /** Saved query the tab was opened from (its path, or file for project queries); parameter values are remembered against it. */
+  savedQuery?: string | null;
+  /** Parameter values of the last run, reused when loading more rows. */
+  runParams?: Record<string, ParamValue>;
+  /** Summary of the run before the app was restarted, shown until the tab runs again. */
+  lastResult?: ResultSummary | null;
+}
+
+// Workspace (persisted tabs)
+
+/** Editor selection, as string indices into the tab's SQL. */
+export interface CursorPosition {
+  anchor: number;
+  head: number;
+}
+
+export interface ResultSummary {
+  ran_at: string;
+  columns: string[];
+  row_count: number;
+  truncated: boolean;
+  affected_rows?: number | null;
+  execution_time_ms: number;
+  error?: string | null;
+}
+
+export interface TabSnapshot {
+  id: number;
+  label: string;
+  sql: string;
+  cursor?: CursorPosition | null;
+  connection_name?: string | null;
+  saved_query?: string | null;
+  /** Recorded by the backend; left out when saving. */
+  last_result?: ResultSummary | null;
+}
+
+export interface WorkspaceSnapshot {
+  tabs: TabSnapshot[];
+  active_tab: number | null;
+  /** Project last opened; set through `workspace.openProject`. */
+  project_root?: string | null;
+}
+
+export interface ProjectInfo {
+  name: string;
+  root: string;
 }
@@ -4,7 +4,7 @@ import { useQueryClient } from "@tanstack/react-query";
 import { HotkeysProvider, useHotkey } from "@tanstack/react-hotkeys";
 import { Group as PanelGroup, Panel, Separator as PanelResizeHandle, useDefaultLayout } from "react-resizable-panels";
 import { WorkspaceContext, type WorkspaceContextValue } from "@/lib/WorkspaceContext";
-import type { Tab, EditableInfo, ConnectionInfo, ConnectionEnvironment, StatementResult } from "@/lib/types";
+import type { Tab, EditableInfo, ConnectionInfo, ConnectionEnvironment, CursorPosition, StatementResult, LintFinding, ParamValue, QueryParam, QueryEvent, QueryResult } from "@/lib/types";
 import { envFromTags } from "@/lib/types";
 import { deriveStatementLabel } from "@/lib/sql-utils";
 import { api } from "@/lib/tauri";
@@ -17,8 +17,8 @@ import { ConnectionDialog } from "@/components/ConnectionDialog";
 import { CommandPalette } from "@/components/CommandPalette";
 import { DangerConfirmDialog, type DangerConfirmRequest } from "@/components/DangerConfirmDialog";
 import { ShortcutsDialog } from "@/components/ShortcutsDialog";
+import { ParamsDialog } from "@/components/ParamsDialog";
 
-const TABS_STORAGE_KEY = "dendron-tabs";
 const DEFAULT_TAB: Tab = {
   id: 1,
   label: "Query 1",
@@ -32,52 +32,67 @@ const DEFAULT_TAB: Tab = {
   activeResultIndex: 0,
 };
 
-function restoreTabs(): { tabs: Tab[]; activeTabId: number; nextId: number } {
-  try {
-    const raw = localStorage.getItem(TABS_STORAGE_KEY);
-    if (!raw) return { tabs: [DEFAULT_TAB], activeTabId: 1, nextId: 2 };
-    const saved = JSON.parse(raw) as { tabs: Array<{ id: number; label: string; sql: string; connectionName: string | null; connectionEnv: string | null }>; activeTabId: number };
-    if (!Array.isArray(saved.tabs) || saved.tabs.length === 0) {
-      return { tabs: [DEFAULT_TAB], activeTabId: 1, nextId: 2 };
-    }
-    const tabs: Tab[] = saved.tabs.map((t) => ({
-      id: t.id,
-      label: t.label,
-      sql: t.sql,
-      connectionName: t.connectionName,
-      connectionEnv: (t.connectionEnv as Tab["connectionEnv"]) ?? null,
-      result: null,
-      error: null,
-      isRunning: false,
-      results: null,
-      activeResultIndex: 0,
-    }));
-    const maxId = Math.max(...tabs.map((t) => t.id));
-    const activeTabId = tabs.some((t) => t.id === saved.activeTabId)
-      ? saved.activeTabId
-      : tabs[0].id;
-    return { tabs, activeTabId, nextId: maxId + 1 };
-  } catch {
-    return { tabs: [DEFAULT_TAB], activeTabId: 1, nextId: 2 };
-  }
+let nextId = 2;
+
+/** Go-ahead for a risky run: what was typed, when the policy asked for the database name. */
+interface Confirmed {
+  confirmation?: string;
 }
 
-const _restored = restoreTabs();
-let nextId = _restored.nextId;
+/**
+ * Query event listener that shows a running SELECT's first rows in the tab,
+ * with the count read so far, until the finished page replaces them.
+ */
+function showPartialRows(tabId: number, updateTab: (id: number, updates: Partial<Tab>) => void) {
+  let partial: QueryResult | null = null;
+  return (event: QueryEvent) => {
+    if (event.kind === "first_batch") {
+      partial = {
+        columns: event.columns,
+        column_types: event.column_types,
+        rows: event.rows,
+        row_count: event.rows.length,
+        execution_time_ms: 0,
+        truncated: false,
+        has_order_by: true,
+      };
+    } else if (event.kind === "progress" && partial) {
+      partial = { ...partial, row_count: event.rows_fetched };
+    } else {
+      return;
+    }
+    updateTab(tabId, { result: partial });
+  };
+}
 
 function RootLayout() {
-  const [tabs, setTabs] = useState<Tab[]>(_restored.tabs);
-  const [activeTabId, setActiveTabId] = useState(_restored.activeTabId);
+  const [tabs, setTabs] = useState<Tab[]>([DEFAULT_TAB]);
+  const [activeTabId, setActiveTabId] = useState(DEFAULT_TAB.id);
   const [connectionDialogState, setConnectionDialogState] = useState<{ open: boolean; editing?: ConnectionInfo }>({ open: false });
   const [showCommandPalette, setShowCommandPalette] = useState(false);
   const [showShortcuts, setShowShortcuts] = useState(false);
   const [openConnections, setOpenConnections] = useState<string[]>([]);
   const [dangerConfirm, setDangerConfirm] = useState<{
     request: DangerConfirmRequest;
-    resolve: (confirmed: boolean) => void;
+    resolve: (typed: string | null) => void;
   } | null>(null);
+  const [paramsPrompt, setParamsPrompt] = useState<{
+    params: QueryParam[];
+    resolve: (values: Record<string, ParamValue> | null) => void;
+  } | null>(null);
+  /** Parameter values used this session, to prefill the next prompt. */
+  const lastParams = useRef<Record<string, ParamValue>>({});
   const editorRef = useRef<QueryEditorHandle | null>(null);
   const queryClient = useQueryClient();
+  /** Editor selections by tab, saved with the workspace without re-rendering. */
+  const cursors = useRef<Record<number, CursorPosition>>({});
+  /** Nothing is saved until the previous workspace has been restored. */
+  const workspaceLoaded = useRef(false);
+  const workspaceSaveTimer = useRef<ReturnType<typeof setTimeout>>(undefined);
+  const tabsRef = useRef(tabs);
+  tabsRef.current = tabs;
+  const activeTabIdRef = useRef(activeTabId);
+  activeTabIdRef.current = activeTabId;
 
   // Persist panel layouts to localStorage
   const mainLayout = useDefaultLayout({ id: "dendron-main", storage: localStorage });
@@ -92,13 +107,91 @@ function RootLayout() {
     return conn ? envFromTags(conn.tags) : null;
   }, [queryClient]);
 
-  /** Show a danger confirmation dialog and return whether the user confirmed. */
-  const showDangerConfirm = useCallback((request: DangerConfirmRequest): Promise<boolean> => {
+  /** Show a danger confirmation dialog; resolves to the text typed, or null if cancelled. */
+  const showDangerConfirm = useCallback((request: DangerConfirmRequest): Promise<string | null> => {
     return new Promise((resolve) => {
       setDangerConfirm({ request, resolve });
     });
   }, []);
 
+  /**
+   * Ask for values when the SQL has placeholders. Resolves to the values
+   * (empty when there are none), or null if the user cancelled. Values are
+   * remembered against the tab's saved query.
+   */
+  const promptParams = useCallback(async (tab: Tab, sql: string): Promise<Record<string, ParamValue> | null> => {
+    const params = await api.queries.getParams(tab.id, sql, tab.savedQuery).catch((): QueryParam[] => []);
+    if (params.length === 0) return {};
+    const values = await new Promise<Record<string, ParamValue> | null>((resolve) => {
+      setParamsPrompt({ params, resolve });
+    });
+    if (values) {
+      lastParams.current = { ...lastParams.current, ...values };
+      if (tab.savedQuery) api.queries.rememberParams(tab.savedQuery, values).catch(() => {});
+    }
+    return values;
+  }, []);
+
+  /**
+   * Run the safety check and linter on SQL about to execute, and ask for
+   * confirmation when the connection is dangerous or a lint error was found.
+   * Resolves to null to stop, or the confirmation to send with the run.
+   */
+  const confirmRisky = useCallback(async (
+    tabId: number,
+    sql: string,
+    fallbackMessage: string,
+    params?: Record<string, ParamValue>,
+  ): Promise<Confirmed | null> => {
+    const [safety, findings] = await Promise.all([
+      api.queries.checkSafety(sql, tabId).catch(() => null),
+      api.queries.lint(tabId, sql).catch((): LintFinding[] => []),
+    ]);
+    const hasError = findings.some((f) => f.severity === "error");
+    // Policy violations are refused by the backend with its own message.
+    if (safety?.violations.length) return {};
+    // If the checks fail, proceed anyway
+    if (!safety?.requires_confirmation && !hasError) return {};
+
+    let message = safety?.warning_message
+      ?? (hasError ? "This query looks like a mistake." : fallbackMessage);
+    const queryType = safety?.query_type?.toLowerCase() ?? "";
+
+    // Count the rows an UPDATE/DELETE would touch; skipped for batches and
+    // when the dry run itself fails.
+    if (queryType === "update" || queryType === "delete") {
+      const preview = await api.queries.dryRun(tabId, sql, 0, params).catch(() => null);
+      if (preview) {
+        const n = preview.rows_affected;
+        const verb = queryType === "delete" ? "delete" : "update";
+        message = `This will ${verb} ${n.toLocaleString()} row${n !== 1 ? "s" : ""}.\n\n${message}`;
+      }
+    }
+    const needsTyped = safety?.typed_confirmation
+      ?? (safety?.requires_confirmation && (queryType === "drop" || queryType === "truncate")
+        ? safety.connection_name
+        : undefined);
+    const typed = await showDangerConfirm({
+      message,
+      requireTypedConfirmation: needsTyped,
+      findings: findings.filter((f) => f.severity !== "info"),
+    });
+    return typed === null ? null : { confirmation: typed || undefined };
+  }, [showDangerConfirm]);
+
+  /**
+   * Ask for the database name before a write without SQL text, such as
+   * committing inline edits, when the environment policy wants it.
+   * Resolves like `confirmRisky`.
+   */
+  const confirmWrite = useCallback(async (tabId: number, queryType: string, message: string): Promise<Confirmed | null> => {
+    const safety = await api.queries.checkWriteSafety(tabId, queryType).catch(() => null);
+    // Violations are refused by the backend; other writes go ahead as before.
+    if (!safety?.typed_confirmation || safety.violations.length) return {};
+    const typed = await showDangerConfirm({ message, requireTypedConfirmation: safety.typed_confirmation });
+    return typed === null ? null : { confirmation: typed };
+  }, [showDangerConfirm]);
+
   const updateTab = useCallback((id: number, updates: Partial<Tab>) => {
     setTabs((prev) => prev.map((t) => (t.id === id ? { ...t, ...updates } : t)));
   }, []);
@@ -155,6 +248,24 @@ function RootLayout() {
     });
   }, []);
 
+  /**
+   * Open the tab's connection if it isn't yet, as for tabs restored from the
+   * last session. Reports failure on the tab and resolves to false.
+   */
+  const ensureTabConnection = useCallback(async (tab: Tab): Promise<boolean> => {
+    const name = tab.connectionName;
+    if (!name || openConnections.includes(name)) return true;
+    try {
+      await api.connections.open(name);
+      setOpenConnections((prev) => prev.includes(name) ? prev : [...prev, name]);
+      return true;
+    } catch (e) {
+      const msg = e instanceof Error ? e.message : String(e);
+      updateTab(tab.id, { error: msg });
+      return false;
+    }
+  }, [openConnections, updateTab]);
+
   /**
    * Smart run: if text is selected run the selection, otherwise run the
    * statement under the cursor.  Falls back to the full editor content when
@@ -170,6 +281,7 @@ function RootLayout() {
       });
       return;
     }
+    if (!(await ensureTabConnection(tab))) return;
 
     // Determine what SQL to run: selection > cursor statement > full text
     const editor = editorRef.current;
@@ -180,7 +292,7 @@ function RootLayout() {
       if (selected) {
         sqlToRun = selected;
       } else {
-        const stmt = editor.getStatementAtCursor();
+        const stmt = await editor.getStatementAtCursor().catch(() => null);
         if (stmt) {
           sqlToRun = stmt.text;
         }
@@ -189,27 +301,16 @@ function RootLayout() {
 
     if (!sqlToRun) return;
 
-    // Safety check
-    try {
-      const safety = await api.queries.checkSafety(sqlToRun, tab.id);
-      if (safety.requires_confirmation) {
-        const msg = safety.warning_message ?? "This query may modify or delete data.";
-        const queryType = safety.query_type?.toLowerCase() ?? "";
-        const needsTyped = (queryType === "drop" || queryType === "truncate")
-          ? safety.connection_name
-          : undefined;
-        const confirmed = await showDangerConfirm({ message: msg, requireTypedConfirmation: needsTyped });
-        if (!confirmed) return;
-      }
-    } catch {
-      // If safety check fails, proceed anyway
-    }
+    const params = await promptParams(tab, sqlToRun);
+    if (!params) return;
 
-    updateTab(tab.id, { isRunning: true, error: null, result: null, editableInfo: null, results: null, activeResultIndex: 0 });
+    const confirmed = await confirmRisky(tab.id, sqlToRun, "This query may modify or delete data.", params);
+    if (!confirmed) return;
+
+    updateTab(tab.id, { isRunning: true, error: null, result: null, editableInfo: null, results: null, activeResultIndex: 0, runParams: params });
 
     try {
-      const result = await api.queries.execute(tab.id, sqlToRun);
-      await api.queries.addHistory(sqlToRun).catch(() => {});
+      const result = await api.queries.execute(tab.id, sqlToRun, undefined, params, confirmed.confirmation, showPartialRows(tab.id, updateTab));
       let editableInfo: EditableInfo | null = null;
       if (result.columns.length > 0) {
         try { editableInfo = await api.queries.getEditableInfo(tab.id, sqlToRun); } catch {}
@@ -219,7 +320,7 @@ function RootLayout() {
       const msg = e instanceof Error ? e.message : String(e);
       updateTab(tab.id, { error: msg, isRunning: false });
     }
-  }, [tabs, activeTabId, updateTab, showDangerConfirm]);
+  }, [tabs, activeTabId, updateTab, confirmRisky, promptParams, ensureTabConnection]);
 
   /**
    * Run all statements in the editor sequentially.  Collects every result
@@ -237,34 +338,26 @@ function RootLayout() {
       });
       return;
     }
+    if (!(await ensureTabConnection(tab))) return;
 
     const editor = editorRef.current;
-    const statements = editor?.getAllStatements() ?? [];
+    const statements = (await editor?.getAllStatements().catch(() => [])) ?? [];
     if (statements.length === 0) return;
 
+    // One prompt for the whole batch; each statement binds the names it uses.
+    const params = await promptParams(tab, tab.sql);
+    if (!params) return;
+
     // Safety check against the full SQL
-    try {
-      const safety = await api.queries.checkSafety(tab.sql, tab.id);
-      if (safety.requires_confirmation) {
-        const msg = safety.warning_message ?? "This batch may modify or delete data.";
-        const queryType = safety.query_type?.toLowerCase() ?? "";
-        const needsTyped = (queryType === "drop" || queryType === "truncate")
-          ? safety.connection_name
-          : undefined;
-        const confirmed = await showDangerConfirm({ message: msg, requireTypedConfirmation: needsTyped });
-        if (!confirmed) return;
-      }
-    } catch {
-      // If safety check fails, proceed anyway
-    }
+    const confirmed = await confirmRisky(tab.id, tab.sql, "This batch may modify or delete data.", params);
+    if (!confirmed) return;
 
-    updateTab(tab.id, { isRunning: true, error: null, result: null, editableInfo: null, results: null, activeResultIndex: 0 });
+    updateTab(tab.id, { isRunning: true, error: null, result: null, editableInfo: null, results: null, activeResultIndex: 0, runParams: params });
 
     // Single statement — use existing single-result mode (no sub-tabs)
     if (statements.length === 1) {
       try {
-        const result = await api.queries.execute(tab.id, statements[0].text);
-        await api.queries.addHistory(tab.sql).catch(() => {});
+        const result = await api.queries.execute(tab.id, statements[0].text, undefined, params, confirmed.confirmation, showPartialRows(tab.id, updateTab));
         let editableInfo: EditableInfo | null = null;
         if (result.columns.length > 0) {
           try { editableInfo = await api.queries.getEditableInfo(tab.id, statements[0].text); } catch {}
@@ -284,7 +377,7 @@ function RootLayout() {
     try {
       for (let i = 0; i < statements.length; i++) {
         const stmt = statements[i];
-        const result = await api.queries.execute(tab.id, stmt.text);
+        const result = await api.queries.execute(tab.id, stmt.text, undefined, params, confirmed.confirmation);
         let editableInfo: EditableInfo | null = null;
         if (result.columns.length > 0) {
           lastSelectIdx = i;
@@ -298,7 +391,6 @@ function RootLayout() {
           editableInfo,
         });
       }
-      await api.queries.addHistory(tab.sql).catch(() => {});
       // Auto-focus the last SELECT result, or the last result if no SELECTs
       const focusIdx = lastSelectIdx >= 0 ? lastSelectIdx : collected.length - 1;
       updateTab(tab.id, { results: collected, activeResultIndex: focusIdx, isRunning: false });
@@ -312,7 +404,7 @@ function RootLayout() {
         updateTab(tab.id, { error: msg, isRunning: false });
       }
     }
-  }, [tabs, activeTabId, updateTab, showDangerConfirm]);
+  }, [tabs, activeTabId, updateTab, confirmRisky, promptParams, ensureTabConnection]);
 
   const loadMoreQuery = useCallback(async () => {
     const tab = tabs.find((t) => t.id === activeTabId);
@@ -324,7 +416,7 @@ function RootLayout() {
       if (!active || !active.result.truncated) return;
       const offset = active.result.rows.length;
       try {
-        const page = await api.queries.execute(tab.id, active.sql, offset);
+        const page = await api.queries.execute(tab.id, active.sql, offset, tab.runParams);
         const updatedResults = tab.results.map((sr, i) =>
           i === tab.activeResultIndex
             ? {
@@ -353,7 +445,7 @@ function RootLayout() {
     if (!tab.result?.truncated) return;
     const offset = tab.result.rows.length;
     try {
-      const page = await api.queries.execute(tab.id, tab.sql, offset);
+      const page = await api.queries.execute(tab.id, tab.sql, offset, tab.runParams);
       updateTab(tab.id, {
         result: {
           ...page,
@@ -406,7 +498,6 @@ function RootLayout() {
     if (autoRun && sql.trim()) {
       try {
         const result = await api.queries.execute(id, sql);
-        await api.queries.addHistory(sql).catch(() => {});
         let editableInfo: EditableInfo | null = null;
         if (result.columns.length > 0) {
           try { editableInfo = await api.queries.getEditableInfo(id, sql); } catch {}
@@ -436,16 +527,57 @@ function RootLayout() {
     api.connections.listOpen().then(setOpenConnections).catch(() => {});
   }, []);
 
-  // Persist tabs to localStorage (debounced)
+  // Restore the previous session's tabs. Their connections are opened again
+  // by the backend the first time each tab runs something.
   useEffect(() => {
-    const timer = setTimeout(() => {
-      const serializable = tabs.map(({ id, label, sql, connectionName, connectionEnv }) => ({
-        id, label, sql, connectionName, connectionEnv,
-      }));
-      localStorage.setItem(TABS_STORAGE_KEY, JSON.stringify({ tabs: serializable, activeTabId }));
+    Promise.all([
+      api.workspace.get(),
+      queryClient.ensureQueryData({ queryKey: ["connections"], queryFn: api.connections.list }).catch((): ConnectionInfo[] => []),
+    ]).then(([workspace, conns]) => {
+      if (workspace.tabs.length > 0) {
+        const restored: Tab[] = workspace.tabs.map((t) => {
+          if (t.cursor) cursors.current[t.id] = t.cursor;
+          const conn = conns.find((c) => c.name === t.connection_name);
+          return {
+            ...DEFAULT_TAB,
+            id: t.id,
+            label: t.label,
+            sql: t.sql,
+            connectionName: t.connection_name ?? null,
+            connectionEnv: conn ? envFromTags(conn.tags) : null,
+            savedQuery: t.saved_query ?? null,
+            lastResult: t.last_result ?? null,
+          };
+        });
+        nextId = Math.max(...restored.map((t) => t.id)) + 1;
+        setTabs(restored);
+        setActiveTabId(workspace.active_tab ?? restored[0].id);
+      }
+    }).catch(() => {}).finally(() => {
+      workspaceLoaded.current = true;
+    });
+  }, [queryClient]);
+
+  /** Send the tabs to the backend once edits pause; it writes them to disk. */
+  const scheduleWorkspaceSave = useCallback(() => {
+    if (!workspaceLoaded.current) return;
+    clearTimeout(workspaceSaveTimer.current);
+    workspaceSaveTimer.current = setTimeout(() => {
+      api.workspace.save({
+        tabs: tabsRef.current.map((t) => ({
+          id: t.id,
+          label: t.label,
+          sql: t.sql,
+          cursor: cursors.current[t.id] ?? null,
+          connection_name: t.connectionName,
+          saved_query: t.savedQuery ?? null,
+        })),
+        active_tab: activeTabIdRef.current,
+      }).catch(() => {});
     }, 500);
-    return () => clearTimeout(timer);
-  }, [tabs, activeTabId]);
+  }, []);
+
+  useEffect(scheduleWorkspaceSave, [tabs, activeTabId, scheduleWorkspaceSave]);
 
   // ── Global keyboard shortcuts (TanStack Hotkeys) ──────────────────────
 
@@ -513,6 +645,7 @@ function RootLayout() {
     runAllQueries,
     loadMoreQuery,
     cancelActiveQuery,
+    confirmWrite,
     insertSql,
     openSqlInNewTab,
     showConnectionDialog: connectionDialogState.open,
@@ -560,6 +693,11 @@ function RootLayout() {
                     tabId={activeTab.id}
                     defaultValue={activeTab.sql}
                     onValueChange={(sql) => updateTab(activeTab.id, { sql })}
+                    initialCursor={cursors.current[activeTab.id]}
+                    onCursorChange={(cursor) => {
+                      cursors.current[activeTab.id] = cursor;
+                      scheduleWorkspaceSave();
+                    }}
                     onRun={runActiveQuery}
                     onRunAll={runAllQueries}
                     onCancel={cancelActiveQuery}
@@ -587,6 +725,7 @@ function RootLayout() {
                     results={activeTab.results}
                     activeResultIndex={activeTab.activeResultIndex}
                     onActiveResultChange={(idx) => updateTab(activeTab.id, { activeResultIndex: idx })}
+                    lastResult={activeTab.lastResult}
                   />
                 </Panel>
               </PanelGroup>
@@ -611,17 +750,33 @@ function RootLayout() {
       {dangerConfirm && (
         <DangerConfirmDialog
           request={dangerConfirm.request}
-          onConfirm={() => {
-            dangerConfirm.resolve(true);
+          onConfirm={(typed) => {
+            dangerConfirm.resolve(typed);
             setDangerConfirm(null);
           }}
           onCancel={() => {
-            dangerConfirm.resolve(false);
+            dangerConfirm.resolve(null);
             setDangerConfirm(null);
           }}
         />
       )}
 
+      {/* Query parameter prompt */}
+      {paramsPrompt && (
+        <ParamsDialog
+          params={paramsPrompt.params}
+          previous={lastParams.current}
+          onSubmit={(values) => {
+            paramsPrompt.resolve(values);
+            setParamsPrompt(null);
+          }}
+          onCancel={() => {
+            paramsPrompt.resolve(null);
+            setParamsPrompt(null);
+          }}
+        />
+      )}
+
       {/* TanStack Router child outlet */}
       <Outlet />
     </WorkspaceContext.Provider>